@@ -1,32 +1,67 @@
 use arrow::{
-    array::RecordBatch,
-    util::display::{ArrayFormatter, FormatOptions},
+    array::{RecordBatch, RecordBatchIterator, RecordBatchReader},
+    datatypes::SchemaRef,
 };
 use parquet::{
-    arrow::arrow_reader::{
-        ArrowReaderMetadata, ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder,
-    },
-    file::metadata::{ParquetMetaDataReader, RowGroupMetaData},
+    file::metadata::{ParquetMetaData, RowGroupMetaData},
+    schema::types::Type,
 };
-use ratatui::widgets::Row;
 
-use crate::error::Result;
-use std::{fs::File, sync::Arc};
+use crate::{
+    delta::DeltaTable,
+    encryption::Encryption,
+    error::Result,
+    iceberg::IcebergTable,
+    partition::Partitioning,
+    salvage::Salvage,
+    source::{Format, Metadata, Source},
+};
+use std::sync::Arc;
+
+const BATCH_SIZE: usize = 64;
+
+/// Column headers of [`Viewer::schema_table_data`].
+pub const SCHEMA_HEADERS: [&str; 11] = [
+    "Name",
+    "Column type",
+    "Logical type",
+    "Converted type",
+    "Physical type",
+    "Type length",
+    "Scale",
+    "Precision",
+    "Sort order",
+    "Repetition",
+    "Field ID",
+];
 
 pub struct Viewer {
+    pub format: Format,
+    /// Schema of the source, which the Data tab may have swapped for query results.
+    pub schema: SchemaRef,
+    /// Properties of formats other than Parquet, e.g. the CSV delimiter.
+    pub format_details: Vec<(String, String)>,
     pub version: String,
-    pub num_rows: i64,
+    /// Only known up front for Parquet.
+    pub num_rows: Option<i64>,
     pub num_cols: usize,
     pub num_row_groups: usize,
     pub created_by: String,
     pub file_kv_data: Vec<(String, String)>,
-    pub schema_table_data: Vec<[String; 9]>,
+    pub schema_table_data: Vec<[String; 11]>,
     pub max_col_name_width: usize,
     // parquet_metadata: Arc<ParquetMetaData>,
     pub file_stem: String,
     pub row_groups: Vec<RowGroupMetaData>,
-    pub reader: ParquetRecordBatchReader,
+    /// Set for files written with Parquet modular encryption.
+    pub encryption: Option<Encryption>,
+    pub reader: Box<dyn RecordBatchReader + Send>,
     pub batch: RecordBatch,
+    /// Number of rows read before `batch`.
+    pub first_row: usize,
+    /// Rows the Data tab shows, when known without reading them all or once
+    /// they have been.
+    pub num_data_rows: Option<usize>,
     // pub batch_table:
     pub batch_size: usize,
     pub selected_row: usize,
@@ -35,12 +70,178 @@ pub struct Viewer {
     pub visible_rows: usize,
     pub col_offset: usize,
     pub visible_cols: usize,
+    pub source: Source,
 }
 
 impl Viewer {
-    pub fn new(file: File, name: Option<String>) -> Result<Self> {
-        let parquet_metadata = ParquetMetaDataReader::new().parse_and_finish(&file)?;
-        let parquet_metadata = Arc::new(parquet_metadata);
+    /// Opens `source`, showing its footer in the Metadata tab and its rows
+    /// in the Data tab.
+    pub fn new(source: Source, name: Option<String>) -> Result<Self> {
+        let (metadata, mut reader) = source.reader(None, BATCH_SIZE)?;
+        let schema = reader.schema();
+        // Files without rows open with an empty Data tab.
+        let batch = match reader.next() {
+            Some(batch) => batch?,
+            None => RecordBatch::new_empty(Arc::clone(&schema)),
+        };
+
+        let mut viewer = Self {
+            format: source.format(),
+            num_cols: schema.fields().len(),
+            schema,
+            format_details: vec![],
+            version: "".to_string(),
+            num_rows: None,
+            num_row_groups: 0,
+            created_by: "".to_string(),
+            file_kv_data: vec![],
+            schema_table_data: vec![],
+            max_col_name_width: 0,
+            row_groups: vec![],
+            encryption: None,
+            file_stem: name.unwrap_or("no name".to_string()),
+            reader,
+            batch,
+            first_row: 0,
+            num_data_rows: None,
+            batch_size: BATCH_SIZE,
+            selected_row: 0,
+            selected_col: 0,
+            row_offset: 0,
+            visible_rows: 50,
+            col_offset: 0,
+            visible_cols: 10,
+            source,
+        };
+        match metadata {
+            Metadata::Parquet(parquet_metadata) => viewer.set_parquet_metadata(&parquet_metadata),
+            Metadata::Encrypted(parquet_metadata, encryption) => {
+                viewer.set_parquet_metadata(&parquet_metadata);
+                viewer.encryption = Some(encryption);
+            }
+            Metadata::Other(details) => {
+                viewer.format_details = details;
+                viewer.file_kv_data = viewer
+                    .schema
+                    .metadata()
+                    .iter()
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect();
+                viewer.file_kv_data.sort();
+                viewer.max_col_name_width = viewer
+                    .schema
+                    .fields()
+                    .iter()
+                    .map(|f| f.name().len())
+                    .max()
+                    .unwrap_or(0);
+            }
+        }
+        viewer.num_data_rows = viewer.num_source_rows();
+        Ok(viewer)
+    }
+
+    /// Rows of the source, from its metadata.
+    pub fn num_source_rows(&self) -> Option<usize> {
+        match self.partitions() {
+            Some(partitioning) => Some(partitioning.num_rows() as usize),
+            None => self.num_rows.map(|n| n as usize),
+        }
+    }
+
+    /// Index of the row group holding the `row`th row of the source, for
+    /// single Parquet files.
+    pub fn row_group(&self, row: usize) -> Option<usize> {
+        if self.partitions().is_some() {
+            return None;
+        }
+        let mut end = 0;
+        self.row_groups.iter().position(|rg| {
+            end += rg.num_rows() as usize;
+            row < end
+        })
+    }
+
+    /// Set when viewing a partitioned directory rather than a single file.
+    pub fn partitions(&self) -> Option<&Partitioning> {
+        self.source.partitions()
+    }
+
+    /// Set when viewing a Delta table.
+    pub fn delta(&self) -> Option<&DeltaTable> {
+        self.source.delta()
+    }
+
+    /// Set when viewing an Iceberg table.
+    pub fn iceberg(&self) -> Option<&IcebergTable> {
+        self.source.iceberg()
+    }
+
+    /// Set when viewing a damaged file with `--salvage`.
+    pub fn salvage(&self) -> Option<&Salvage> {
+        self.source.salvage()
+    }
+
+    /// Shows the batches of `reader`, e.g. query results, in the Data tab
+    /// instead of the file contents.
+    pub fn set_data(&mut self, mut reader: Box<dyn RecordBatchReader + Send>) -> Result<()> {
+        self.batch = match reader.next() {
+            Some(batch) => batch?,
+            None => RecordBatch::new_empty(reader.schema()),
+        };
+        self.reader = reader;
+        self.first_row = 0;
+        self.num_data_rows = None;
+        self.selected_row = 0;
+        self.selected_col = 0;
+        self.row_offset = 0;
+        self.col_offset = 0;
+        Ok(())
+    }
+
+    /// Shows the rows of `reader`, which starts at row `first_row`, keeping
+    /// the column selected.
+    pub fn seek(
+        &mut self,
+        mut reader: Box<dyn RecordBatchReader + Send>,
+        first_row: usize,
+    ) -> Result<()> {
+        self.batch = match reader.next() {
+            Some(batch) => batch?,
+            None => RecordBatch::new_empty(reader.schema()),
+        };
+        self.reader = reader;
+        self.first_row = first_row;
+        self.selected_row = 0;
+        self.row_offset = 0;
+        Ok(())
+    }
+
+    /// Moves on to the next batch, unless the reader has run out. Errors,
+    /// e.g. a corrupt page past which the reader can't carry on, end the
+    /// rows rather than closing the file.
+    pub fn next_batch(&mut self) -> Result<bool> {
+        match self.reader.next() {
+            Some(Ok(batch)) => {
+                self.first_row += self.batch.num_rows();
+                self.batch = batch;
+                self.selected_row = 0;
+                self.row_offset = 0;
+                Ok(true)
+            }
+            Some(Err(e)) => {
+                let schema = self.reader.schema();
+                self.reader = Box::new(RecordBatchIterator::new(vec![], schema));
+                Err(e.into())
+            }
+            None => {
+                self.num_data_rows = Some(self.first_row + self.batch.num_rows());
+                Ok(false)
+            }
+        }
+    }
+
+    fn set_parquet_metadata(&mut self, parquet_metadata: &ParquetMetaData) {
         let version = parquet_metadata.file_metadata().version().to_string();
         let num_rows = parquet_metadata.file_metadata().num_rows();
         let num_cols = parquet_metadata
@@ -68,89 +269,76 @@ impl Viewer {
             .columns()
             .iter()
         {
-            let name = c.name().to_string();
+            let name = c.path().string();
             max_col_name_width = max_col_name_width.max(name.len());
-            let sort_order = c.sort_order().to_string();
-            let row = match c.self_type() {
-                parquet::schema::types::Type::PrimitiveType {
-                    basic_info,
-                    physical_type,
-                    type_length,
-                    scale,
-                    precision,
-                } => {
-                    let ctype = basic_info.converted_type().to_string();
-                    let ltype = basic_info
-                        .logical_type()
-                        .map_or("".to_string(), |t| format!("{t:?}"));
-                    let ptype = physical_type.to_string();
-                    [
-                        name,
-                        "primitive".to_string(),
-                        ltype,
-                        ctype,
-                        ptype,
-                        type_length.to_string(),
-                        scale.to_string(),
-                        precision.to_string(),
-                        sort_order,
-                    ]
-                }
-                parquet::schema::types::Type::GroupType { basic_info, .. } => {
-                    let ctype = basic_info.converted_type().to_string();
-                    let ltype = basic_info
-                        .logical_type()
-                        .map_or("".to_string(), |t| format!("{t:?}"));
-                    [
-                        name,
-                        "group".to_string(),
-                        ltype,
-                        ctype,
-                        "".to_string(),
-                        "".to_string(),
-                        "".to_string(),
-                        "".to_string(),
-                        sort_order,
-                    ]
-                }
-            };
+            let row = schema_row(name, c.self_type(), c.sort_order().to_string());
             schema_table_data.push(row);
         }
 
-        let file_stem = name.unwrap_or("no name".to_string());
-
-        let batch_size = 64;
-        let arrow_metadata =
-            ArrowReaderMetadata::try_new(Arc::clone(&parquet_metadata), Default::default())?;
-        let builder = ParquetRecordBatchReaderBuilder::new_with_metadata(file, arrow_metadata)
-            .with_batch_size(batch_size);
-        let mut reader = builder.build()?;
-
         // TODO: consider keeping only the necessary data
         let row_groups = parquet_metadata.row_groups().to_vec();
 
-        let batch = reader.next().unwrap()?;
-
-        Ok(Self {
-            version,
-            num_rows,
-            num_cols,
-            num_row_groups,
-            created_by,
-            file_kv_data,
-            schema_table_data,
-            max_col_name_width,
-            row_groups,
-            file_stem,
-            reader,
-            batch,
-            batch_size,
-            selected_row: 0,
-            selected_col: 0,
-            row_offset: 0,
-            visible_rows: 50,
-            col_offset: 0,
-            visible_cols: 10,
-        })
+        self.version = version;
+        self.num_rows = Some(num_rows);
+        self.num_cols = num_cols;
+        self.num_row_groups = num_row_groups;
+        self.created_by = created_by;
+        self.file_kv_data = file_kv_data;
+        self.schema_table_data = schema_table_data;
+        self.max_col_name_width = max_col_name_width;
+        self.row_groups = row_groups;
+    }
+}
+
+/// Row of [`Viewer::schema_table_data`] for the schema node `ty` at `path`.
+pub(crate) fn schema_row(path: String, ty: &Type, sort_order: String) -> [String; 11] {
+    let basic_info = ty.get_basic_info();
+    let repetition = if basic_info.has_repetition() {
+        basic_info.repetition().to_string()
+    } else {
+        "".to_string()
+    };
+    let field_id = if basic_info.has_id() {
+        basic_info.id().to_string()
+    } else {
+        "".to_string()
+    };
+    let ctype = basic_info.converted_type().to_string();
+    let ltype = basic_info
+        .logical_type()
+        .map_or("".to_string(), |t| format!("{t:?}"));
+    match ty {
+        Type::PrimitiveType {
+            physical_type,
+            type_length,
+            scale,
+            precision,
+            ..
+        } => [
+            path,
+            "primitive".to_string(),
+            ltype,
+            ctype,
+            physical_type.to_string(),
+            type_length.to_string(),
+            scale.to_string(),
+            precision.to_string(),
+            sort_order,
+            repetition,
+            field_id,
+        ],
+        Type::GroupType { .. } => [
+            path,
+            "group".to_string(),
+            ltype,
+            ctype,
+            "".to_string(),
+            "".to_string(),
+            "".to_string(),
+            "".to_string(),
+            sort_order,
+            repetition,
+            field_id,
+        ],
     }
 }
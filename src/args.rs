@@ -1,10 +1,156 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
+
+use crate::{
+    delta::parse_timestamp,
+    encryption::{ColumnKey, Key},
+    partition::PartitionFilter,
+};
 
 #[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct Args {
-    /// Parquet file to view
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+
+    /// Parquet, CSV, JSON Lines or Arrow IPC file, hive-partitioned directory,
+    /// Delta or Iceberg table or object store URL (`s3://`, `gs://`, `az://`, `https://`) to view.
+    /// Use `-`, or leave out when piping, to read from stdin
     #[arg(name = "FILE")]
-    pub filename: PathBuf,
+    pub filename: Option<PathBuf>,
+
+    /// More files to open, each in a buffer of its own (`:bn`, `:bp` and
+    /// `:buffers` switch between them)
+    #[arg(name = "MORE", requires = "FILE")]
+    pub more: Vec<PathBuf>,
+
+    /// Only read partitions matching `key=value` or `key!=value` (repeatable)
+    #[arg(short, long = "filter", value_name = "FILTER")]
+    pub filters: Vec<PartitionFilter>,
+
+    /// Read a Delta table as of this version, or an Iceberg table as of this
+    /// snapshot ID
+    #[arg(long, value_name = "VERSION", conflicts_with = "at_timestamp")]
+    pub at_version: Option<i64>,
+
+    /// Read a Delta or Iceberg table as of the last version committed at or
+    /// before this time, e.g. `2024-10-01T12:00:00Z` or milliseconds since the epoch
+    #[arg(long, value_name = "TIMESTAMP", value_parser = parse_timestamp)]
+    pub at_timestamp: Option<i64>,
+
+    /// Profile only the first ROWS rows of a column rather than all of them
+    #[arg(long, value_name = "ROWS")]
+    pub sample: Option<usize>,
+
+    /// Read a damaged Parquet file one column chunk at a time, showing the
+    /// chunks that can't be decoded as nulls
+    #[arg(long)]
+    pub salvage: bool,
+
+    /// Key of an encrypted Parquet footer, as characters or as `hex:` followed
+    /// by hex digits
+    #[arg(
+        long,
+        value_name = "KEY",
+        env = "PARQOUR_FOOTER_KEY",
+        hide_env_values = true
+    )]
+    pub footer_key: Option<Key>,
+
+    /// Key of an encrypted Parquet column, as `column:key` (repeatable)
+    #[arg(
+        long = "column-key",
+        value_name = "COLUMN:KEY",
+        env = "PARQOUR_COLUMN_KEYS",
+        value_delimiter = ',',
+        hide_env_values = true
+    )]
+    pub column_keys: Vec<ColumnKey>,
+
+    /// File of `footer: key` and `column: key` lines, overridden by
+    /// --footer-key and --column-key
+    #[arg(long, value_name = "PATH", env = "PARQOUR_KEY_FILE")]
+    pub key_file: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+pub enum Commands {
+    /// Compare the schemas and metadata of two Parquet files, and exit with
+    /// an error if they differ
+    Diff(DiffArgs),
+    /// Run SQL against files, each registered as a table named after its file
+    /// stem
+    Query(QueryArgs),
+    /// Check the integrity of Parquet files, decoding every page, and exit
+    /// with an error if any of them is damaged
+    Validate(ValidateArgs),
+    /// Check Parquet files against best practices, and exit with an error if
+    /// any of them breaks a rule set to `error`
+    Lint(LintArgs),
+}
+
+#[derive(clap::Args)]
+pub struct QueryArgs {
+    /// Query to run, e.g. "SELECT count(*) FROM data"
+    #[arg(name = "SQL")]
+    pub sql: String,
+
+    /// Parquet, CSV, JSON Lines or Arrow IPC files, or hive-partitioned
+    /// directories, to query
+    #[arg(name = "FILE", required = true)]
+    pub files: Vec<PathBuf>,
+
+    /// Print the results instead of opening them in the terminal UI
+    #[arg(short, long)]
+    pub print: bool,
+}
+
+#[derive(clap::Args)]
+pub struct ValidateArgs {
+    /// Parquet files to check
+    #[arg(name = "FILE", required = true)]
+    pub files: Vec<PathBuf>,
+}
+
+#[derive(clap::Args)]
+pub struct LintArgs {
+    /// Parquet files to check
+    #[arg(name = "FILE", required = true)]
+    pub files: Vec<PathBuf>,
+
+    /// TOML file setting the level of each rule, and its thresholds
+    #[arg(short, long, value_name = "PATH", env = "PARQOUR_LINT_CONFIG")]
+    pub config: Option<PathBuf>,
+}
+
+#[derive(clap::Args)]
+pub struct DiffArgs {
+    /// Original file
+    #[arg(name = "FILE_A")]
+    pub left: PathBuf,
+
+    /// File to compare against
+    #[arg(name = "FILE_B")]
+    pub right: PathBuf,
+
+    /// Also compare row counts and column statistics
+    #[arg(short, long)]
+    pub stats: bool,
+
+    /// Also compare rows, matched on these key columns (repeatable)
+    #[arg(short, long = "key", value_name = "COLUMN")]
+    pub keys: Vec<String>,
+
+    /// Rows to sort in memory before spilling to disk when comparing rows
+    #[arg(long, default_value_t = 1_000_000)]
+    pub run_size: usize,
+
+    /// Differing rows to show when comparing rows
+    #[arg(long, default_value_t = 1000)]
+    pub limit: usize,
+
+    /// Show the differences side by side in the terminal UI
+    #[arg(long)]
+    pub tui: bool,
 }
@@ -0,0 +1,89 @@
+use std::{
+    collections::BTreeMap,
+    env,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{
+    error::{Error, Result},
+    tui::{clipboard::Clipboard, theme::ColorDepth},
+};
+
+/// Where what is kept across runs is saved: `$XDG_STATE_HOME/parqour`,
+/// falling back to `~/.local/state`.
+pub fn state_dir() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_STATE_HOME")
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var_os("HOME").map(|home| Path::new(&home).join(".local").join("state"))
+        })?;
+    Some(dir.join("parqour"))
+}
+
+/// Settings of the terminal UI, read from `$XDG_CONFIG_HOME/parqour/config.toml`,
+/// e.g.
+///
+/// ```toml
+/// # `dark`, `light`, `high-contrast` or one of `[themes]`.
+/// theme = "solarized"
+/// # Detected from `COLORTERM`, `TERM` and `NO_COLOR` when left out.
+/// color-depth = "256"
+///
+/// [themes.solarized]
+/// inherits = "light"
+/// base = "#fdf6e3"
+/// love = "red"
+///
+/// [clipboard]
+/// # `osc52` or `native`.
+/// backend = "osc52"
+/// # `tsv` or `json`.
+/// row-format = "json"
+/// # `tsv` or `markdown`.
+/// selection-format = "markdown"
+///
+/// [keys]
+/// n = "down"
+/// e = "up"
+/// "ctrl-d" = "quit"
+/// "g t" = "next-tab"
+/// j = "nothing"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    /// Name of a built-in or user-defined theme, `dark` by default.
+    pub theme: Option<String>,
+    /// `truecolor`, `256`, `16` or `none`.
+    pub color_depth: Option<ColorDepth>,
+    /// User-defined themes, mapping colour names to `#rrggbb`, ANSI colour
+    /// names or indices, and `inherits` to the theme they change.
+    pub themes: BTreeMap<String, BTreeMap<String, String>>,
+    /// Keys or space-separated chords, mapped to the name of a command.
+    pub keys: BTreeMap<String, String>,
+    pub clipboard: Clipboard,
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/parqour/config.toml`, falling back to `~/.config`.
+    pub fn path() -> Option<PathBuf> {
+        let dir = env::var_os("XDG_CONFIG_HOME")
+            .filter(|d| !d.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(dir.join("parqour").join("config.toml"))
+    }
+
+    /// Reads the config file, if there is one.
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path().filter(|p| p.is_file()) else {
+            return Ok(Self::default());
+        };
+        let text = std::fs::read_to_string(&path)?;
+        toml::from_str(&text)
+            .map_err(|e| Error::InvalidConfig(path, e.to_string().trim_end().to_string()))
+    }
+}
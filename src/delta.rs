@@ -0,0 +1,657 @@
+use arrow::{compute::kernels::cast_utils::string_to_timestamp_nanos, json::LineDelimitedWriter};
+use parquet::{
+    arrow::arrow_reader::ParquetRecordBatchReaderBuilder, file::metadata::ParquetMetaDataReader,
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    error::{Error, Result},
+    partition::{percent_decode, PartitionFile, Partitioning},
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, File},
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+const LOG_DIR: &str = "_delta_log";
+
+/// Highest `minReaderVersion` of the Delta protocol understood.
+const MAX_READER_VERSION: i32 = 3;
+
+/// Reader features of protocol version 3 that are supported. Column mapping
+/// and deletion vectors are only checked for in the metadata and files, as a
+/// table may enable them without using them yet; the others don't change
+/// how data files are read.
+const READER_FEATURES: &[&str] = &[
+    "columnMapping",
+    "deletionVectors",
+    "timestampNtz",
+    "vacuumProtocolCheck",
+];
+
+/// Which version of a Delta table to read.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum TimeTravel {
+    #[default]
+    Latest,
+    Version(i64),
+    /// The last version committed at or before this time, in milliseconds
+    /// since the epoch.
+    Timestamp(i64),
+}
+
+/// A Delta Lake table in a local directory, read at a single version.
+#[derive(Clone, Debug)]
+pub struct DeltaTable {
+    pub root: PathBuf,
+    pub version: i64,
+    pub metadata: Option<TableMetadata>,
+    pub protocol: Option<Protocol>,
+    /// Data files of the version, with their partition values.
+    pub partitioning: Partitioning,
+    /// Commits still in the log, newest first.
+    pub history: Vec<Commit>,
+}
+
+/// The `metaData` action of the table.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableMetadata {
+    pub id: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub schema_string: String,
+    #[serde(default)]
+    pub partition_columns: Vec<String>,
+    #[serde(default)]
+    pub configuration: BTreeMap<String, String>,
+    pub created_time: Option<i64>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Protocol {
+    pub min_reader_version: i32,
+    pub min_writer_version: i32,
+    /// Features readers must support, from reader version 3.
+    pub reader_features: Option<Vec<String>>,
+}
+
+/// A single JSON commit of the log.
+#[derive(Clone, Debug)]
+pub struct Commit {
+    pub version: i64,
+    /// Milliseconds since the epoch.
+    pub timestamp: i64,
+    pub operation: Option<String>,
+    pub operation_parameters: Vec<(String, String)>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// A line of a commit, or a row of a checkpoint.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Action {
+    add: Option<Add>,
+    remove: Option<Remove>,
+    meta_data: Option<TableMetadata>,
+    protocol: Option<Protocol>,
+    commit_info: Option<CommitInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Add {
+    path: String,
+    #[serde(default)]
+    partition_values: HashMap<String, Option<String>>,
+    size: u64,
+    stats: Option<String>,
+    /// Rows of the file that were deleted without rewriting it.
+    deletion_vector: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Remove {
+    path: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CommitInfo {
+    timestamp: Option<i64>,
+    operation: Option<String>,
+    #[serde(default)]
+    operation_parameters: BTreeMap<String, Value>,
+}
+
+/// Statistics of an added file, of which only the row count is used.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Stats {
+    num_records: Option<i64>,
+}
+
+impl DeltaTable {
+    /// Whether `path` is the root directory of a Delta table.
+    pub fn is_table(path: &Path) -> bool {
+        path.join(LOG_DIR).is_dir()
+    }
+
+    /// Replays the log of the table at `root` up to the version picked by `at`,
+    /// starting from the latest checkpoint before it.
+    pub fn open(root: &Path, at: TimeTravel) -> Result<Self> {
+        let mut commits = BTreeMap::new();
+        let mut checkpoints: BTreeMap<i64, Vec<PathBuf>> = BTreeMap::new();
+        for entry in fs::read_dir(root.join(LOG_DIR))? {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let Some(version) = name.get(..20).and_then(|v| v.parse::<i64>().ok()) else {
+                continue;
+            };
+            match &name[20..] {
+                ".json" => {
+                    commits.insert(version, path);
+                }
+                rest if rest.starts_with(".checkpoint") && rest.ends_with(".parquet") => {
+                    checkpoints.entry(version).or_default().push(path);
+                }
+                _ => {}
+            }
+        }
+
+        let mut actions = BTreeMap::new();
+        let mut history = vec![];
+        for (&version, path) in commits.iter() {
+            let commit_actions = read_commit(path)?;
+            history.push(summarize(version, path, &commit_actions)?);
+            actions.insert(version, commit_actions);
+        }
+        history.reverse();
+
+        let latest = commits
+            .keys()
+            .chain(checkpoints.keys())
+            .max()
+            .copied()
+            .ok_or_else(|| Error::NoDeltaVersion("in an empty log".to_string()))?;
+        let version = match at {
+            TimeTravel::Latest => latest,
+            TimeTravel::Version(version) if version <= latest => version,
+            TimeTravel::Version(version) => {
+                return Err(Error::NoDeltaVersion(format!("`{version}`")));
+            }
+            TimeTravel::Timestamp(timestamp) => history
+                .iter()
+                .find(|c| c.timestamp <= timestamp)
+                .map(|c| c.version)
+                .ok_or_else(|| {
+                    Error::NoDeltaVersion(format!("at or before `{}`", format_timestamp(timestamp)))
+                })?,
+        };
+
+        // Start from the latest checkpoint, if any, then replay the commits after it.
+        let mut files = BTreeMap::new();
+        let mut metadata = None;
+        let mut protocol = None;
+        let mut apply = |action: Action| {
+            if let Some(add) = action.add {
+                files.insert(add.path.clone(), add);
+            }
+            if let Some(remove) = action.remove {
+                files.remove(&remove.path);
+            }
+            if action.meta_data.is_some() {
+                metadata = action.meta_data;
+            }
+            if action.protocol.is_some() {
+                protocol = action.protocol;
+            }
+        };
+        let checkpoint = checkpoints.range(..=version).next_back();
+        let start = match checkpoint {
+            Some((&checkpoint_version, parts)) => {
+                for part in parts {
+                    read_checkpoint(part)?.into_iter().for_each(&mut apply);
+                }
+                checkpoint_version + 1
+            }
+            None => 0,
+        };
+        for v in start..=version {
+            let commit_actions = actions.remove(&v).ok_or_else(|| {
+                Error::NoDeltaVersion(format!("`{version}`, commit `{v}` is missing from the log"))
+            })?;
+            commit_actions.into_iter().for_each(&mut apply);
+        }
+        check_protocol(protocol.as_ref(), metadata.as_ref(), files.values())?;
+
+        let keys = metadata
+            .as_ref()
+            .map_or(vec![], |m: &TableMetadata| m.partition_columns.clone());
+        let files = files
+            .into_values()
+            .map(|add| {
+                let path = root.join(percent_decode(&add.path));
+                let values = keys
+                    .iter()
+                    .map(|k| add.partition_values.get(k).cloned().flatten())
+                    .collect();
+                let num_rows = match add
+                    .stats
+                    .as_deref()
+                    .and_then(|s| serde_json::from_str::<Stats>(s).ok())
+                    .and_then(|s| s.num_records)
+                {
+                    Some(num_rows) => num_rows,
+                    None => ParquetMetaDataReader::new()
+                        .parse_and_finish(&File::open(&path)?)?
+                        .file_metadata()
+                        .num_rows(),
+                };
+                Ok(PartitionFile {
+                    path,
+                    values,
+                    num_rows,
+                    size: add.size,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            version,
+            metadata,
+            protocol,
+            partitioning: Partitioning {
+                root: root.to_path_buf(),
+                keys,
+                files,
+            },
+            history,
+        })
+    }
+}
+
+/// Fails on the features of the table that would make its rows read wrong:
+/// columns stored under other names, and rows deleted by deletion vectors.
+fn check_protocol<'a>(
+    protocol: Option<&Protocol>,
+    metadata: Option<&TableMetadata>,
+    mut files: impl Iterator<Item = &'a Add>,
+) -> Result<()> {
+    if let Some(protocol) = protocol {
+        if protocol.min_reader_version > MAX_READER_VERSION {
+            return Err(Error::UnsupportedDeltaFeature(format!(
+                "reader version {}",
+                protocol.min_reader_version
+            )));
+        }
+        let features = protocol.reader_features.iter().flatten();
+        if let Some(feature) = features
+            .into_iter()
+            .find(|f| !READER_FEATURES.contains(&f.as_str()))
+        {
+            return Err(Error::UnsupportedDeltaFeature(format!(
+                "the `{feature}` reader feature"
+            )));
+        }
+    }
+    let mode = metadata.and_then(|m| m.configuration.get("delta.columnMapping.mode"));
+    if let Some(mode) = mode.filter(|mode| *mode != "none") {
+        return Err(Error::UnsupportedDeltaFeature(format!(
+            "column mapping by {mode}"
+        )));
+    }
+    if files.any(|add| add.deletion_vector.is_some()) {
+        return Err(Error::UnsupportedDeltaFeature(
+            "deletion vectors".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn read_commit(path: &Path) -> Result<Vec<Action>> {
+    let mut actions = vec![];
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            actions.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(actions)
+}
+
+/// Reads a checkpoint, whose rows have the same shape as commit actions once
+/// written out as JSON.
+fn read_checkpoint(path: &Path) -> Result<Vec<Action>> {
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?.build()?;
+    let mut writer = LineDelimitedWriter::new(vec![]);
+    for batch in reader {
+        writer.write(&batch?)?;
+    }
+    writer.finish()?;
+    writer
+        .into_inner()
+        .split(|&b| b == b'\n')
+        .filter(|line| !line.is_empty())
+        .map(|line| Ok(serde_json::from_slice(line)?))
+        .collect()
+}
+
+fn summarize(version: i64, path: &Path, actions: &[Action]) -> Result<Commit> {
+    let info = actions.iter().find_map(|a| a.commit_info.as_ref());
+    let timestamp = match info.and_then(|i| i.timestamp) {
+        Some(timestamp) => timestamp,
+        // Fall back to the file's modification time, as Delta readers do.
+        None => fs::metadata(path)?
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as i64),
+    };
+    let operation_parameters = info.map_or(vec![], |i| {
+        i.operation_parameters
+            .iter()
+            .map(|(k, v)| {
+                let v = match v {
+                    Value::String(s) => s.clone(),
+                    v => v.to_string(),
+                };
+                (k.clone(), v)
+            })
+            .collect()
+    });
+    Ok(Commit {
+        version,
+        timestamp,
+        operation: info.and_then(|i| i.operation.clone()),
+        operation_parameters,
+        added: actions
+            .iter()
+            .filter_map(|a| a.add.as_ref().map(|a| percent_decode(&a.path)))
+            .collect(),
+        removed: actions
+            .iter()
+            .filter_map(|a| a.remove.as_ref().map(|r| percent_decode(&r.path)))
+            .collect(),
+    })
+}
+
+/// Parses `--at-timestamp`, either RFC 3339 (e.g. `2024-10-01T12:00:00Z`) or
+/// milliseconds since the epoch.
+pub fn parse_timestamp(s: &str) -> std::result::Result<i64, String> {
+    if let Ok(millis) = s.parse() {
+        return Ok(millis);
+    }
+    string_to_timestamp_nanos(s)
+        .map(|nanos| nanos / 1_000_000)
+        .map_err(|e| e.to_string())
+}
+
+/// Formats milliseconds since the epoch as a UTC date and time.
+pub fn format_timestamp(millis: i64) -> String {
+    arrow::temporal_conversions::timestamp_ms_to_datetime(millis).map_or(millis.to_string(), |t| {
+        t.format("%Y-%m-%d %H:%M:%S").to_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Cursor, sync::Arc};
+
+    use arrow::{
+        array::{ArrayRef, Int64Array, RecordBatch},
+        json::{reader::infer_json_schema, ReaderBuilder},
+    };
+    use parquet::arrow::ArrowWriter;
+    use serde_json::json;
+
+    use super::*;
+
+    fn write_file(root: &Path, name: &str, ids: Vec<i64>) -> Value {
+        let path = root.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let batch =
+            RecordBatch::try_from_iter([("id", Arc::new(Int64Array::from(ids)) as ArrayRef)])
+                .unwrap();
+        let mut writer =
+            ArrowWriter::try_new(File::create(&path).unwrap(), batch.schema(), None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+        let size = fs::metadata(&path).unwrap().len();
+        json!({ "add": { "path": name, "partitionValues": {}, "size": size } })
+    }
+
+    fn lines(actions: &[Value]) -> String {
+        actions.iter().map(|a| format!("{a}\n")).collect()
+    }
+
+    fn commit(root: &Path, version: i64, actions: &[Value]) {
+        let dir = root.join(LOG_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(format!("{version:020}.json")), lines(actions)).unwrap();
+    }
+
+    /// Writes the actions as a checkpoint, leaving out empty maps, which
+    /// Delta writers store as map columns but would be inferred as empty
+    /// structs here.
+    fn checkpoint(root: &Path, version: i64, actions: &[Value]) {
+        fn drop_empty(value: &mut Value) {
+            if let Value::Object(object) = value {
+                object.retain(|_, v| !v.as_object().is_some_and(|o| o.is_empty()));
+                object.values_mut().for_each(drop_empty);
+            }
+        }
+        let mut actions = actions.to_vec();
+        actions.iter_mut().for_each(drop_empty);
+        let text = lines(&actions);
+        let (schema, _) = infer_json_schema(Cursor::new(&text), None).unwrap();
+        let schema = Arc::new(schema);
+        let reader = ReaderBuilder::new(Arc::clone(&schema))
+            .build(Cursor::new(&text))
+            .unwrap();
+        let path = root
+            .join(LOG_DIR)
+            .join(format!("{version:020}.checkpoint.parquet"));
+        let mut writer = ArrowWriter::try_new(File::create(path).unwrap(), schema, None).unwrap();
+        for batch in reader {
+            writer.write(&batch.unwrap()).unwrap();
+        }
+        writer.close().unwrap();
+    }
+
+    fn protocol() -> Value {
+        json!({ "protocol": { "minReaderVersion": 1, "minWriterVersion": 2 } })
+    }
+
+    fn metadata(configuration: Value) -> Value {
+        json!({ "metaData": {
+            "id": "test",
+            "format": { "provider": "parquet", "options": {} },
+            "schemaString": "{\"type\":\"struct\",\"fields\":[]}",
+            "partitionColumns": [],
+            "configuration": configuration,
+        } })
+    }
+
+    fn commit_info(timestamp: i64, operation: &str) -> Value {
+        json!({ "commitInfo": { "timestamp": timestamp, "operation": operation } })
+    }
+
+    fn paths(table: &DeltaTable) -> Vec<String> {
+        table
+            .partitioning
+            .files
+            .iter()
+            .map(|f| {
+                f.path
+                    .strip_prefix(&table.root)
+                    .unwrap()
+                    .display()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    /// A table of three versions: two files, then one replaced, then one
+    /// more added.
+    fn table(root: &Path) {
+        let a = write_file(root, "a.parquet", vec![1, 2]);
+        let b = write_file(root, "b.parquet", vec![3]);
+        let c = write_file(root, "c.parquet", vec![4, 5, 6]);
+        let d = write_file(root, "d.parquet", vec![7]);
+        commit(
+            root,
+            0,
+            &[
+                commit_info(1_000, "WRITE"),
+                protocol(),
+                metadata(json!({})),
+                a,
+                b.clone(),
+            ],
+        );
+        commit(
+            root,
+            1,
+            &[
+                commit_info(2_000, "DELETE"),
+                json!({ "remove": { "path": "a.parquet" } }),
+                c.clone(),
+            ],
+        );
+        commit(root, 2, &[commit_info(3_000, "WRITE"), d]);
+    }
+
+    #[test]
+    fn replays_the_log() {
+        let dir = tempfile::tempdir().unwrap();
+        table(dir.path());
+
+        let table = DeltaTable::open(dir.path(), TimeTravel::Latest).unwrap();
+        assert_eq!(table.version, 2);
+        assert_eq!(paths(&table), ["b.parquet", "c.parquet", "d.parquet"]);
+        assert_eq!(table.partitioning.num_rows(), 5);
+        let versions: Vec<_> = table.history.iter().map(|c| c.version).collect();
+        assert_eq!(versions, [2, 1, 0]);
+        assert_eq!(table.history[1].operation.as_deref(), Some("DELETE"));
+        assert_eq!(table.history[1].removed, ["a.parquet"]);
+
+        let table = DeltaTable::open(dir.path(), TimeTravel::Version(0)).unwrap();
+        assert_eq!(paths(&table), ["a.parquet", "b.parquet"]);
+
+        let table = DeltaTable::open(dir.path(), TimeTravel::Timestamp(2_500)).unwrap();
+        assert_eq!(table.version, 1);
+        assert_eq!(paths(&table), ["b.parquet", "c.parquet"]);
+
+        assert!(matches!(
+            DeltaTable::open(dir.path(), TimeTravel::Version(3)),
+            Err(Error::NoDeltaVersion(_))
+        ));
+        assert!(matches!(
+            DeltaTable::open(dir.path(), TimeTravel::Timestamp(500)),
+            Err(Error::NoDeltaVersion(_))
+        ));
+    }
+
+    #[test]
+    fn starts_from_checkpoints() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        table(root);
+        let b = write_file(root, "b.parquet", vec![3]);
+        let c = write_file(root, "c.parquet", vec![4, 5, 6]);
+        checkpoint(root, 1, &[protocol(), metadata(json!({})), b, c]);
+        // Logs before a checkpoint may be cleaned up.
+        fs::remove_file(root.join(LOG_DIR).join(format!("{:020}.json", 0))).unwrap();
+        fs::remove_file(root.join(LOG_DIR).join(format!("{:020}.json", 1))).unwrap();
+
+        let table = DeltaTable::open(root, TimeTravel::Latest).unwrap();
+        assert_eq!(paths(&table), ["b.parquet", "c.parquet", "d.parquet"]);
+        assert_eq!(table.protocol.unwrap().min_reader_version, 1);
+
+        let table = DeltaTable::open(root, TimeTravel::Version(1)).unwrap();
+        assert_eq!(paths(&table), ["b.parquet", "c.parquet"]);
+
+        assert!(matches!(
+            DeltaTable::open(root, TimeTravel::Version(0)),
+            Err(Error::NoDeltaVersion(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_unsupported_features() {
+        let open = |actions: &[Value]| {
+            let dir = tempfile::tempdir().unwrap();
+            let mut actions = actions.to_vec();
+            actions.push(write_file(dir.path(), "a.parquet", vec![1]));
+            commit(dir.path(), 0, &actions);
+            DeltaTable::open(dir.path(), TimeTravel::Latest).map(|_| ())
+        };
+        let unsupported = |actions: &[Value], feature: &str| match open(actions) {
+            Err(Error::UnsupportedDeltaFeature(f)) => assert_eq!(f, feature),
+            result => panic!("expected {feature} to be unsupported, got {result:?}"),
+        };
+
+        let features = |features: Value| {
+            json!({ "protocol": {
+                "minReaderVersion": 3,
+                "minWriterVersion": 7,
+                "readerFeatures": features,
+            } })
+        };
+        open(&[features(json!(["timestampNtz"])), metadata(json!({}))]).unwrap();
+        open(&[
+            features(json!(["columnMapping", "deletionVectors"])),
+            metadata(json!({ "delta.columnMapping.mode": "none" })),
+        ])
+        .unwrap();
+        unsupported(
+            &[features(json!(["v2Checkpoint"])), metadata(json!({}))],
+            "the `v2Checkpoint` reader feature",
+        );
+        unsupported(
+            &[
+                json!({ "protocol": { "minReaderVersion": 4, "minWriterVersion": 7 } }),
+                metadata(json!({})),
+            ],
+            "reader version 4",
+        );
+        unsupported(
+            &[
+                json!({ "protocol": { "minReaderVersion": 2, "minWriterVersion": 5 } }),
+                metadata(json!({ "delta.columnMapping.mode": "name" })),
+            ],
+            "column mapping by name",
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut add = write_file(dir.path(), "a.parquet", vec![1, 2]);
+        add["add"]["deletionVector"] = json!({
+            "storageType": "i",
+            "pathOrInlineDv": "wi5b=000010000siXQKl0rr91000f55c8Xg0@@D72lkbi5=-{L",
+            "sizeInBytes": 40,
+            "cardinality": 1,
+        });
+        commit(
+            dir.path(),
+            0,
+            &[
+                features(json!(["deletionVectors"])),
+                metadata(json!({})),
+                add,
+            ],
+        );
+        assert!(matches!(
+            DeltaTable::open(dir.path(), TimeTravel::Latest),
+            Err(Error::UnsupportedDeltaFeature(f)) if f == "deletion vectors"
+        ));
+    }
+}
@@ -0,0 +1,527 @@
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+    fmt,
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use arrow::{
+    array::{ArrayRef, RecordBatch, RecordBatchReader, UInt32Array},
+    compute::{cast, concat_batches, take_record_batch},
+    datatypes::{DataType, SchemaRef},
+    row::{OwnedRow, RowConverter, Rows, SortField},
+    util::display::{ArrayFormatter, FormatOptions},
+};
+use parquet::arrow::{arrow_reader::ParquetRecordBatchReaderBuilder, ArrowWriter};
+
+use super::DiffKind;
+use crate::{
+    error::{Error, Result},
+    file_stem,
+};
+
+const READ_BATCH_SIZE: usize = 8192;
+
+/// Settings of a [`DataDiff`].
+#[derive(Clone, Debug)]
+pub struct DataDiffOptions {
+    /// Columns identifying a row in both files.
+    pub keys: Vec<String>,
+    /// Rows sorted in memory at once before spilling to a temporary file.
+    pub run_size: usize,
+    /// Differing rows to keep for display. All of them are counted.
+    pub limit: usize,
+}
+
+/// A row that is only in one file, or whose values differ.
+#[derive(Clone, Debug)]
+pub struct RowDiff {
+    pub kind: DiffKind,
+    /// Key values, formatted as `key=value` pairs.
+    pub key: String,
+    /// Values of [`DataDiff::columns`] in the left-hand file.
+    pub left: Option<Vec<String>>,
+    /// Values of [`DataDiff::columns`] in the right-hand file.
+    pub right: Option<Vec<String>>,
+}
+
+impl RowDiff {
+    /// Indices of the columns whose values differ.
+    pub fn changed_columns(&self) -> Vec<usize> {
+        match (&self.left, &self.right) {
+            (Some(l), Some(r)) => (0..l.len()).filter(|&i| l[i] != r[i]).collect(),
+            _ => vec![],
+        }
+    }
+}
+
+/// Row-level differences between two files, matched on key columns.
+#[derive(Clone, Debug)]
+pub struct DataDiff {
+    pub left_name: String,
+    pub right_name: String,
+    pub keys: Vec<String>,
+    /// Columns present in both files, compared by value.
+    pub columns: Vec<String>,
+    /// The first [`DataDiffOptions::limit`] differing rows, in key order.
+    pub rows: Vec<RowDiff>,
+    pub num_removed: usize,
+    pub num_added: usize,
+    pub num_changed: usize,
+    pub num_unchanged: usize,
+}
+
+impl DataDiff {
+    /// Sort-merges both files on the key columns.
+    ///
+    /// Each file is sorted in runs of [`DataDiffOptions::run_size`] rows, so
+    /// files larger than memory spill to temporary files.
+    pub fn new(left: &Path, right: &Path, options: &DataDiffOptions) -> Result<Self> {
+        let left_schema = ParquetRecordBatchReaderBuilder::try_new(File::open(left)?)?
+            .schema()
+            .clone();
+        let right_schema = ParquetRecordBatchReaderBuilder::try_new(File::open(right)?)?
+            .schema()
+            .clone();
+
+        let mut key_types = vec![];
+        for key in options.keys.iter() {
+            let field = left_schema
+                .field_with_name(key)
+                .map_err(|_| Error::UnknownColumn(key.clone()))?;
+            right_schema
+                .field_with_name(key)
+                .map_err(|_| Error::UnknownColumn(key.clone()))?;
+            key_types.push(field.data_type().clone());
+        }
+        let converter = RowConverter::new(
+            key_types
+                .iter()
+                .map(|t| SortField::new(t.clone()))
+                .collect(),
+        )?;
+
+        let columns: Vec<String> = left_schema
+            .fields()
+            .iter()
+            .map(|f| f.name().clone())
+            .filter(|name| right_schema.field_with_name(name).is_ok())
+            .collect();
+
+        let mut diff = Self {
+            left_name: file_stem(left).unwrap_or_default(),
+            right_name: file_stem(right).unwrap_or_default(),
+            keys: options.keys.clone(),
+            columns,
+            rows: vec![],
+            num_removed: 0,
+            num_added: 0,
+            num_changed: 0,
+            num_unchanged: 0,
+        };
+
+        let mut left_rows = SortedRows::open(left, options, &key_types, &converter)?;
+        let mut right_rows = SortedRows::open(right, options, &key_types, &converter)?;
+        let mut l = left_rows.next(&converter)?;
+        let mut r = right_rows.next(&converter)?;
+        loop {
+            let ordering = match (&l, &r) {
+                (None, None) => break,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(l), Some(r)) => l.key.cmp(&r.key),
+            };
+            match ordering {
+                Ordering::Less => {
+                    let row = l.take().unwrap();
+                    diff.num_removed += 1;
+                    diff.push(DiffKind::Removed, Some(&row), None, options.limit);
+                    l = left_rows.next(&converter)?;
+                }
+                Ordering::Greater => {
+                    let row = r.take().unwrap();
+                    diff.num_added += 1;
+                    diff.push(DiffKind::Added, None, Some(&row), options.limit);
+                    r = right_rows.next(&converter)?;
+                }
+                Ordering::Equal => {
+                    let (lrow, rrow) = (l.take().unwrap(), r.take().unwrap());
+                    if diff.values(&lrow) == diff.values(&rrow) {
+                        diff.num_unchanged += 1;
+                    } else {
+                        diff.num_changed += 1;
+                        diff.push(DiffKind::Changed, Some(&lrow), Some(&rrow), options.limit);
+                    }
+                    l = left_rows.next(&converter)?;
+                    r = right_rows.next(&converter)?;
+                }
+            }
+        }
+        Ok(diff)
+    }
+
+    /// Whether any row was added, removed or changed.
+    pub fn has_changes(&self) -> bool {
+        self.num_removed + self.num_added + self.num_changed > 0
+    }
+
+    fn push(
+        &mut self,
+        kind: DiffKind,
+        left: Option<&SortedRow>,
+        right: Option<&SortedRow>,
+        limit: usize,
+    ) {
+        if self.rows.len() >= limit {
+            return;
+        }
+        let keyed = left.or(right).unwrap();
+        self.rows.push(RowDiff {
+            kind,
+            key: format_key(&keyed.batch, &self.keys, keyed.row),
+            left: left.map(|row| self.values(row)),
+            right: right.map(|row| self.values(row)),
+        });
+    }
+
+    fn values(&self, row: &SortedRow) -> Vec<String> {
+        self.columns
+            .iter()
+            .map(|c| format_value(&row.batch, c, row.row))
+            .collect()
+    }
+}
+
+impl fmt::Display for DataDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Rows (keyed on {}):", self.keys.join(", "))?;
+        writeln!(f, "  only in {}: {}", self.left_name, self.num_removed)?;
+        writeln!(f, "  only in {}: {}", self.right_name, self.num_added)?;
+        writeln!(f, "  changed: {}", self.num_changed)?;
+        writeln!(f, "  unchanged: {}", self.num_unchanged)?;
+        for row in self.rows.iter() {
+            write!(f, "  {} {}", row.kind.symbol(), row.key)?;
+            if let (Some(l), Some(r)) = (&row.left, &row.right) {
+                let changes = row
+                    .changed_columns()
+                    .into_iter()
+                    .map(|i| format!("{}: {} -> {}", self.columns[i], l[i], r[i]))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, ": {changes}")?;
+            }
+            writeln!(f)?;
+        }
+        let total = self.num_removed + self.num_added + self.num_changed;
+        if total > self.rows.len() {
+            writeln!(f, "  … {} more", total - self.rows.len())?;
+        }
+        Ok(())
+    }
+}
+
+/// Key values of a row, formatted as `key=value` pairs.
+fn format_key(batch: &RecordBatch, keys: &[String], row: usize) -> String {
+    keys.iter()
+        .map(|k| format!("{k}={}", format_value(batch, k, row)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn format_value(batch: &RecordBatch, column: &str, row: usize) -> String {
+    let options = FormatOptions::default().with_null("null");
+    batch
+        .column_by_name(column)
+        .and_then(|c| ArrayFormatter::try_new(c.as_ref(), &options).ok())
+        .map_or("".to_string(), |f| f.value(row).to_string())
+}
+
+fn key_columns(batch: &RecordBatch, keys: &[String], types: &[DataType]) -> Result<Vec<ArrayRef>> {
+    keys.iter()
+        .zip(types.iter())
+        .map(|(key, data_type)| {
+            let column = batch
+                .column_by_name(key)
+                .ok_or_else(|| Error::UnknownColumn(key.clone()))?;
+            Ok(cast(column, data_type)?)
+        })
+        .collect()
+}
+
+/// A row of a [`SortedRows`] stream.
+struct SortedRow {
+    key: OwnedRow,
+    batch: RecordBatch,
+    row: usize,
+}
+
+/// A sorted run, either kept in memory or spilled to a temporary file.
+struct Run {
+    batches: Box<dyn Iterator<Item = Result<RecordBatch>>>,
+    batch: RecordBatch,
+    keys: Rows,
+    row: usize,
+}
+
+/// Rows of a file in key order, produced by merging sorted runs.
+struct SortedRows {
+    path: PathBuf,
+    runs: Vec<Run>,
+    heap: BinaryHeap<Reverse<(OwnedRow, usize)>>,
+    keys: Vec<String>,
+    key_types: Vec<DataType>,
+    /// Key of the last row, which the next one must differ from.
+    previous: Option<OwnedRow>,
+}
+
+impl SortedRows {
+    fn open(
+        path: &Path,
+        options: &DataDiffOptions,
+        key_types: &[DataType],
+        converter: &RowConverter,
+    ) -> Result<Self> {
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?
+            .with_batch_size(READ_BATCH_SIZE)
+            .build()?;
+        let schema = reader.schema();
+
+        let mut sorted_runs = vec![];
+        let mut pending = vec![];
+        let mut pending_rows = 0;
+        for batch in reader {
+            let batch = batch?;
+            pending_rows += batch.num_rows();
+            pending.push(batch);
+            if pending_rows >= options.run_size {
+                let run = sort_run(&schema, &pending, &options.keys, key_types, converter)?;
+                sorted_runs.push(spill(run)?);
+                pending.clear();
+                pending_rows = 0;
+            }
+        }
+        if pending_rows > 0 {
+            let run = sort_run(&schema, &pending, &options.keys, key_types, converter)?;
+            if sorted_runs.is_empty() {
+                // Everything fits in a single run: no need to spill.
+                sorted_runs.push(Box::new(std::iter::once(Ok(run))) as Box<dyn Iterator<Item = _>>);
+            } else {
+                sorted_runs.push(spill(run)?);
+            }
+        }
+
+        let mut sorted = Self {
+            path: path.to_path_buf(),
+            runs: vec![],
+            heap: BinaryHeap::new(),
+            keys: options.keys.clone(),
+            key_types: key_types.to_vec(),
+            previous: None,
+        };
+        for batches in sorted_runs {
+            let mut run = Run {
+                batches,
+                batch: RecordBatch::new_empty(schema.clone()),
+                keys: converter.empty_rows(0, 0),
+                row: 0,
+            };
+            if load_batch(&mut run, &sorted.keys, &sorted.key_types, converter)? {
+                sorted
+                    .heap
+                    .push(Reverse((run.keys.row(0).owned(), sorted.runs.len())));
+                sorted.runs.push(run);
+            }
+        }
+        Ok(sorted)
+    }
+
+    fn next(&mut self, converter: &RowConverter) -> Result<Option<SortedRow>> {
+        let Some(Reverse((key, i))) = self.heap.pop() else {
+            return Ok(None);
+        };
+        let run = &mut self.runs[i];
+        if self.previous.as_ref() == Some(&key) {
+            let key = format_key(&run.batch, &self.keys, run.row);
+            return Err(Error::DuplicateKey(self.path.clone(), key));
+        }
+        self.previous = Some(key.clone());
+        let row = SortedRow {
+            key,
+            batch: run.batch.clone(),
+            row: run.row,
+        };
+        run.row += 1;
+        if run.row < run.batch.num_rows()
+            || load_batch(run, &self.keys, &self.key_types, converter)?
+        {
+            self.heap.push(Reverse((run.keys.row(run.row).owned(), i)));
+        }
+        Ok(Some(row))
+    }
+}
+
+/// Moves `run` to its next non-empty batch, returning `false` once exhausted.
+fn load_batch(
+    run: &mut Run,
+    keys: &[String],
+    key_types: &[DataType],
+    converter: &RowConverter,
+) -> Result<bool> {
+    for batch in run.batches.by_ref() {
+        let batch = batch?;
+        if batch.num_rows() > 0 {
+            run.keys = converter.convert_columns(&key_columns(&batch, keys, key_types)?)?;
+            run.batch = batch;
+            run.row = 0;
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn sort_run(
+    schema: &SchemaRef,
+    batches: &[RecordBatch],
+    keys: &[String],
+    key_types: &[DataType],
+    converter: &RowConverter,
+) -> Result<RecordBatch> {
+    let batch = concat_batches(schema, batches)?;
+    let rows = converter.convert_columns(&key_columns(&batch, keys, key_types)?)?;
+    let mut indices: Vec<u32> = (0..batch.num_rows() as u32).collect();
+    indices.sort_by(|&a, &b| rows.row(a as usize).cmp(&rows.row(b as usize)));
+    Ok(take_record_batch(&batch, &UInt32Array::from(indices))?)
+}
+
+/// Writes a sorted run to a temporary file that is removed once read.
+fn spill(run: RecordBatch) -> Result<Box<dyn Iterator<Item = Result<RecordBatch>>>> {
+    let file = tempfile::tempfile()?;
+    let mut writer = ArrowWriter::try_new(file.try_clone()?, run.schema(), None)?;
+    writer.write(&run)?;
+    writer.close()?;
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)?
+        .with_batch_size(READ_BATCH_SIZE)
+        .build()?;
+    Ok(Box::new(reader.map(|b| b.map_err(Error::from))))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::Int64Array;
+
+    use super::*;
+
+    const NUM_ROWS: i64 = 20_000;
+
+    /// Writes `(id, value)` rows in a scrambled order, so that sorting them
+    /// matters.
+    fn write(path: &Path, rows: impl Iterator<Item = (i64, i64)>) {
+        let mut rows: Vec<_> = rows.collect();
+        rows.sort_by_key(|(id, _)| id.wrapping_mul(7919) % 10_007);
+        let (ids, values): (Vec<_>, Vec<_>) = rows.into_iter().unzip();
+        let batch = RecordBatch::try_from_iter([
+            ("id", Arc::new(Int64Array::from(ids)) as ArrayRef),
+            ("value", Arc::new(Int64Array::from(values)) as ArrayRef),
+        ])
+        .unwrap();
+        let mut writer =
+            ArrowWriter::try_new(File::create(path).unwrap(), batch.schema(), None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+    }
+
+    fn diff(run_size: usize) -> DataDiff {
+        let dir = tempfile::tempdir().unwrap();
+        let left = dir.path().join("left.parquet");
+        let right = dir.path().join("right.parquet");
+        write(&left, (0..NUM_ROWS).map(|id| (id, id)));
+        // Drops every 1000th row, changes every 997th and adds 5 more, past
+        // the dropped 20000.
+        write(
+            &right,
+            (0..=NUM_ROWS + 5)
+                .filter(|id| id % 1000 != 0)
+                .map(|id| (id, if id % 997 == 0 { -id } else { id })),
+        );
+        let options = DataDiffOptions {
+            keys: vec!["id".to_string()],
+            run_size,
+            limit: 3,
+        };
+        DataDiff::new(&left, &right, &options).unwrap()
+    }
+
+    #[test]
+    fn merges_rows() {
+        let diff = diff(usize::MAX);
+        assert_eq!(diff.columns, ["id", "value"]);
+        assert_eq!(diff.num_removed, 20);
+        assert_eq!(diff.num_added, 5);
+        // Multiples of 997 that aren't multiples of 1000, 0 excepted.
+        assert_eq!(diff.num_changed, 20);
+        assert_eq!(diff.num_unchanged, NUM_ROWS as usize - 40);
+        assert!(diff.has_changes());
+
+        // The first differing rows, in key order.
+        assert_eq!(diff.rows.len(), 3);
+        assert_eq!(diff.rows[0].kind, DiffKind::Removed);
+        assert_eq!(diff.rows[0].key, "id=0");
+        assert_eq!(diff.rows[1].kind, DiffKind::Changed);
+        assert_eq!(diff.rows[1].key, "id=997");
+        assert_eq!(diff.rows[1].changed_columns(), [1]);
+        assert_eq!(diff.rows[1].right.as_ref().unwrap()[1], "-997");
+        assert_eq!(diff.rows[2].kind, DiffKind::Removed);
+        assert_eq!(diff.rows[2].key, "id=1000");
+    }
+
+    #[test]
+    fn spills_runs() {
+        let merged = diff(usize::MAX);
+        // Several runs per file, each spilled and merged back.
+        let spilled = diff(READ_BATCH_SIZE);
+        assert_eq!(spilled.to_string(), merged.to_string());
+    }
+
+    #[test]
+    fn fails_on_duplicate_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let unique = dir.path().join("unique.parquet");
+        let duplicated = dir.path().join("duplicated.parquet");
+        write(&unique, (0..NUM_ROWS).map(|id| (id, id)));
+        write(
+            &duplicated,
+            (0..NUM_ROWS).map(|id| (id, id)).chain([(1234, 0)]),
+        );
+        for run_size in [usize::MAX, READ_BATCH_SIZE] {
+            let options = DataDiffOptions {
+                keys: vec!["id".to_string()],
+                run_size,
+                limit: 10,
+            };
+            for (left, right) in [(&unique, &duplicated), (&duplicated, &unique)] {
+                let error = DataDiff::new(left, right, &options).err().unwrap();
+                assert!(
+                    matches!(&error, Error::DuplicateKey(path, key) if *path == duplicated && key == "id=1234"),
+                    "{error}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fails_on_unknown_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.parquet");
+        write(&path, (0..10).map(|id| (id, id)));
+        let options = DataDiffOptions {
+            keys: vec!["nope".to_string()],
+            run_size: 10,
+            limit: 10,
+        };
+        let error = DataDiff::new(&path, &path, &options).err().unwrap();
+        assert!(matches!(error, Error::UnknownColumn(c) if c == "nope"));
+    }
+}
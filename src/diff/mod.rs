@@ -0,0 +1,38 @@
+/// Schema and metadata comparison.
+pub mod schema;
+
+/// Row-level comparison on key columns.
+pub mod data;
+
+/// How an item differs between two files.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DiffKind {
+    /// Only in the right-hand file.
+    Added,
+    /// Only in the left-hand file.
+    Removed,
+    /// In both files, with different values.
+    Changed,
+    Unchanged,
+}
+
+impl DiffKind {
+    fn of<T: PartialEq>(left: Option<&T>, right: Option<&T>) -> Self {
+        match (left, right) {
+            (None, Some(_)) => Self::Added,
+            (Some(_), None) => Self::Removed,
+            (Some(l), Some(r)) if l != r => Self::Changed,
+            _ => Self::Unchanged,
+        }
+    }
+
+    /// Marker used in text reports.
+    pub fn symbol(self) -> char {
+        match self {
+            Self::Added => '+',
+            Self::Removed => '-',
+            Self::Changed => '~',
+            Self::Unchanged => ' ',
+        }
+    }
+}
@@ -0,0 +1,495 @@
+use std::{collections::HashMap, fmt};
+
+use parquet::{
+    data_type::{ByteArray, FixedLenByteArray},
+    file::{
+        metadata::{ParquetMetaData, RowGroupMetaData},
+        statistics::Statistics,
+    },
+    schema::types::{SchemaDescriptor, Type},
+};
+
+use super::DiffKind;
+use crate::app::{schema_row, SCHEMA_HEADERS};
+
+/// Longest metadata value shown in text reports.
+const MAX_VALUE_WIDTH: usize = 60;
+
+/// A schema column of either or both files, matched by column path.
+#[derive(Clone, Debug)]
+pub struct ColumnDiff {
+    pub left: Option<[String; 11]>,
+    pub right: Option<[String; 11]>,
+}
+
+impl ColumnDiff {
+    pub fn path(&self) -> &str {
+        self.left.as_ref().or(self.right.as_ref()).unwrap()[0].as_str()
+    }
+
+    pub fn kind(&self) -> DiffKind {
+        DiffKind::of(self.left.as_ref(), self.right.as_ref())
+    }
+
+    /// Attributes that differ, as `(attribute, left, right)`.
+    pub fn changes(&self) -> Vec<(&'static str, &str, &str)> {
+        match (&self.left, &self.right) {
+            (Some(left), Some(right)) => SCHEMA_HEADERS
+                .iter()
+                .zip(left.iter().zip(right.iter()))
+                .skip(1)
+                .filter(|(_, (l, r))| l != r)
+                .map(|(h, (l, r))| (*h, l.as_str(), r.as_str()))
+                .collect(),
+            _ => vec![],
+        }
+    }
+}
+
+/// A key-value metadata entry of either or both files.
+#[derive(Clone, Debug)]
+pub struct KeyValueDiff {
+    pub key: String,
+    pub left: Option<String>,
+    pub right: Option<String>,
+}
+
+impl KeyValueDiff {
+    pub fn kind(&self) -> DiffKind {
+        DiffKind::of(self.left.as_ref(), self.right.as_ref())
+    }
+}
+
+/// Statistics of a column aggregated over all row groups.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ColumnStats {
+    pub null_count: Option<u64>,
+    pub min: Option<String>,
+    pub max: Option<String>,
+}
+
+impl ColumnStats {
+    fn from_row_groups(row_groups: &[RowGroupMetaData]) -> HashMap<String, Self> {
+        let mut stats: HashMap<String, Vec<&Statistics>> = HashMap::new();
+        for rg in row_groups.iter() {
+            for chunk in rg.columns().iter() {
+                let entry = stats.entry(chunk.column_path().string()).or_default();
+                if let Some(s) = chunk.statistics() {
+                    entry.push(s);
+                }
+            }
+        }
+        stats
+            .into_iter()
+            .map(|(path, s)| {
+                let null_count = s
+                    .iter()
+                    .map(|s| s.null_count_opt())
+                    .sum::<Option<u64>>()
+                    .filter(|_| !s.is_empty());
+                let (min, max) = min_max(&s);
+                (
+                    path,
+                    Self {
+                        null_count,
+                        min,
+                        max,
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+/// Statistics of a column present in both files.
+#[derive(Clone, Debug)]
+pub struct StatsDiff {
+    pub path: String,
+    pub left: ColumnStats,
+    pub right: ColumnStats,
+}
+
+impl StatsDiff {
+    pub fn kind(&self) -> DiffKind {
+        DiffKind::of(Some(&self.left), Some(&self.right))
+    }
+}
+
+/// Differences between the schemas and footers of two files.
+#[derive(Clone, Debug)]
+pub struct SchemaDiff {
+    pub left_name: String,
+    pub right_name: String,
+    pub columns: Vec<ColumnDiff>,
+    pub key_values: Vec<KeyValueDiff>,
+    /// Row counts, if requested.
+    pub num_rows: Option<(i64, i64)>,
+    /// Statistics of the columns in both files, if requested.
+    pub stats: Vec<StatsDiff>,
+}
+
+impl SchemaDiff {
+    /// Compares the footers of `left` with `right`, including row counts and
+    /// column statistics when `with_stats` is set.
+    pub fn new(
+        (left_name, left): (String, &ParquetMetaData),
+        (right_name, right): (String, &ParquetMetaData),
+        with_stats: bool,
+    ) -> Self {
+        let left_rows = schema_rows(left);
+        let right_rows = schema_rows(right);
+        let mut columns: Vec<_> = left_rows
+            .iter()
+            .map(|l| ColumnDiff {
+                left: Some(l.clone()),
+                right: right_rows.iter().find(|r| r[0] == l[0]).cloned(),
+            })
+            .collect();
+        columns.extend(
+            right_rows
+                .iter()
+                .filter(|r| !left_rows.iter().any(|l| l[0] == r[0]))
+                .map(|r| ColumnDiff {
+                    left: None,
+                    right: Some(r.clone()),
+                }),
+        );
+
+        let find = |kv: &[(String, String)], key: &str| {
+            kv.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone())
+        };
+        let left_kv = key_values(left);
+        let right_kv = key_values(right);
+        let mut key_values: Vec<_> = left_kv
+            .iter()
+            .map(|(k, v)| KeyValueDiff {
+                key: k.clone(),
+                left: Some(v.clone()),
+                right: find(&right_kv, k),
+            })
+            .collect();
+        key_values.extend(
+            right_kv
+                .iter()
+                .filter(|(k, _)| find(&left_kv, k).is_none())
+                .map(|(k, v)| KeyValueDiff {
+                    key: k.clone(),
+                    left: None,
+                    right: Some(v.clone()),
+                }),
+        );
+
+        let mut num_rows = None;
+        let mut stats = vec![];
+        if with_stats {
+            num_rows = Some((
+                left.file_metadata().num_rows(),
+                right.file_metadata().num_rows(),
+            ));
+            let left_stats = ColumnStats::from_row_groups(left.row_groups());
+            let right_stats = ColumnStats::from_row_groups(right.row_groups());
+            // Only leaf columns have statistics.
+            for c in columns.iter().filter(|c| {
+                c.left.is_some()
+                    && c.right.is_some()
+                    && (left_stats.contains_key(c.path()) || right_stats.contains_key(c.path()))
+            }) {
+                stats.push(StatsDiff {
+                    path: c.path().to_string(),
+                    left: left_stats.get(c.path()).cloned().unwrap_or_default(),
+                    right: right_stats.get(c.path()).cloned().unwrap_or_default(),
+                });
+            }
+        }
+
+        Self {
+            left_name,
+            right_name,
+            columns,
+            key_values,
+            num_rows,
+            stats,
+        }
+    }
+
+    /// Whether the files differ in anything that was compared.
+    pub fn has_changes(&self) -> bool {
+        self.columns.iter().any(|c| c.kind() != DiffKind::Unchanged)
+            || self
+                .key_values
+                .iter()
+                .any(|kv| kv.kind() != DiffKind::Unchanged)
+            || self.num_rows.is_some_and(|(l, r)| l != r)
+            || self.stats.iter().any(|s| s.kind() != DiffKind::Unchanged)
+    }
+}
+
+impl fmt::Display for SchemaDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "--- {}", self.left_name)?;
+        writeln!(f, "+++ {}", self.right_name)?;
+        if !self.has_changes() {
+            return writeln!(f, "No differences");
+        }
+
+        let columns: Vec<_> = self
+            .columns
+            .iter()
+            .filter(|c| c.kind() != DiffKind::Unchanged)
+            .collect();
+        if !columns.is_empty() {
+            writeln!(f, "Columns:")?;
+            for c in columns {
+                let kind = c.kind();
+                match c.left.as_ref().xor(c.right.as_ref()) {
+                    // Physical type, or `group`, and repetition of an added or
+                    // removed column.
+                    Some(row) => {
+                        let ty = if row[4].is_empty() { &row[1] } else { &row[4] };
+                        writeln!(f, "  {} {} ({ty}, {})", kind.symbol(), row[0], row[9])?
+                    }
+                    None => {
+                        writeln!(f, "  {} {}", kind.symbol(), c.path())?;
+                        for (attr, l, r) in c.changes() {
+                            writeln!(f, "      {attr}: {} -> {}", or_none(l), or_none(r))?;
+                        }
+                    }
+                }
+            }
+        }
+
+        let key_values: Vec<_> = self
+            .key_values
+            .iter()
+            .filter(|kv| kv.kind() != DiffKind::Unchanged)
+            .collect();
+        if !key_values.is_empty() {
+            writeln!(f, "Key-value metadata:")?;
+            for kv in key_values {
+                let value = |v: &Option<String>| truncate(v.as_deref().unwrap_or("(none)"));
+                writeln!(
+                    f,
+                    "  {} {}: {} -> {}",
+                    kv.kind().symbol(),
+                    kv.key,
+                    value(&kv.left),
+                    value(&kv.right)
+                )?;
+            }
+        }
+
+        if let Some((l, r)) = self.num_rows.filter(|(l, r)| l != r) {
+            writeln!(f, "Row count: {l} -> {r}")?;
+        }
+
+        let stats: Vec<_> = self
+            .stats
+            .iter()
+            .filter(|s| s.kind() != DiffKind::Unchanged)
+            .collect();
+        if !stats.is_empty() {
+            writeln!(f, "Statistics:")?;
+            for s in stats {
+                writeln!(f, "  ~ {}", s.path)?;
+                let opt = |v: Option<String>| v.unwrap_or("(none)".to_string());
+                if s.left.null_count != s.right.null_count {
+                    writeln!(
+                        f,
+                        "      # null: {} -> {}",
+                        opt(s.left.null_count.map(|n| n.to_string())),
+                        opt(s.right.null_count.map(|n| n.to_string()))
+                    )?;
+                }
+                if s.left.min != s.right.min {
+                    writeln!(
+                        f,
+                        "      Min: {} -> {}",
+                        opt(s.left.min.clone()),
+                        opt(s.right.min.clone())
+                    )?;
+                }
+                if s.left.max != s.right.max {
+                    writeln!(
+                        f,
+                        "      Max: {} -> {}",
+                        opt(s.left.max.clone()),
+                        opt(s.right.max.clone())
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Rows of the Schema tab for every node of the schema of `metadata`, in
+/// depth-first order: groups too, so that changing the repetition of a
+/// struct or list shows up, and not only that of its leaves.
+fn schema_rows(metadata: &ParquetMetaData) -> Vec<[String; 11]> {
+    fn walk(
+        group: &Type,
+        prefix: &str,
+        descr: &SchemaDescriptor,
+        leaf: &mut usize,
+        rows: &mut Vec<[String; 11]>,
+    ) {
+        for field in group.get_fields() {
+            let path = if prefix.is_empty() {
+                field.name().to_string()
+            } else {
+                format!("{prefix}.{}", field.name())
+            };
+            if field.is_primitive() {
+                let sort_order = descr.column(*leaf).sort_order().to_string();
+                rows.push(schema_row(path, field, sort_order));
+                *leaf += 1;
+            } else {
+                rows.push(schema_row(path.clone(), field, "".to_string()));
+                walk(field, &path, descr, leaf, rows);
+            }
+        }
+    }
+
+    let descr = metadata.file_metadata().schema_descr();
+    let mut rows = vec![];
+    walk(descr.root_schema(), "", descr, &mut 0, &mut rows);
+    rows
+}
+
+fn key_values(metadata: &ParquetMetaData) -> Vec<(String, String)> {
+    metadata
+        .file_metadata()
+        .key_value_metadata()
+        .into_iter()
+        .flatten()
+        .map(|kv| (kv.key.clone(), kv.value.clone().unwrap_or_default()))
+        .collect()
+}
+
+fn or_none(s: &str) -> &str {
+    if s.is_empty() {
+        "(none)"
+    } else {
+        s
+    }
+}
+
+fn truncate(s: &str) -> String {
+    if s.chars().count() > MAX_VALUE_WIDTH {
+        let truncated: String = s.chars().take(MAX_VALUE_WIDTH).collect();
+        format!("{truncated}…")
+    } else {
+        s.to_string()
+    }
+}
+
+/// Smallest minimum and largest maximum over the statistics of several chunks.
+fn min_max(stats: &[&Statistics]) -> (Option<String>, Option<String>) {
+    fn fold<'a, T: PartialOrd + 'a>(
+        values: impl Iterator<Item = (Option<&'a T>, Option<&'a T>)>,
+        fmt: impl Fn(&T) -> String,
+    ) -> (Option<String>, Option<String>) {
+        let mut min: Option<&T> = None;
+        let mut max: Option<&T> = None;
+        for (lo, hi) in values {
+            if let Some(lo) = lo {
+                if min.is_none_or(|m| lo < m) {
+                    min = Some(lo);
+                }
+            }
+            if let Some(hi) = hi {
+                if max.is_none_or(|m| hi > m) {
+                    max = Some(hi);
+                }
+            }
+        }
+        (min.map(&fmt), max.map(&fmt))
+    }
+
+    macro_rules! fold_variant {
+        ($variant:ident, $fmt:expr) => {
+            fold(
+                stats.iter().filter_map(|s| match s {
+                    Statistics::$variant(v) => Some((v.min_opt(), v.max_opt())),
+                    _ => None,
+                }),
+                $fmt,
+            )
+        };
+    }
+
+    match stats.first() {
+        Some(Statistics::Boolean(_)) => fold_variant!(Boolean, |v: &bool| v.to_string()),
+        Some(Statistics::Int32(_)) => fold_variant!(Int32, |v: &i32| v.to_string()),
+        Some(Statistics::Int64(_)) => fold_variant!(Int64, |v: &i64| v.to_string()),
+        Some(Statistics::Float(_)) => fold_variant!(Float, |v: &f32| v.to_string()),
+        Some(Statistics::Double(_)) => fold_variant!(Double, |v: &f64| v.to_string()),
+        Some(Statistics::ByteArray(_)) => fold_variant!(ByteArray, |v: &ByteArray| {
+            String::from_utf8_lossy(v.data()).into_owned()
+        }),
+        Some(Statistics::FixedLenByteArray(_)) => {
+            fold_variant!(FixedLenByteArray, |v: &FixedLenByteArray| {
+                String::from_utf8_lossy(v.data()).into_owned()
+            })
+        }
+        // INT96 has no defined sort order.
+        Some(Statistics::Int96(_)) | None => (None, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, path::Path, sync::Arc};
+
+    use arrow::{
+        array::{Array, ArrayRef, Int32Array, RecordBatch, StructArray},
+        datatypes::{DataType, Field},
+    };
+    use parquet::{arrow::ArrowWriter, file::metadata::ParquetMetaDataReader};
+
+    use super::*;
+
+    /// Writes a file with a struct column `s`, nullable or not, holding an
+    /// optional `x`.
+    fn write(path: &Path, nullable: bool) -> ParquetMetaData {
+        let x = Arc::new(Field::new("x", DataType::Int32, true));
+        let s = StructArray::from(vec![(x, Arc::new(Int32Array::from(vec![1])) as ArrayRef)]);
+        let field = Field::new("s", s.data_type().clone(), nullable);
+        let schema = Arc::new(arrow::datatypes::Schema::new(vec![field]));
+        let batch = RecordBatch::try_new(Arc::clone(&schema), vec![Arc::new(s)]).unwrap();
+        let mut writer = ArrowWriter::try_new(File::create(path).unwrap(), schema, None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+        ParquetMetaDataReader::new()
+            .parse_and_finish(&File::open(path).unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn compares_groups() {
+        let dir = tempfile::tempdir().unwrap();
+        let left = write(&dir.path().join("left.parquet"), true);
+        let right = write(&dir.path().join("right.parquet"), false);
+
+        let diff = SchemaDiff::new(("left".into(), &left), ("right".into(), &right), true);
+        assert!(diff.has_changes());
+        let changed: Vec<_> = diff
+            .columns
+            .iter()
+            .filter(|c| c.kind() == DiffKind::Changed)
+            .collect();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].path(), "s");
+        assert_eq!(
+            changed[0].changes(),
+            [("Repetition", "OPTIONAL", "REQUIRED")]
+        );
+        // The leaf is the same in both files, groups have no statistics.
+        assert_eq!(diff.stats.len(), 1);
+        assert_eq!(diff.stats[0].path, "s.x");
+
+        let diff = SchemaDiff::new(("left".into(), &left), ("left".into(), &left), true);
+        assert!(!diff.has_changes());
+        assert_eq!(diff.to_string(), "--- left\n+++ left\nNo differences\n");
+    }
+}
@@ -0,0 +1,643 @@
+use bytes::Bytes;
+use parquet::{
+    arrow::{
+        arrow_reader::{
+            ArrowReaderMetadata, ArrowReaderOptions, ParquetRecordBatchReader,
+            ParquetRecordBatchReaderBuilder,
+        },
+        ProjectionMask,
+    },
+    encryption::decrypt::FileDecryptionProperties,
+    file::{
+        column_crypto_metadata::ColumnCryptoMetaData as CryptoMetaData,
+        metadata::{ParquetMetaData, ParquetMetaDataReader},
+    },
+    format::{
+        ColumnCryptoMetaData, ColumnMetaData, CompressionCodec, EncryptionAlgorithm,
+        FileCryptoMetaData, FileMetaData,
+    },
+    schema::types::{self, ColumnDescriptor, SchemaDescriptor},
+    thrift::TSerializable,
+};
+use thrift::protocol::{TCompactInputProtocol, TCompactOutputProtocol, TOutputProtocol};
+
+use crate::{
+    error::{Error, Result},
+    source::projection,
+};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Debug, Formatter},
+    fs::{self, File},
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+
+/// Magic of files whose footer is encrypted, in place of `PAR1`.
+const ENCRYPTED_MAGIC: &[u8; 4] = b"PARE";
+/// Length and magic at the very end of a file.
+const FOOTER_TAIL_SIZE: usize = 8;
+const PLAINTEXT_MAGIC: &[u8; 4] = b"PAR1";
+/// Sizes of AES keys, in bytes.
+const KEY_SIZES: [usize; 3] = [16, 24, 32];
+/// Size of the keys the Parquet reader decrypts with, as it only implements
+/// AES-128.
+const SUPPORTED_KEY_SIZE: usize = 16;
+/// Key file line giving the footer key rather than that of a column.
+const FOOTER_KEY_NAME: &str = "footer";
+/// Prefix of keys given as hex digits rather than characters.
+const HEX_PREFIX: &str = "hex";
+
+/// An AES key of 128, 192 or 256 bits, given either as characters, e.g.
+/// `0123456789012345`, or as hex digits after `hex:`, e.g.
+/// `hex:30313233343536373839303132333435`.
+#[derive(Clone)]
+pub struct Key(Vec<u8>);
+
+impl FromStr for Key {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.trim();
+        let bytes = match s
+            .split_once(':')
+            .filter(|(prefix, _)| *prefix == HEX_PREFIX)
+        {
+            Some((_, hex)) => {
+                let hex = hex.trim();
+                if hex.len() % 2 != 0 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(format!(
+                        "expected an even number of hex digits, got `{hex}`"
+                    ));
+                }
+                (0..hex.len() / 2)
+                    .map(|i| u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap())
+                    .collect()
+            }
+            None => s.as_bytes().to_vec(),
+        };
+        if !KEY_SIZES.contains(&bytes.len()) {
+            return Err(
+                "expected a 128, 192 or 256-bit key, as 16, 24 or 32 characters or as `hex:` \
+                 followed by 32, 48 or 64 hex digits"
+                    .to_string(),
+            );
+        }
+        Ok(Self(bytes))
+    }
+}
+
+/// Keys are kept out of logs and panics.
+impl Debug for Key {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("Key(..)")
+    }
+}
+
+/// Key of a column, e.g. `address.city:0123456789abcdef` or
+/// `address.city:hex:30313233343536373839616263646566`.
+#[derive(Clone, Debug)]
+pub struct ColumnKey {
+    pub column: String,
+    pub key: Key,
+}
+
+impl FromStr for ColumnKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let Some((column, key)) = s.rsplit_once(':') else {
+            return Err(format!("expected `column:key`, got `{s}`"));
+        };
+        // The key itself follows the last colon, even when given in hex.
+        let (column, key) = match column.rsplit_once(':') {
+            Some((column, prefix)) if prefix.trim() == HEX_PREFIX => {
+                (column, format!("{HEX_PREFIX}:{key}"))
+            }
+            _ => (column, key.to_string()),
+        };
+        Ok(Self {
+            column: column.trim().to_string(),
+            key: key.parse()?,
+        })
+    }
+}
+
+/// Keys to read files encrypted with Parquet modular encryption.
+#[derive(Clone, Debug, Default)]
+pub struct Keys {
+    pub footer: Option<Key>,
+    /// Keys by column path, e.g. `address.city`.
+    pub columns: BTreeMap<String, Key>,
+}
+
+impl Keys {
+    /// Gathers the keys of a key file, if any, overridden by those given on
+    /// the command line.
+    pub fn new(
+        key_file: Option<&Path>,
+        footer: Option<Key>,
+        columns: &[ColumnKey],
+    ) -> Result<Self> {
+        let mut keys = match key_file {
+            Some(path) => Self::read(path)?,
+            None => Self::default(),
+        };
+        if footer.is_some() {
+            keys.footer = footer;
+        }
+        for column in columns {
+            keys.columns
+                .insert(column.column.clone(), column.key.clone());
+        }
+        Ok(keys)
+    }
+
+    /// Reads a key file, with one `name: key` per line, `name` being either
+    /// `footer` or a column path. Blank lines and lines starting with `#` are
+    /// skipped.
+    fn read(path: &Path) -> Result<Self> {
+        let mut keys = Self::default();
+        for (i, line) in fs::read_to_string(path)?.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid =
+                |e: String| Error::InvalidKey(format!("{}:{}: {e}", path.display(), i + 1));
+            let ColumnKey { column, key } = line.parse().map_err(invalid)?;
+            if column == FOOTER_KEY_NAME {
+                keys.footer = Some(key);
+            } else {
+                keys.columns.insert(column, key);
+            }
+        }
+        Ok(keys)
+    }
+
+    /// Plaintext footers can be read without the footer key, as long as
+    /// their signature isn't verified.
+    fn decryption_properties(&self, verify_footer: bool) -> Result<FileDecryptionProperties> {
+        let keys = self.footer.iter().chain(self.columns.values());
+        if let Some(key) = keys.into_iter().find(|k| k.0.len() != SUPPORTED_KEY_SIZE) {
+            return Err(Error::UnsupportedKeySize(key.0.len() * 8));
+        }
+        let mut builder = match self.footer.as_ref() {
+            Some(key) => FileDecryptionProperties::builder(key.0.clone()),
+            None => FileDecryptionProperties::builder(vec![0; 16]),
+        };
+        if !verify_footer || self.footer.is_none() {
+            builder = builder.disable_footer_signature_verification();
+        }
+        for (column, key) in self.columns.iter() {
+            builder = builder.with_column_key(column, key.0.clone());
+        }
+        Ok(builder.build()?)
+    }
+}
+
+/// How a Parquet file is encrypted.
+#[derive(Clone, Debug)]
+pub struct Encryption {
+    /// `AES_GCM_V1` or `AES_GCM_CTR_V1`.
+    pub algorithm: &'static str,
+    pub encrypted_footer: bool,
+    pub columns: Vec<EncryptedColumn>,
+}
+
+#[derive(Clone, Debug)]
+pub struct EncryptedColumn {
+    pub path: String,
+    /// Whether it's encrypted with its own key rather than the footer key.
+    pub column_key: bool,
+    /// Whether its key was given.
+    pub decrypted: bool,
+}
+
+impl Encryption {
+    pub fn column(&self, path: &str) -> Option<&EncryptedColumn> {
+        self.columns.iter().find(|c| c.path == path)
+    }
+
+    /// Columns whose key wasn't given.
+    pub fn missing_keys(&self) -> Vec<&str> {
+        self.columns
+            .iter()
+            .filter(|c| !c.decrypted)
+            .map(|c| c.path.as_str())
+            .collect()
+    }
+}
+
+/// A local Parquet file written with modular encryption.
+#[derive(Clone, Debug)]
+pub struct EncryptedFile {
+    pub path: PathBuf,
+    pub keys: Arc<Keys>,
+}
+
+impl EncryptedFile {
+    /// Whether the Parquet file at `path` is encrypted, either its footer or
+    /// some of its columns.
+    ///
+    /// Files that can't be read as Parquet aren't, leaving the error to the
+    /// reader.
+    pub fn is_encrypted(path: &Path) -> bool {
+        let is_encrypted = || -> Result<bool> {
+            let (footer, magic) = read_footer(&mut File::open(path)?)?;
+            if magic == *ENCRYPTED_MAGIC {
+                return Ok(true);
+            }
+            let metadata: FileMetaData = decode(&footer)?;
+            Ok(metadata.encryption_algorithm.is_some())
+        };
+        is_encrypted().unwrap_or(false)
+    }
+
+    /// Reads the metadata and prepares a reader over `columns`, or every
+    /// column when `None`.
+    ///
+    /// When keys are missing from a file with a plaintext footer, the columns
+    /// they encrypt are left out of the reader, and their metadata is
+    /// returned without any values.
+    pub fn reader(
+        &self,
+        columns: Option<&[String]>,
+        batch_size: usize,
+    ) -> Result<(Arc<ParquetMetaData>, Encryption, ParquetRecordBatchReader)> {
+        let mut file = File::open(&self.path)?;
+        let footer = Footer::read(&mut file)?;
+        if footer.encrypted_footer && self.keys.footer.is_none() {
+            return Err(Error::MissingFooterKey(self.path.clone()));
+        }
+
+        let options = ArrowReaderOptions::new()
+            .with_file_decryption_properties(self.keys.decryption_properties(true)?);
+        let error =
+            match ParquetRecordBatchReaderBuilder::try_new_with_options(file.try_clone()?, options)
+            {
+                Ok(builder) => {
+                    let metadata = Arc::clone(builder.metadata());
+                    let chunks = metadata.row_groups().first().map_or(vec![], |row_group| {
+                        row_group
+                            .columns()
+                            .iter()
+                            .map(|c| {
+                                let column_key = c.crypto_metadata().map(|m| {
+                                    matches!(m, CryptoMetaData::EncryptionWithColumnKey(_))
+                                });
+                                (c.column_path().string(), column_key)
+                            })
+                            .collect()
+                    });
+                    let encryption = footer.encryption(chunks, &self.keys);
+                    let mask = projection(builder.schema(), builder.parquet_schema(), columns);
+                    let reader = builder
+                        .with_projection(mask)
+                        .with_batch_size(batch_size)
+                        .build()?;
+                    return Ok((metadata, encryption, reader));
+                }
+                Err(e) => e,
+            };
+
+        // The reader fails on columns whose key is missing. Their metadata can
+        // be left out of plaintext footers, but encrypted footers are only
+        // decrypted along with that of every column.
+        if footer.encrypted_footer {
+            return Err(error.into());
+        }
+        let metadata: FileMetaData = decode(&footer.bytes)?;
+        let schema = SchemaDescriptor::new(types::from_thrift(&metadata.schema)?);
+        let chunks = metadata.row_groups.first().map_or(vec![], |row_group| {
+            row_group
+                .columns
+                .iter()
+                .zip(schema.columns())
+                .map(|(chunk, descr)| {
+                    let column_key = chunk
+                        .crypto_metadata
+                        .as_ref()
+                        .map(|m| matches!(m, ColumnCryptoMetaData::ENCRYPTIONWITHCOLUMNKEY(_)));
+                    (descr.path().string(), column_key)
+                })
+                .collect()
+        });
+        let encryption = footer.encryption(chunks, &self.keys);
+        if encryption.missing_keys().is_empty() {
+            return Err(error.into());
+        }
+
+        let metadata = Arc::new(footer.into_partial(metadata, &schema, &self.keys)?);
+        let arrow_metadata =
+            ArrowReaderMetadata::try_new(Arc::clone(&metadata), Default::default())?;
+        let schema = metadata.file_metadata().schema_descr();
+        let leaves = (0..schema.num_columns()).filter(|&i| {
+            let root = schema.get_column_root(i).name().to_string();
+            let path = schema.column(i).path().string();
+            encryption.column(&path).is_none_or(|c| c.decrypted)
+                && columns.is_none_or(|c| c.contains(&root))
+        });
+        let mask = ProjectionMask::leaves(schema, leaves);
+        let reader = ParquetRecordBatchReaderBuilder::new_with_metadata(file, arrow_metadata)
+            .with_projection(mask)
+            .with_batch_size(batch_size)
+            .build()?;
+        Ok((metadata, encryption, reader))
+    }
+}
+
+/// The plaintext part of the footer of an encrypted file.
+struct Footer {
+    /// The footer, without its length and magic.
+    bytes: Vec<u8>,
+    algorithm: EncryptionAlgorithm,
+    encrypted_footer: bool,
+}
+
+impl Footer {
+    fn read(file: &mut File) -> Result<Self> {
+        let (bytes, magic) = read_footer(file)?;
+        let encrypted_footer = magic == *ENCRYPTED_MAGIC;
+        let algorithm = if encrypted_footer {
+            decode::<FileCryptoMetaData>(&bytes)?.encryption_algorithm
+        } else {
+            decode::<FileMetaData>(&bytes)?
+                .encryption_algorithm
+                .ok_or_else(|| Error::DecryptionError("an unencrypted file".to_string()))?
+        };
+        Ok(Self {
+            bytes,
+            algorithm,
+            encrypted_footer,
+        })
+    }
+
+    /// Which of the columns, given by path along with whether they're
+    /// encrypted with their own key, if at all, can be decrypted.
+    fn encryption(&self, chunks: Vec<(String, Option<bool>)>, keys: &Keys) -> Encryption {
+        let columns = chunks
+            .into_iter()
+            .filter_map(|(path, column_key)| {
+                let column_key = column_key?;
+                let decrypted = if column_key {
+                    keys.columns.contains_key(&path)
+                } else {
+                    keys.footer.is_some()
+                };
+                Some(EncryptedColumn {
+                    path,
+                    column_key,
+                    decrypted,
+                })
+            })
+            .collect();
+        let algorithm = match self.algorithm {
+            EncryptionAlgorithm::AESGCMV1(_) => "AES_GCM_V1",
+            EncryptionAlgorithm::AESGCMCTRV1(_) => "AES_GCM_CTR_V1",
+        };
+        Encryption {
+            algorithm,
+            encrypted_footer: self.encrypted_footer,
+            columns,
+        }
+    }
+
+    /// `metadata`, with that of the columns whose key is missing replaced by
+    /// metadata without any values, so the other columns can still be read.
+    ///
+    /// The footer is rewritten in plaintext, so its signature can't be
+    /// verified.
+    fn into_partial(
+        self,
+        mut metadata: FileMetaData,
+        schema: &SchemaDescriptor,
+        keys: &Keys,
+    ) -> Result<ParquetMetaData> {
+        for row_group in metadata.row_groups.iter_mut() {
+            for (chunk, descr) in row_group.columns.iter_mut().zip(schema.columns()) {
+                let decrypted = match chunk.crypto_metadata {
+                    None => continue,
+                    Some(ColumnCryptoMetaData::ENCRYPTIONWITHCOLUMNKEY(_)) => {
+                        keys.columns.contains_key(&descr.path().string())
+                    }
+                    Some(ColumnCryptoMetaData::ENCRYPTIONWITHFOOTERKEY(_)) => keys.footer.is_some(),
+                };
+                if !decrypted && chunk.encrypted_column_metadata.take().is_some() {
+                    chunk.meta_data = Some(placeholder(descr));
+                }
+            }
+        }
+        metadata.encryption_algorithm = Some(self.algorithm);
+
+        let mut buf = vec![];
+        let mut protocol = TCompactOutputProtocol::new(&mut buf);
+        metadata.write_to_out_protocol(&mut protocol)?;
+        protocol.flush()?;
+        let length = buf.len() as u32;
+        buf.extend(length.to_le_bytes());
+        buf.extend(PLAINTEXT_MAGIC);
+        let properties = keys.decryption_properties(false)?;
+        Ok(ParquetMetaDataReader::new()
+            .with_decryption_properties(Some(&properties))
+            .parse_and_finish(&Bytes::from(buf))?)
+    }
+}
+
+/// Reads the footer, without its length and magic, and the magic.
+fn read_footer(file: &mut File) -> Result<(Vec<u8>, [u8; 4])> {
+    let mut tail = [0; FOOTER_TAIL_SIZE];
+    file.seek(SeekFrom::End(-(FOOTER_TAIL_SIZE as i64)))?;
+    file.read_exact(&mut tail)?;
+    let length = u32::from_le_bytes(tail[..4].try_into().unwrap()) as i64;
+    let size = file.metadata()?.len() as i64;
+    if length > size - FOOTER_TAIL_SIZE as i64 {
+        return Err(Error::DecryptionError(
+            "the footer, longer than the file".to_string(),
+        ));
+    }
+    let mut footer = vec![0; length as usize];
+    file.seek(SeekFrom::End(-(FOOTER_TAIL_SIZE as i64) - length))?;
+    file.read_exact(&mut footer)?;
+    Ok((footer, tail[4..].try_into().unwrap()))
+}
+
+fn decode<T: TSerializable>(buf: &[u8]) -> Result<T> {
+    let mut buf = buf;
+    Ok(T::read_from_in_protocol(&mut TCompactInputProtocol::new(
+        &mut buf,
+    ))?)
+}
+
+/// Column metadata without any values, for columns that can't be decrypted.
+fn placeholder(descr: &ColumnDescriptor) -> ColumnMetaData {
+    ColumnMetaData::new(
+        descr.physical_type().into(),
+        vec![],
+        descr.path().parts().to_vec(),
+        CompressionCodec::UNCOMPRESSED,
+        0,
+        0,
+        0,
+        None,
+        0,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::{
+        array::{ArrayRef, Int64Array, RecordBatch, StringArray},
+        util::pretty::pretty_format_batches,
+    };
+    use parquet::{
+        arrow::ArrowWriter, encryption::encrypt::FileEncryptionProperties,
+        file::properties::WriterProperties,
+    };
+
+    use super::*;
+
+    const FOOTER_KEY: &str = "0123456789012345";
+    const NAME_KEY: &str = "1234567890123450";
+
+    /// Writes a file whose `name` column is encrypted with its own key, and
+    /// whose `id` column isn't encrypted.
+    fn write(path: &Path, plaintext_footer: bool) {
+        let batch = RecordBatch::try_from_iter([
+            ("id", Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef),
+            (
+                "name",
+                Arc::new(StringArray::from(vec!["a", "b"])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+        let encryption = FileEncryptionProperties::builder(FOOTER_KEY.as_bytes().to_vec())
+            .with_column_key("name", NAME_KEY.as_bytes().to_vec())
+            .with_plaintext_footer(plaintext_footer)
+            .build()
+            .unwrap();
+        let properties = WriterProperties::builder()
+            .with_file_encryption_properties(encryption)
+            .build();
+        let mut writer = ArrowWriter::try_new(
+            File::create(path).unwrap(),
+            batch.schema(),
+            Some(properties),
+        )
+        .unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+    }
+
+    fn read(path: &Path, footer: Option<&str>, columns: &[&str]) -> Result<(Encryption, String)> {
+        let keys = Keys::new(
+            None,
+            footer.map(|k| k.parse().unwrap()),
+            &columns
+                .iter()
+                .map(|c| c.parse().unwrap())
+                .collect::<Vec<_>>(),
+        )?;
+        let file = EncryptedFile {
+            path: path.to_path_buf(),
+            keys: Arc::new(keys),
+        };
+        let (_, encryption, reader) = file.reader(None, 1024)?;
+        let batches = reader.collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok((encryption, pretty_format_batches(&batches)?.to_string()))
+    }
+
+    #[test]
+    fn parses_keys() {
+        assert_eq!(FOOTER_KEY.parse::<Key>().unwrap().0, FOOTER_KEY.as_bytes());
+        let key: Key = "hex:000102030405060708090a0b0c0d0e0f".parse().unwrap();
+        assert_eq!(key.0, (0..16).collect::<Vec<u8>>());
+        assert_eq!(
+            format!("hex:{}", "ab".repeat(24))
+                .parse::<Key>()
+                .unwrap()
+                .0
+                .len(),
+            24
+        );
+        assert_eq!("x".repeat(32).parse::<Key>().unwrap().0.len(), 32);
+        // Without the prefix, hex digits are characters.
+        assert_eq!(
+            "ab".repeat(8).parse::<Key>().unwrap().0,
+            b"abababababababab"
+        );
+        assert!("0123".parse::<Key>().is_err());
+        assert!(format!("hex:{}", "a".repeat(33)).parse::<Key>().is_err());
+        assert!(format!("hex:{}", "g".repeat(32)).parse::<Key>().is_err());
+
+        let key: ColumnKey = "address.city: 0123456789012345".parse().unwrap();
+        assert_eq!(key.column, "address.city");
+        let key: ColumnKey = "a:b:hex:000102030405060708090a0b0c0d0e0f".parse().unwrap();
+        assert_eq!(key.column, "a:b");
+        assert_eq!(key.key.0, (0..16).collect::<Vec<u8>>());
+        assert!("0123456789012345".parse::<ColumnKey>().is_err());
+    }
+
+    #[test]
+    fn reads_with_every_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("encrypted.parquet");
+        write(&path, false);
+        assert!(EncryptedFile::is_encrypted(&path));
+
+        let name_key = format!("name:{NAME_KEY}");
+        let (encryption, rows) = read(&path, Some(FOOTER_KEY), &[&name_key]).unwrap();
+        assert!(encryption.encrypted_footer);
+        assert_eq!(encryption.algorithm, "AES_GCM_V1");
+        assert!(encryption.missing_keys().is_empty());
+        assert!(encryption.column("id").is_none());
+        assert!(encryption.column("name").unwrap().column_key);
+        assert!(rows.contains("| 2  | b    |"), "{rows}");
+    }
+
+    #[test]
+    fn leaves_out_columns_without_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("encrypted.parquet");
+        write(&path, true);
+        let (encryption, rows) = read(&path, Some(FOOTER_KEY), &[]).unwrap();
+        assert!(!encryption.encrypted_footer);
+        assert_eq!(encryption.missing_keys(), ["name"]);
+        assert!(rows.contains("| 2  |"), "{rows}");
+        assert!(!rows.contains("name"), "{rows}");
+
+        write(&path, false);
+        let error = read(&path, Some(FOOTER_KEY), &[]).unwrap_err().to_string();
+        assert!(error.contains("'name'"), "{error}");
+    }
+
+    #[test]
+    fn fails_on_missing_and_wrong_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("encrypted.parquet");
+        write(&path, false);
+        assert!(matches!(
+            read(&path, None, &[]),
+            Err(Error::MissingFooterKey(_))
+        ));
+        let error = read(&path, Some("0123456789012399"), &[])
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("footer"), "{error}");
+        assert!(read(&path, Some(FOOTER_KEY), &["name:0123456789012399"]).is_err());
+        assert!(matches!(
+            read(&path, Some(&"x".repeat(32)), &[]),
+            Err(Error::UnsupportedKeySize(256))
+        ));
+    }
+}
@@ -1,3 +1,11 @@
+use std::{
+    cell::Cell,
+    panic::{self, AssertUnwindSafe, PanicHookInfo},
+    path::PathBuf,
+    sync::Once,
+    thread,
+};
+
 use arrow::error::ArrowError;
 use parquet::errors::ParquetError;
 
@@ -8,14 +16,264 @@ pub enum Error {
     IoError(#[from] std::io::Error),
 
     #[error("parquet error: `{0}`")]
-    ParquetError(#[from] ParquetError),
+    ParquetError(ParquetError),
 
     #[error("arrow error: `{0}`")]
-    ArrowError(#[from] ArrowError),
+    ArrowError(ArrowError),
+
+    #[error("query error: `{0}`")]
+    DataFusionError(#[from] datafusion::error::DataFusionError),
+
+    /// Error that may occur while talking to an object store.
+    #[error("object store error: `{0}`")]
+    ObjectStoreError(#[from] object_store::Error),
+
+    #[error("invalid URL: `{0}`")]
+    UrlError(#[from] url::ParseError),
+
+    #[error("JSON error: `{0}`")]
+    JsonError(#[from] serde_json::Error),
+
+    #[error("thrift error: `{0}`")]
+    ThriftError(#[from] thrift::Error),
+
+    /// Error that may occur while reading the rules of `parqour lint`.
+    #[error("invalid lint config: {0}")]
+    TomlError(#[from] toml::de::Error),
+
+    /// Boxed, as it's much larger than the other variants.
+    #[error("avro error: `{0}`")]
+    AvroError(Box<apache_avro::Error>),
+
+    /// A file is too short to hold a Parquet footer.
+    #[error(
+        "`{file}` is only {size} bytes, too short for a Parquet footer: it's either empty or \
+         was cut off while being written or copied"
+    )]
+    TruncatedFooter { file: String, size: u64 },
+
+    /// A file doesn't end with the Parquet magic.
+    #[error(
+        "`{file}` ends with {found} rather than the Parquet magic `PAR1`: it was either cut off \
+         while being written or copied, or isn't a Parquet file"
+    )]
+    BadMagic { file: String, found: String },
+
+    /// The footer of a file gives a metadata length that doesn't fit in the
+    /// file.
+    #[error(
+        "the footer of `{file}` gives {length} bytes of metadata, which don't fit in its {size} \
+         bytes: the file was cut off or its footer is corrupt"
+    )]
+    InvalidMetadataLength {
+        file: String,
+        length: u64,
+        size: u64,
+    },
+
+    /// A page doesn't match the CRC checksum in its header.
+    #[error(
+        "a page doesn't match its CRC checksum, so the file is corrupt: open it with --salvage \
+         to read the row groups and columns that can still be decoded"
+    )]
+    PageCrcMismatch,
+
+    /// A directory was opened that doesn't contain any Parquet files.
+    #[error("no parquet files found in `{}`", .0.display())]
+    NoParquetFiles(PathBuf),
+
+    /// A partition filter names a key the files aren't partitioned by.
+    #[error("unknown partition key `{key}`: {}", expected_keys(.keys))]
+    UnknownPartitionKey { key: String, keys: Vec<String> },
+
+    /// A file of a partitioned directory or table has a column of another
+    /// type than the files before it.
+    #[error("`{}` doesn't match the schema of the other files: {1}", .0.display())]
+    MismatchedPartitionFile(PathBuf, String),
+
+    /// The requested version of a Delta table can't be rebuilt from its log.
+    #[error("no version of the Delta table {0}")]
+    NoDeltaVersion(String),
+
+    /// A Delta table needs a reader feature that isn't implemented, without
+    /// which its rows would be read wrong.
+    #[error("the Delta table uses {0}, which parqour can't read")]
+    UnsupportedDeltaFeature(String),
+
+    /// A directory was opened as an Iceberg table without a metadata file.
+    #[error("no Iceberg metadata found in `{}`", .0.display())]
+    NoIcebergMetadata(PathBuf),
+
+    /// The requested snapshot isn't in the metadata of an Iceberg table.
+    #[error("no snapshot of the Iceberg table {0}")]
+    NoIcebergSnapshot(String),
+
+    /// A file with an encrypted footer was opened without the footer key.
+    #[error("`{}` has an encrypted footer: pass its key with --footer-key", .0.display())]
+    MissingFooterKey(PathBuf),
+
+    /// A key given on the command line or in a key file isn't an AES key.
+    #[error("invalid key: {0}")]
+    InvalidKey(String),
+
+    /// A key is of a size the Parquet reader can't decrypt with yet.
+    #[error("{0}-bit keys aren't supported yet, only 128-bit ones")]
+    UnsupportedKeySize(usize),
+
+    /// Part of an encrypted file couldn't be decrypted, usually because of a
+    /// wrong key.
+    #[error("failed to decrypt {0}: is the key right?")]
+    DecryptionError(String),
+
+    /// Several rows of a file compared by `parqour diff --keys` have the same
+    /// key, so they can't be matched with those of the other file.
+    #[error("`{}` has several rows with {1}: diff on keys that identify its rows", .0.display())]
+    DuplicateKey(PathBuf, String),
+
+    /// The files compared by `parqour diff` differ, as was already reported.
+    #[error("the files differ")]
+    FilesDiffer,
+
+    /// Some of the files checked by `parqour validate` have issues, which
+    /// were already reported.
+    #[error("{0} of {1} files failed validation")]
+    ValidationFailed(usize, usize),
+
+    /// A file other than Parquet was given to `parqour lint`.
+    #[error("only Parquet files can be linted, not {0} files")]
+    UnsupportedLintFormat(&'static str),
+
+    /// Some of the files checked by `parqour lint` broke rules set to
+    /// `error`, which were already reported.
+    #[error("{0} of {1} files failed linting")]
+    LintFailed(usize, usize),
+
+    /// The config file of the terminal UI can't be parsed, or binds an
+    /// unknown key or command.
+    #[error("invalid config `{}`: {1}", .0.display())]
+    InvalidConfig(PathBuf, String),
+
+    /// The native clipboard backend was chosen, but none of the clipboard
+    /// commands it tries is installed.
+    #[error("no clipboard command found: install one of pbcopy, wl-copy, xclip or xsel")]
+    NoClipboard,
+
+    /// No file was given and nothing was piped to stdin.
+    #[error("no FILE given and nothing piped to stdin")]
+    NoInput,
+
+    /// SQL was run against a file format the query engine can't read.
+    #[error("SQL queries aren't supported on {0} files")]
+    UnsupportedQueryFormat(&'static str),
+
+    /// Rows were exported to a file whose extension names no format that can
+    /// be written.
+    #[error(
+        "can't export to `{}`: use a .parquet, .csv, .tsv, .jsonl, .arrow or .arrows file",
+        .0.display()
+    )]
+    UnsupportedExportFormat(PathBuf),
+
+    /// Rows were exported over the file, or into the table, they're read from.
+    #[error("can't export to `{}`: it's being read from", .0.display())]
+    ExportToSource(PathBuf),
+
+    /// Rows were exported to a file that exists, without `:export!`.
+    #[error("`{}` already exists: use :export! to overwrite it", .0.display())]
+    ExportTargetExists(PathBuf),
+
+    /// The sessions file couldn't be read or parsed, which is then left as
+    /// it is.
+    #[error("can't read `{}`: {1}, so sessions won't be saved", .0.display())]
+    InvalidSessions(PathBuf, String),
+
+    /// A column was requested that isn't in the file.
+    #[error("unknown column `{0}`")]
+    UnknownColumn(String),
 
     /// Error that may occur while receiving messages from the channel.
     #[error("Channel receive error: `{0}`")]
     ChannelReceiveError(#[from] std::sync::mpsc::RecvError),
 }
 
+fn expected_keys(keys: &[String]) -> String {
+    if keys.is_empty() {
+        return "the files aren't partitioned".to_string();
+    }
+    format!("expected one of {}", keys.join(", "))
+}
+
+/// Message of the parquet crate when a page doesn't match its checksum.
+const PAGE_CRC_MISMATCH: &str = "Page CRC checksum mismatch";
+
+impl From<ParquetError> for Error {
+    fn from(e: ParquetError) -> Self {
+        match e {
+            ParquetError::General(message) if message == PAGE_CRC_MISMATCH => Self::PageCrcMismatch,
+            e => Self::ParquetError(e),
+        }
+    }
+}
+
+/// Parquet errors reach readers of record batches as strings.
+impl From<ArrowError> for Error {
+    fn from(e: ArrowError) -> Self {
+        match e {
+            ArrowError::ParquetError(message) if message.contains(PAGE_CRC_MISMATCH) => {
+                Self::PageCrcMismatch
+            }
+            e => Self::ArrowError(e),
+        }
+    }
+}
+
+impl From<apache_avro::Error> for Error {
+    fn from(e: apache_avro::Error) -> Self {
+        Self::AvroError(Box::new(e))
+    }
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
+
+thread_local! {
+    /// Whether a panic on this thread would be caught by [`catch_panic`].
+    static CATCHING_PANIC: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs `f`, returning an error rather than unwinding when it panics, as
+/// the parquet crate does on some corrupt pages, without the panic being
+/// reported.
+///
+/// Only panics of `f`, on this thread, are kept quiet: those on any other
+/// thread, e.g. one profiling a column for the terminal UI, still go to the
+/// panic hook.
+pub(crate) fn catch_panic<T>(f: impl FnOnce() -> T) -> thread::Result<T> {
+    static QUIET_HOOK: Once = Once::new();
+    QUIET_HOOK.call_once(|| set_panic_hook(panic::take_hook()));
+    let catching = CATCHING_PANIC.replace(true);
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING_PANIC.set(catching);
+    result
+}
+
+/// Sets the panic hook, leaving out the panics [`catch_panic`] catches.
+pub(crate) fn set_panic_hook(hook: Box<dyn Fn(&PanicHookInfo) + Send + Sync>) {
+    panic::set_hook(Box::new(move |info| {
+        if !CATCHING_PANIC.get() {
+            hook(info);
+        }
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catches_panics() {
+        assert!(catch_panic(|| panic!("corrupt page")).is_err());
+        assert_eq!(catch_panic(|| 1).unwrap(), 1);
+        // Only while catching.
+        assert!(!CATCHING_PANIC.get());
+    }
+}
@@ -0,0 +1,211 @@
+use std::{
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use arrow::{
+    array::RecordBatchReader,
+    csv,
+    ipc::writer::{FileWriter, StreamWriter},
+    json::LineDelimitedWriter,
+};
+use parquet::arrow::ArrowWriter;
+use tempfile::NamedTempFile;
+
+use crate::{
+    error::{Error, Result},
+    source::Format,
+};
+
+/// Writes the batches of `reader` to `path`, in the format its extension
+/// names, returning the number of rows written.
+///
+/// The rows are written to a temporary file next to `path`, which only
+/// replaces it once complete, and only if `overwrite`. `path` may not be, or
+/// be inside, `source`, which the rows are read from.
+pub fn export(
+    reader: Box<dyn RecordBatchReader + Send>,
+    path: &Path,
+    source: Option<&Path>,
+    overwrite: bool,
+) -> Result<usize> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase);
+    let format = match extension.as_deref() {
+        Some("parquet" | "pq") => Format::Parquet,
+        Some(
+            extension @ ("csv" | "tsv" | "json" | "jsonl" | "ndjson" | "arrow" | "feather" | "ipc"
+            | "arrows"),
+        ) => Format::detect(Some(extension), &[]),
+        _ => return Err(Error::UnsupportedExportFormat(path.to_path_buf())),
+    };
+    let target = canonicalize(path)?;
+    if let Some(source) = source.and_then(|s| s.canonicalize().ok()) {
+        if target.starts_with(source) {
+            return Err(Error::ExportToSource(path.to_path_buf()));
+        }
+    }
+    if !overwrite && target.exists() {
+        return Err(Error::ExportTargetExists(path.to_path_buf()));
+    }
+
+    let schema = reader.schema();
+    let mut temp = NamedTempFile::new_in(target.parent().unwrap_or(Path::new(".")))?;
+    let file = BufWriter::new(temp.as_file_mut());
+    let mut rows = 0;
+    let mut file = match format {
+        Format::Parquet => {
+            let mut writer = ArrowWriter::try_new(file, schema, None)?;
+            for batch in reader {
+                let batch = batch?;
+                rows += batch.num_rows();
+                writer.write(&batch)?;
+            }
+            writer.into_inner()?
+        }
+        Format::Csv { delimiter } => {
+            let mut writer = csv::WriterBuilder::new()
+                .with_delimiter(delimiter)
+                .build(file);
+            for batch in reader {
+                let batch = batch?;
+                rows += batch.num_rows();
+                writer.write(&batch)?;
+            }
+            writer.into_inner()
+        }
+        Format::Json => {
+            let mut writer = LineDelimitedWriter::new(file);
+            for batch in reader {
+                let batch = batch?;
+                rows += batch.num_rows();
+                writer.write(&batch)?;
+            }
+            writer.finish()?;
+            writer.into_inner()
+        }
+        Format::ArrowFile => {
+            let mut writer = FileWriter::try_new(file, &schema)?;
+            for batch in reader {
+                let batch = batch?;
+                rows += batch.num_rows();
+                writer.write(&batch)?;
+            }
+            writer.into_inner()?
+        }
+        Format::ArrowStream => {
+            let mut writer = StreamWriter::try_new(file, &schema)?;
+            for batch in reader {
+                let batch = batch?;
+                rows += batch.num_rows();
+                writer.write(&batch)?;
+            }
+            writer.into_inner()?
+        }
+    };
+    // Dropping it would flush it too, but ignore errors.
+    file.flush()?;
+    drop(file);
+    if overwrite {
+        temp.persist(&target).map_err(|e| e.error)?;
+    } else {
+        temp.persist_noclobber(&target)
+            .map_err(|e| match e.error.kind() {
+                io::ErrorKind::AlreadyExists => Error::ExportTargetExists(path.to_path_buf()),
+                _ => e.error.into(),
+            })?;
+    }
+    Ok(rows)
+}
+
+/// `path` made absolute, with symbolic links resolved, even if it doesn't
+/// exist yet.
+fn canonicalize(path: &Path) -> Result<PathBuf> {
+    if path.exists() {
+        return Ok(path.canonicalize()?);
+    }
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let name = path
+        .file_name()
+        .ok_or_else(|| Error::UnsupportedExportFormat(path.to_path_buf()))?;
+    Ok(parent.canonicalize()?.join(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, sync::Arc};
+
+    use arrow::array::{ArrayRef, Int32Array, RecordBatch, RecordBatchIterator};
+
+    use super::*;
+
+    fn reader() -> Box<dyn RecordBatchReader + Send> {
+        let batch = RecordBatch::try_from_iter([(
+            "id",
+            Arc::new(Int32Array::from(vec![1, 2, 3])) as ArrayRef,
+        )])
+        .unwrap();
+        let schema = batch.schema();
+        Box::new(RecordBatchIterator::new([Ok(batch)], schema))
+    }
+
+    #[test]
+    fn exports_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let csv = dir.path().join("rows.csv");
+        assert_eq!(export(reader(), &csv, None, false).unwrap(), 3);
+        assert_eq!(fs::read_to_string(&csv).unwrap(), "id\n1\n2\n3\n");
+
+        let parquet = dir.path().join("rows.parquet");
+        assert_eq!(export(reader(), &parquet, None, false).unwrap(), 3);
+        assert!(fs::read(&parquet).unwrap().starts_with(b"PAR1"));
+        // No temporary files are left behind.
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+
+        let unknown = dir.path().join("rows.txt");
+        assert!(matches!(
+            export(reader(), &unknown, None, false),
+            Err(Error::UnsupportedExportFormat(_))
+        ));
+    }
+
+    #[test]
+    fn overwrites_only_when_asked() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rows.jsonl");
+        fs::write(&path, "kept").unwrap();
+        assert!(matches!(
+            export(reader(), &path, None, false),
+            Err(Error::ExportTargetExists(_))
+        ));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "kept");
+
+        assert_eq!(export(reader(), &path, None, true).unwrap(), 3);
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 3);
+    }
+
+    #[test]
+    fn refuses_the_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("rows.csv");
+        fs::write(&source, "id\n1\n").unwrap();
+        // The same file, by another path.
+        let path = dir.path().join(".").join("rows.csv");
+        assert!(matches!(
+            export(reader(), &path, Some(&source), true),
+            Err(Error::ExportToSource(_))
+        ));
+        // Or a file inside a table being read.
+        let path = dir.path().join("part-0.csv");
+        assert!(matches!(
+            export(reader(), &path, Some(dir.path()), true),
+            Err(Error::ExportToSource(_))
+        ));
+        assert_eq!(fs::read_to_string(&source).unwrap(), "id\n1\n");
+    }
+}
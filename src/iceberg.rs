@@ -0,0 +1,946 @@
+use apache_avro::{types::Value as AvroValue, Reader};
+use arrow::temporal_conversions::{
+    date32_to_datetime, timestamp_ns_to_datetime, timestamp_us_to_datetime,
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    delta::TimeTravel,
+    error::{Error, Result},
+    partition::{PartitionFile, Partitioning},
+};
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+const METADATA_DIR: &str = "metadata";
+const METADATA_SUFFIX: &str = ".metadata.json";
+/// Written by Hadoop catalogs next to the metadata files.
+const VERSION_HINT: &str = "version-hint.text";
+
+/// An Apache Iceberg table in a local warehouse, read at a single snapshot.
+#[derive(Clone, Debug)]
+pub struct IcebergTable {
+    pub root: PathBuf,
+    /// The `metadata.json` file the table was read from.
+    pub metadata_path: PathBuf,
+    pub format_version: i32,
+    pub table_uuid: Option<String>,
+    pub location: String,
+    pub last_updated_ms: Option<i64>,
+    pub properties: BTreeMap<String, String>,
+    pub current_schema_id: i32,
+    /// Every schema the table has had, oldest first.
+    pub schemas: Vec<Schema>,
+    pub default_spec_id: i32,
+    pub partition_specs: Vec<PartitionSpec>,
+    /// Snapshots still in the metadata, newest first.
+    pub snapshots: Vec<Snapshot>,
+    /// The snapshot being read, `None` for a table without any.
+    pub snapshot_id: Option<i64>,
+    /// Data and delete files of the snapshot.
+    pub data_files: Vec<DataFile>,
+    /// Parquet data files of the snapshot, with their partition values.
+    ///
+    /// Delete files aren't applied, so deleted rows are still read, as
+    /// [`Self::deletes_warning`] warns.
+    pub partitioning: Partitioning,
+}
+
+#[derive(Clone, Debug)]
+pub struct Schema {
+    pub schema_id: i32,
+    /// Fields by ID, nested ones named by their path, e.g. `address.city`.
+    pub fields: Vec<SchemaField>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SchemaField {
+    pub id: i32,
+    pub name: String,
+    pub data_type: String,
+    pub required: bool,
+    pub doc: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PartitionSpec {
+    #[serde(default)]
+    pub spec_id: i32,
+    #[serde(default)]
+    pub fields: Vec<PartitionField>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PartitionField {
+    pub name: String,
+    pub transform: String,
+    pub source_id: i32,
+    pub field_id: Option<i32>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Snapshot {
+    pub snapshot_id: i64,
+    pub parent_snapshot_id: Option<i64>,
+    pub sequence_number: Option<i64>,
+    pub timestamp_ms: i64,
+    pub manifest_list: Option<String>,
+    /// Manifests listed inline, in place of a manifest list, by some
+    /// format version 1 writers.
+    #[serde(default)]
+    pub manifests: Vec<String>,
+    /// The operation, e.g. `append`, and counts of added and removed files.
+    #[serde(default)]
+    pub summary: BTreeMap<String, String>,
+    pub schema_id: Option<i32>,
+}
+
+impl Snapshot {
+    pub fn operation(&self) -> Option<&str> {
+        self.summary.get("operation").map(String::as_str)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FileContent {
+    Data,
+    PositionDeletes,
+    EqualityDeletes,
+}
+
+impl FileContent {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Data => "data",
+            Self::PositionDeletes => "position deletes",
+            Self::EqualityDeletes => "equality deletes",
+        }
+    }
+}
+
+/// A file tracked by a manifest.
+#[derive(Clone, Debug)]
+pub struct DataFile {
+    pub content: FileContent,
+    pub path: PathBuf,
+    /// E.g. `PARQUET`.
+    pub file_format: String,
+    pub spec_id: i32,
+    pub partition: Vec<(String, Option<String>)>,
+    pub record_count: i64,
+    pub file_size: u64,
+    pub columns: Vec<ColumnStats>,
+}
+
+/// Statistics of a column of a data file, as recorded in its manifest.
+#[derive(Clone, Debug, Default)]
+pub struct ColumnStats {
+    pub field_id: i32,
+    /// Bytes on disk.
+    pub size: Option<i64>,
+    pub values: Option<i64>,
+    pub nulls: Option<i64>,
+    pub nans: Option<i64>,
+    pub lower: Option<String>,
+    pub upper: Option<String>,
+}
+
+/// The parts of `metadata.json` that are read, for both format versions.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct TableMetadata {
+    format_version: i32,
+    table_uuid: Option<String>,
+    location: String,
+    last_updated_ms: Option<i64>,
+    #[serde(default)]
+    properties: BTreeMap<String, String>,
+    current_schema_id: Option<i32>,
+    #[serde(default)]
+    schemas: Vec<RawSchema>,
+    /// The only schema, in format version 1.
+    schema: Option<RawSchema>,
+    default_spec_id: Option<i32>,
+    #[serde(default)]
+    partition_specs: Vec<PartitionSpec>,
+    /// Fields of the only spec, in format version 1.
+    partition_spec: Option<Vec<PartitionField>>,
+    current_snapshot_id: Option<i64>,
+    #[serde(default)]
+    snapshots: Vec<Snapshot>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RawSchema {
+    #[serde(default)]
+    schema_id: i32,
+    fields: Vec<Value>,
+}
+
+impl IcebergTable {
+    /// Whether `path` is the root directory of an Iceberg table, or one of
+    /// its metadata files.
+    pub fn is_table(path: &Path) -> bool {
+        if path.is_file() {
+            return path
+                .file_name()
+                .is_some_and(|n| n.to_string_lossy().ends_with(METADATA_SUFFIX));
+        }
+        latest_metadata(path).is_some()
+    }
+
+    /// Reads the table at `path`, from its latest metadata file unless given
+    /// one, at the snapshot picked by `at`, whose versions are snapshot IDs.
+    pub fn open(path: &Path, at: TimeTravel) -> Result<Self> {
+        let (root, metadata_path) = if path.is_file() {
+            let root = path
+                .parent()
+                .and_then(Path::parent)
+                .unwrap_or(Path::new("."));
+            (root.to_path_buf(), path.to_path_buf())
+        } else {
+            let metadata_path = latest_metadata(path)
+                .ok_or_else(|| Error::NoIcebergMetadata(path.join(METADATA_DIR)))?;
+            (path.to_path_buf(), metadata_path)
+        };
+        let metadata: TableMetadata = serde_json::from_reader(File::open(&metadata_path)?)?;
+
+        let raw_schemas = match metadata.schema {
+            Some(schema) if metadata.schemas.is_empty() => vec![schema],
+            _ => metadata.schemas,
+        };
+        let schemas: Vec<_> = raw_schemas
+            .iter()
+            .map(|s| {
+                let mut fields = vec![];
+                flatten_fields(&s.fields, "", &mut fields);
+                Schema {
+                    schema_id: s.schema_id,
+                    fields,
+                }
+            })
+            .collect();
+        let current_schema_id = metadata
+            .current_schema_id
+            .or_else(|| schemas.last().map(|s| s.schema_id))
+            .unwrap_or_default();
+        let partition_specs = match metadata.partition_spec {
+            Some(fields) if metadata.partition_specs.is_empty() => {
+                vec![PartitionSpec { spec_id: 0, fields }]
+            }
+            _ => metadata.partition_specs,
+        };
+
+        let mut snapshots = metadata.snapshots;
+        snapshots.sort_by_key(|s| std::cmp::Reverse((s.timestamp_ms, s.sequence_number)));
+        let snapshot_id = match at {
+            // Format version 1 uses -1 for "no snapshot".
+            TimeTravel::Latest => metadata.current_snapshot_id.filter(|&id| id >= 0),
+            TimeTravel::Version(id) => Some(
+                snapshots
+                    .iter()
+                    .find(|s| s.snapshot_id == id)
+                    .map(|s| s.snapshot_id)
+                    .ok_or_else(|| Error::NoIcebergSnapshot(format!("`{id}`")))?,
+            ),
+            TimeTravel::Timestamp(timestamp) => Some(
+                snapshots
+                    .iter()
+                    .find(|s| s.timestamp_ms <= timestamp)
+                    .map(|s| s.snapshot_id)
+                    .ok_or_else(|| {
+                        Error::NoIcebergSnapshot(format!(
+                            "at or before `{}`",
+                            crate::delta::format_timestamp(timestamp)
+                        ))
+                    })?,
+            ),
+        };
+
+        let mut table = Self {
+            root: root.clone(),
+            metadata_path,
+            format_version: metadata.format_version,
+            table_uuid: metadata.table_uuid,
+            location: metadata.location,
+            last_updated_ms: metadata.last_updated_ms,
+            properties: metadata.properties,
+            current_schema_id,
+            schemas,
+            default_spec_id: metadata.default_spec_id.unwrap_or_default(),
+            partition_specs,
+            snapshots,
+            snapshot_id,
+            data_files: vec![],
+            partitioning: Partitioning {
+                root,
+                keys: vec![],
+                files: vec![],
+            },
+        };
+        if let Some(snapshot) = table.snapshot().cloned() {
+            table.data_files = table.read_data_files(&snapshot)?;
+        }
+        table.partitioning = table.parquet_files();
+        Ok(table)
+    }
+
+    /// The snapshot being read.
+    pub fn snapshot(&self) -> Option<&Snapshot> {
+        let id = self.snapshot_id?;
+        self.snapshots.iter().find(|s| s.snapshot_id == id)
+    }
+
+    /// Warns that the rows read include deleted ones, when the snapshot has
+    /// delete files.
+    pub fn deletes_warning(&self) -> Option<String> {
+        let n = self
+            .data_files
+            .iter()
+            .filter(|f| f.content != FileContent::Data)
+            .count();
+        let files = match n {
+            0 => return None,
+            1 => "1 delete file isn't".to_string(),
+            n => format!("{n} delete files aren't"),
+        };
+        Some(format!("{files} applied, so deleted rows are still shown"))
+    }
+
+    pub fn current_schema(&self) -> Option<&Schema> {
+        self.schemas
+            .iter()
+            .find(|s| s.schema_id == self.current_schema_id)
+    }
+
+    /// Name of field `id` in the current schema, falling back to older ones
+    /// for dropped fields.
+    pub fn field(&self, id: i32) -> Option<&SchemaField> {
+        self.current_schema()
+            .into_iter()
+            .chain(self.schemas.iter().rev())
+            .find_map(|s| s.fields.iter().find(|f| f.id == id))
+    }
+
+    /// Every field ID of every schema, ascending.
+    pub fn field_ids(&self) -> Vec<i32> {
+        let mut ids: Vec<_> = self
+            .schemas
+            .iter()
+            .flat_map(|s| s.fields.iter().map(|f| f.id))
+            .collect();
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+
+    /// Maps a path recorded in the metadata to the local file system.
+    ///
+    /// Paths under the table location are resolved against the table root,
+    /// so tables that have been copied or moved can still be read.
+    fn local_path(&self, uri: &str) -> PathBuf {
+        let location = self.location.trim_end_matches('/');
+        if let Some(relative) = uri.strip_prefix(location) {
+            return self.root.join(relative.trim_start_matches('/'));
+        }
+        let path = uri
+            .strip_prefix("file://")
+            .or_else(|| uri.strip_prefix("file:"))
+            .unwrap_or(uri);
+        PathBuf::from(path)
+    }
+
+    fn read_data_files(&self, snapshot: &Snapshot) -> Result<Vec<DataFile>> {
+        let manifests = match snapshot.manifest_list.as_deref() {
+            Some(manifest_list) => {
+                let mut manifests = vec![];
+                for entry in read_avro(&self.local_path(manifest_list))? {
+                    if let Some(path) = field(&entry, "manifest_path").and_then(as_string) {
+                        manifests.push(path);
+                    }
+                }
+                manifests
+            }
+            None => snapshot.manifests.clone(),
+        };
+
+        let mut files = vec![];
+        for manifest in manifests {
+            let path = self.local_path(&manifest);
+            let reader = Reader::new(File::open(&path)?)?;
+            // Manifests record the ID of the spec their partitions follow.
+            let spec_id = reader
+                .user_metadata()
+                .get("partition-spec-id")
+                .and_then(|id| String::from_utf8_lossy(id).parse().ok())
+                .unwrap_or(self.default_spec_id);
+            for entry in reader {
+                let entry = entry?;
+                // Entries of files removed by the snapshot are kept, as deleted.
+                if field(&entry, "status").and_then(as_long) == Some(2) {
+                    continue;
+                }
+                if let Some(data_file) = field(&entry, "data_file") {
+                    files.push(self.data_file(data_file, spec_id));
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    fn data_file(&self, value: &AvroValue, spec_id: i32) -> DataFile {
+        let long = |name| field(value, name).and_then(as_long);
+        let content = match long("content") {
+            Some(1) => FileContent::PositionDeletes,
+            Some(2) => FileContent::EqualityDeletes,
+            _ => FileContent::Data,
+        };
+        let partition = match field(value, "partition") {
+            Some(AvroValue::Record(fields)) => fields
+                .iter()
+                .map(|(name, v)| (name.clone(), format_value(v)))
+                .collect(),
+            _ => vec![],
+        };
+
+        let mut columns: BTreeMap<i32, ColumnStats> = BTreeMap::new();
+        let mut stat = |name, set: &dyn Fn(&mut ColumnStats, &AvroValue)| {
+            for (id, v) in field(value, name).map(map_entries).unwrap_or_default() {
+                let stats = columns.entry(id).or_insert_with(|| ColumnStats {
+                    field_id: id,
+                    ..Default::default()
+                });
+                set(stats, v);
+            }
+        };
+        stat("column_sizes", &|s, v| s.size = as_long(v));
+        stat("value_counts", &|s, v| s.values = as_long(v));
+        stat("null_value_counts", &|s, v| s.nulls = as_long(v));
+        stat("nan_value_counts", &|s, v| s.nans = as_long(v));
+        // Bounds are stored as bytes and decoded according to the field type.
+        stat("lower_bounds", &|s, v| {
+            s.lower = as_bytes(v).map(|b| self.decode(s.field_id, b))
+        });
+        stat("upper_bounds", &|s, v| {
+            s.upper = as_bytes(v).map(|b| self.decode(s.field_id, b))
+        });
+
+        DataFile {
+            content,
+            path: self.local_path(
+                &field(value, "file_path")
+                    .and_then(as_string)
+                    .unwrap_or_default(),
+            ),
+            file_format: field(value, "file_format")
+                .and_then(as_string)
+                .unwrap_or_default(),
+            spec_id,
+            partition,
+            record_count: long("record_count").unwrap_or_default(),
+            file_size: long("file_size_in_bytes").unwrap_or_default() as u64,
+            columns: columns.into_values().collect(),
+        }
+    }
+
+    fn decode(&self, field_id: i32, bytes: &[u8]) -> String {
+        match self.field(field_id) {
+            Some(field) => decode_bound(&field.data_type, bytes),
+            None => hex(bytes),
+        }
+    }
+
+    /// The Parquet data files, keyed by partition field name.
+    fn parquet_files(&self) -> Partitioning {
+        let mut keys: Vec<String> = vec![];
+        let data_files: Vec<_> = self
+            .data_files
+            .iter()
+            .filter(|f| f.content == FileContent::Data && f.file_format == "PARQUET")
+            .collect();
+        for file in data_files.iter() {
+            for (name, _) in file.partition.iter() {
+                if !keys.contains(name) {
+                    keys.push(name.clone());
+                }
+            }
+        }
+        let files = data_files
+            .into_iter()
+            .map(|file| PartitionFile {
+                path: file.path.clone(),
+                values: keys
+                    .iter()
+                    .map(|k| {
+                        file.partition
+                            .iter()
+                            .find(|(name, _)| name == k)
+                            .and_then(|(_, v)| v.clone())
+                    })
+                    .collect(),
+                num_rows: file.record_count,
+                size: file.file_size,
+            })
+            .collect();
+        Partitioning {
+            root: self.root.clone(),
+            keys,
+            files,
+        }
+    }
+}
+
+/// The metadata file with the highest version under `root/metadata`.
+fn latest_metadata(root: &Path) -> Option<PathBuf> {
+    let dir = root.join(METADATA_DIR);
+    if let Ok(hint) = fs::read_to_string(dir.join(VERSION_HINT)) {
+        let path = dir.join(format!("v{}{METADATA_SUFFIX}", hint.trim()));
+        if path.is_file() {
+            return Some(path);
+        }
+    }
+    fs::read_dir(&dir)
+        .ok()?
+        .filter_map(|entry| {
+            let name = entry.ok()?.file_name().to_string_lossy().to_string();
+            let stem = name.strip_suffix(METADATA_SUFFIX)?;
+            // Either `v3` or `00003-<uuid>`.
+            let version = stem
+                .trim_start_matches('v')
+                .split('-')
+                .next()?
+                .parse::<i64>()
+                .ok()?;
+            Some((version, name))
+        })
+        .max()
+        .map(|(_, name)| dir.join(name))
+}
+
+/// Collects the fields of a struct, and those nested in them, by ID.
+fn flatten_fields(fields: &[Value], prefix: &str, out: &mut Vec<SchemaField>) {
+    for f in fields {
+        let (Some(id), Some(name)) = (f["id"].as_i64(), f["name"].as_str()) else {
+            continue;
+        };
+        let doc = f["doc"].as_str().map(str::to_string);
+        let required = f["required"].as_bool().unwrap_or(false);
+        push_field(id, &join(prefix, name), &f["type"], required, doc, out);
+    }
+}
+
+fn push_field(
+    id: i64,
+    name: &str,
+    data_type: &Value,
+    required: bool,
+    doc: Option<String>,
+    out: &mut Vec<SchemaField>,
+) {
+    out.push(SchemaField {
+        id: id as i32,
+        name: name.to_string(),
+        data_type: type_name(data_type),
+        required,
+        doc,
+    });
+    match data_type["type"].as_str() {
+        Some("struct") => {
+            if let Some(fields) = data_type["fields"].as_array() {
+                flatten_fields(fields, name, out);
+            }
+        }
+        Some("list") => push_nested(data_type, name, "element", out),
+        Some("map") => {
+            push_nested(data_type, name, "key", out);
+            push_nested(data_type, name, "value", out);
+        }
+        _ => {}
+    }
+}
+
+/// Pushes the element of a list, or the key or value of a map, whose IDs are
+/// kept next to their types as e.g. `element-id`.
+fn push_nested(data_type: &Value, name: &str, part: &str, out: &mut Vec<SchemaField>) {
+    let Some(id) = data_type[format!("{part}-id")].as_i64() else {
+        return;
+    };
+    // Map keys are always required.
+    let required = data_type[format!("{part}-required")]
+        .as_bool()
+        .unwrap_or(part == "key");
+    push_field(id, &join(name, part), &data_type[part], required, None, out);
+}
+
+fn join(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{prefix}.{name}")
+    }
+}
+
+fn type_name(data_type: &Value) -> String {
+    match data_type {
+        Value::String(name) => name.clone(),
+        Value::Object(_) => match data_type["type"].as_str() {
+            Some("list") => format!("list<{}>", type_name(&data_type["element"])),
+            Some("map") => format!(
+                "map<{}, {}>",
+                type_name(&data_type["key"]),
+                type_name(&data_type["value"])
+            ),
+            Some(name) => name.to_string(),
+            None => data_type.to_string(),
+        },
+        _ => data_type.to_string(),
+    }
+}
+
+fn read_avro(path: &Path) -> Result<Vec<AvroValue>> {
+    Ok(Reader::new(File::open(path)?)?.collect::<std::result::Result<_, _>>()?)
+}
+
+/// Field `name` of a record, looking through nullable unions.
+fn field<'a>(value: &'a AvroValue, name: &str) -> Option<&'a AvroValue> {
+    match value {
+        AvroValue::Record(fields) => fields
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| unwrap_union(v)),
+        _ => None,
+    }
+}
+
+fn unwrap_union(value: &AvroValue) -> &AvroValue {
+    match value {
+        AvroValue::Union(_, v) => unwrap_union(v),
+        v => v,
+    }
+}
+
+fn as_long(value: &AvroValue) -> Option<i64> {
+    match unwrap_union(value) {
+        AvroValue::Int(i) => Some(*i as i64),
+        AvroValue::Long(i) => Some(*i),
+        _ => None,
+    }
+}
+
+fn as_string(value: &AvroValue) -> Option<String> {
+    match unwrap_union(value) {
+        AvroValue::String(s) | AvroValue::Enum(_, s) => Some(s.clone()),
+        _ => None,
+    }
+}
+
+fn as_bytes(value: &AvroValue) -> Option<&[u8]> {
+    match unwrap_union(value) {
+        AvroValue::Bytes(b) | AvroValue::Fixed(_, b) => Some(b),
+        _ => None,
+    }
+}
+
+/// Entries of a map keyed by field ID, which Iceberg writes as an array of
+/// key/value records.
+fn map_entries(value: &AvroValue) -> Vec<(i32, &AvroValue)> {
+    match value {
+        AvroValue::Array(entries) => entries
+            .iter()
+            .filter_map(|e| {
+                Some((
+                    field(e, "key").and_then(as_long)? as i32,
+                    field(e, "value")?,
+                ))
+            })
+            .collect(),
+        AvroValue::Map(entries) => entries
+            .iter()
+            .filter_map(|(k, v)| Some((k.parse().ok()?, unwrap_union(v))))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Formats a partition value, `None` for null.
+fn format_value(value: &AvroValue) -> Option<String> {
+    let value = match unwrap_union(value) {
+        AvroValue::Null => return None,
+        AvroValue::Boolean(b) => b.to_string(),
+        AvroValue::Int(i) => i.to_string(),
+        AvroValue::Long(i) => i.to_string(),
+        AvroValue::Float(x) => x.to_string(),
+        AvroValue::Double(x) => x.to_string(),
+        AvroValue::String(s) | AvroValue::Enum(_, s) => s.clone(),
+        AvroValue::Bytes(b) | AvroValue::Fixed(_, b) => hex(b),
+        AvroValue::Date(days) => decode_bound("date", &days.to_le_bytes()),
+        AvroValue::TimestampMicros(t) | AvroValue::LocalTimestampMicros(t) => {
+            decode_bound("timestamp", &t.to_le_bytes())
+        }
+        AvroValue::Uuid(uuid) => uuid.to_string(),
+        v => format!("{v:?}"),
+    };
+    Some(value)
+}
+
+/// Decodes a lower or upper bound from Iceberg's single-value serialization.
+fn decode_bound(data_type: &str, bytes: &[u8]) -> String {
+    let long = || {
+        let mut buf = [0; 8];
+        let n = bytes.len().min(8);
+        buf[..n].copy_from_slice(&bytes[..n]);
+        i64::from_le_bytes(buf)
+    };
+    let decoded =
+        match data_type {
+            "boolean" => bytes.first().map(|&b| (b != 0).to_string()),
+            "int" => Some((long() as i32).to_string()),
+            "long" => Some(long().to_string()),
+            "float" => bytes
+                .try_into()
+                .ok()
+                .map(|b| f32::from_le_bytes(b).to_string()),
+            "double" => bytes
+                .try_into()
+                .ok()
+                .map(|b| f64::from_le_bytes(b).to_string()),
+            "string" => Some(String::from_utf8_lossy(bytes).into_owned()),
+            "date" => date32_to_datetime(long() as i32).map(|d| d.format("%Y-%m-%d").to_string()),
+            "time" => Some(format!("{}µs", long())),
+            "timestamp" | "timestamptz" => timestamp_us_to_datetime(long())
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S%.f").to_string()),
+            "timestamp_ns" | "timestamptz_ns" => timestamp_ns_to_datetime(long())
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S%.f").to_string()),
+            "uuid" if bytes.len() == 16 => {
+                let h = hex(bytes);
+                Some(format!(
+                    "{}-{}-{}-{}-{}",
+                    &h[..8],
+                    &h[8..12],
+                    &h[12..16],
+                    &h[16..20],
+                    &h[20..]
+                ))
+            }
+            t if t.starts_with("decimal(") && bytes.len() <= 16 => {
+                let scale = t
+                    .trim_end_matches(')')
+                    .split(',')
+                    .nth(1)
+                    .and_then(|s| s.trim().parse::<usize>().ok())
+                    .unwrap_or(0);
+                // Big-endian two's complement, sign-extended to 128 bits.
+                let fill = if bytes.first().is_some_and(|&b| b & 0x80 != 0) {
+                    0xff
+                } else {
+                    0
+                };
+                let mut buf = [fill; 16];
+                buf[16 - bytes.len()..].copy_from_slice(bytes);
+                Some(format_decimal(i128::from_be_bytes(buf), scale))
+            }
+            _ => None,
+        };
+    decoded.unwrap_or_else(|| hex(bytes))
+}
+
+fn format_decimal(unscaled: i128, scale: usize) -> String {
+    let digits = unscaled.unsigned_abs().to_string();
+    let sign = if unscaled < 0 { "-" } else { "" };
+    if scale == 0 {
+        return format!("{sign}{digits}");
+    }
+    let digits = format!("{digits:0>width$}", width = scale + 1);
+    let (int, frac) = digits.split_at(digits.len() - scale);
+    format!("{sign}{int}.{frac}")
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use apache_avro::{Schema as AvroSchema, Writer};
+    use serde_json::json;
+
+    use super::*;
+
+    const MANIFEST_SCHEMA: &str = r#"{
+        "type": "record",
+        "name": "manifest_entry",
+        "fields": [
+            {"name": "status", "type": "int"},
+            {"name": "data_file", "type": {
+                "type": "record",
+                "name": "r2",
+                "fields": [
+                    {"name": "content", "type": "int"},
+                    {"name": "file_path", "type": "string"},
+                    {"name": "file_format", "type": "string"},
+                    {"name": "record_count", "type": "long"},
+                    {"name": "file_size_in_bytes", "type": "long"}
+                ]
+            }}
+        ]
+    }"#;
+
+    const MANIFEST_LIST_SCHEMA: &str = r#"{
+        "type": "record",
+        "name": "manifest_file",
+        "fields": [{"name": "manifest_path", "type": "string"}]
+    }"#;
+
+    fn write_avro(path: &Path, schema: &str, records: Vec<AvroValue>) {
+        let schema = AvroSchema::parse_str(schema).unwrap();
+        let mut writer = Writer::new(&schema, File::create(path).unwrap());
+        for record in records {
+            writer.append(record).unwrap();
+        }
+        writer.flush().unwrap();
+    }
+
+    fn entry(content: i32, path: &str, record_count: i64) -> AvroValue {
+        AvroValue::Record(vec![
+            ("status".to_string(), AvroValue::Int(1)),
+            (
+                "data_file".to_string(),
+                AvroValue::Record(vec![
+                    ("content".to_string(), AvroValue::Int(content)),
+                    ("file_path".to_string(), AvroValue::String(path.to_string())),
+                    (
+                        "file_format".to_string(),
+                        AvroValue::String("PARQUET".to_string()),
+                    ),
+                    ("record_count".to_string(), AvroValue::Long(record_count)),
+                    ("file_size_in_bytes".to_string(), AvroValue::Long(100)),
+                ]),
+            ),
+        ])
+    }
+
+    /// A table of a single snapshot, whose manifest lists `entries`.
+    fn write_table(root: &Path, entries: Vec<AvroValue>) {
+        let dir = root.join(METADATA_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        write_avro(&dir.join("m1.avro"), MANIFEST_SCHEMA, entries);
+        write_avro(
+            &dir.join("snap-1.avro"),
+            MANIFEST_LIST_SCHEMA,
+            vec![AvroValue::Record(vec![(
+                "manifest_path".to_string(),
+                AvroValue::String("/warehouse/t/metadata/m1.avro".to_string()),
+            )])],
+        );
+        let metadata = json!({
+            "format-version": 2,
+            "location": "/warehouse/t",
+            "current-schema-id": 0,
+            "schemas": [{"schema-id": 0, "fields": [
+                {"id": 1, "name": "id", "type": "long", "required": true},
+            ]}],
+            "current-snapshot-id": 1,
+            "snapshots": [{
+                "snapshot-id": 1,
+                "timestamp-ms": 1_000,
+                "manifest-list": "/warehouse/t/metadata/snap-1.avro",
+                "summary": {"operation": "overwrite"},
+            }],
+        });
+        fs::write(dir.join("v1.metadata.json"), metadata.to_string()).unwrap();
+    }
+
+    #[test]
+    fn warns_of_delete_files() {
+        let dir = tempfile::tempdir().unwrap();
+        write_table(
+            dir.path(),
+            vec![
+                entry(0, "/warehouse/t/data/a.parquet", 10),
+                entry(1, "/warehouse/t/data/a-deletes.parquet", 2),
+            ],
+        );
+        let table = IcebergTable::open(dir.path(), TimeTravel::Latest).unwrap();
+        assert_eq!(table.data_files.len(), 2);
+        assert_eq!(table.data_files[1].content, FileContent::PositionDeletes);
+        // Only the data file is read, under the table root.
+        let files = &table.partitioning.files;
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, dir.path().join("data/a.parquet"));
+        assert_eq!(
+            table.deletes_warning().as_deref(),
+            Some("1 delete file isn't applied, so deleted rows are still shown")
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        write_table(
+            dir.path(),
+            vec![entry(0, "/warehouse/t/data/a.parquet", 10)],
+        );
+        let table = IcebergTable::open(dir.path(), TimeTravel::Latest).unwrap();
+        assert_eq!(table.deletes_warning(), None);
+    }
+
+    #[test]
+    fn decodes_bounds() {
+        assert_eq!(decode_bound("boolean", &[1]), "true");
+        assert_eq!(decode_bound("int", &(-42i32).to_le_bytes()), "-42");
+        assert_eq!(
+            decode_bound("long", &1_234_567_890_123i64.to_le_bytes()),
+            "1234567890123"
+        );
+        // Ints widened to longs since keep their 4-byte bounds.
+        assert_eq!(decode_bound("long", &7i32.to_le_bytes()), "7");
+        assert_eq!(decode_bound("float", &1.5f32.to_le_bytes()), "1.5");
+        assert_eq!(decode_bound("double", &(-0.25f64).to_le_bytes()), "-0.25");
+        assert_eq!(decode_bound("string", "héllo".as_bytes()), "héllo");
+        assert_eq!(decode_bound("date", &19_723i32.to_le_bytes()), "2024-01-01");
+        assert_eq!(
+            decode_bound("timestamp", &1_704_067_200_000_001i64.to_le_bytes()),
+            "2024-01-01 00:00:00.000001"
+        );
+        assert_eq!(
+            decode_bound(
+                "timestamptz_ns",
+                &1_704_067_200_000_000_001i64.to_le_bytes()
+            ),
+            "2024-01-01 00:00:00.000000001"
+        );
+        assert_eq!(
+            decode_bound("uuid", &(0..16).collect::<Vec<u8>>()),
+            "00010203-0405-0607-0809-0a0b0c0d0e0f"
+        );
+    }
+
+    #[test]
+    fn decodes_decimal_bounds() {
+        // Big-endian two's complement, in as few bytes as fit.
+        assert_eq!(
+            decode_bound("decimal(9, 2)", &12_345i32.to_be_bytes()[2..]),
+            "123.45"
+        );
+        assert_eq!(
+            decode_bound("decimal(9, 2)", &(-5i32).to_be_bytes()[3..]),
+            "-0.05"
+        );
+        assert_eq!(decode_bound("decimal(38, 0)", &[0x01, 0x00]), "256");
+        assert_eq!(decode_bound("decimal(38, 3)", &[0xff]), "-0.001");
+    }
+
+    #[test]
+    fn falls_back_to_hex() {
+        assert_eq!(decode_bound("binary", &[0xca, 0xfe]), "cafe");
+        assert_eq!(decode_bound("fixed[2]", &[0x00, 0x01]), "0001");
+        // Too short for the type.
+        assert_eq!(decode_bound("double", &[0x01, 0x02]), "0102");
+        assert_eq!(decode_bound("uuid", &[0xab]), "ab");
+    }
+}
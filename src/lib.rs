@@ -1,35 +1,332 @@
 pub mod app;
 pub mod args;
+pub mod config;
+pub mod delta;
+pub mod diff;
+pub mod encryption;
 pub mod error;
+pub mod export;
+pub mod iceberg;
+pub mod lint;
+pub mod mmap;
+pub mod parallel;
+pub mod partition;
 pub mod prelude;
+pub mod profile;
+pub mod query;
+pub mod remote;
+pub mod salvage;
+pub mod source;
 pub mod tui;
+pub mod validate;
 
-use std::{fs::File, io};
+use std::{
+    fs::File,
+    io::{self, IsTerminal, Read},
+    path::Path,
+    sync::Arc,
+};
+
+use arrow::util::pretty::pretty_format_batches;
+use parquet::file::metadata::{ParquetMetaData, ParquetMetaDataReader};
 
 use app::Viewer;
-use args::Args;
+use args::{Args, Commands, DiffArgs, LintArgs, QueryArgs, ValidateArgs};
+use config::Config;
+use delta::{DeltaTable, TimeTravel};
+use diff::{
+    data::{DataDiff, DataDiffOptions},
+    schema::SchemaDiff,
+};
+use encryption::{EncryptedFile, Keys};
+use iceberg::IcebergTable;
+use lint::{Lint, LintConfig};
+use partition::PartitionFilter;
+use partition::Partitioning;
 use prelude::*;
+use query::QueryEngine;
 use ratatui::{backend::CrosstermBackend, Terminal};
+use remote::RemoteFile;
+use salvage::Salvage;
+use source::{check_footer, Format, Source};
 use tui::{
-    command::Command,
+    command::Keymap,
     event::{Event, EventHandler},
+    palette::History,
+    session::Sessions,
     state::State,
-    Tui,
+    theme, Tui,
 };
+use validate::Validation;
 
 pub fn run(args: Args) -> Result<()> {
-    let name = args
-        .filename
-        .file_stem()
-        .map(|s| s.to_string_lossy().to_string());
-    let file = File::open(args.filename)?;
+    if let Some(command) = args.command {
+        return match command {
+            Commands::Diff(diff_args) => run_diff(diff_args),
+            Commands::Query(query_args) => run_query(query_args),
+            Commands::Validate(validate_args) => run_validate(validate_args),
+            Commands::Lint(lint_args) => run_lint(lint_args),
+        };
+    }
+    let filename = match args.filename {
+        Some(filename) => filename,
+        None if !io::stdin().is_terminal() => "-".into(),
+        None => return Err(Error::NoInput),
+    };
+
+    let viewer = if filename.as_os_str() == "-" {
+        // Parquet is read footer first, so the whole stream has to be buffered.
+        let mut buf = vec![];
+        io::stdin().lock().read_to_end(&mut buf)?;
+        Viewer::new(Source::bytes(buf.into()), Some("stdin".to_string()))?
+    } else if let Some(url) = filename.to_str().filter(|f| RemoteFile::is_url(f)) {
+        let remote = RemoteFile::open(url)?;
+        let name = remote.name();
+        Viewer::new(Source::Remote(remote), name)?
+    } else {
+        let at = match (args.at_version, args.at_timestamp) {
+            (Some(version), _) => TimeTravel::Version(version),
+            (_, Some(timestamp)) => TimeTravel::Timestamp(timestamp),
+            _ => TimeTravel::Latest,
+        };
+        let keys = Keys::new(args.key_file.as_deref(), args.footer_key, &args.column_keys)?;
+        open_local(&filename, &args.filters, at, keys, args.salvage)?
+    };
+    let state = State::new(viewer)
+        .with_profile_limit(args.sample)
+        .with_buffers(&args.more)?;
+    start_tui(state)
+}
+
+/// Registers the file `viewer` shows with a new query engine, unless it's
+/// in a format or place the engine can't read.
+pub(crate) fn query_engine(viewer: &Viewer) -> Result<Option<QueryEngine>> {
+    let name = viewer.file_stem.clone();
+    match &viewer.source {
+        Source::File(_, Format::ArrowStream) => Ok(None),
+        Source::File(path, _) => {
+            let mut engine = QueryEngine::new()?;
+            engine.register(&name, path)?;
+            Ok(Some(engine))
+        }
+        // Query the files left after --filter rather than discovering them
+        // again, the version being viewed rather than the latest, encrypted
+        // files with the keys given, damaged files as salvaged, and files
+        // read from stdin or an object store.
+        source => {
+            let mut engine = QueryEngine::new()?;
+            engine.register_source(&name, source.clone())?;
+            Ok(Some(engine))
+        }
+    }
+}
+
+/// Opens a local file or table, or an object store URL, as a further file of
+/// the terminal UI.
+pub(crate) fn open_path(path: &Path) -> Result<Viewer> {
+    if let Some(url) = path.to_str().filter(|p| RemoteFile::is_url(p)) {
+        let remote = RemoteFile::open(url)?;
+        let name = remote.name();
+        return Viewer::new(Source::Remote(remote), name);
+    }
+    open_local(path, &[], TimeTravel::Latest, Keys::default(), false)
+}
+
+/// Opens a local file, Delta or Iceberg table, or hive-partitioned directory.
+///
+/// `keys` are only used if the file is encrypted, and `salvage` only for
+/// Parquet files.
+pub(crate) fn open_local(
+    path: &Path,
+    filters: &[PartitionFilter],
+    at: TimeTravel,
+    keys: Keys,
+    salvage: bool,
+) -> Result<Viewer> {
+    if DeltaTable::is_table(path) {
+        let mut table = DeltaTable::open(path, at)?;
+        table.partitioning.prune(filters)?;
+        Viewer::new(Source::Delta(Arc::new(table)), file_stem(path))
+    } else if IcebergTable::is_table(path) {
+        let mut table = IcebergTable::open(path, at)?;
+        table.partitioning.prune(filters)?;
+        let name = file_stem(&table.root);
+        Viewer::new(Source::Iceberg(Arc::new(table)), name)
+    } else if path.is_dir() {
+        let mut partitioning = Partitioning::discover(path)?;
+        partitioning.prune(filters)?;
+        Viewer::new(Source::Partitioned(partitioning), file_stem(path))
+    } else {
+        let source = match Source::file(path)? {
+            Source::File(path, Format::Parquet) if salvage => {
+                Source::Salvaged(Arc::new(Salvage::open(&path)?))
+            }
+            Source::File(path, Format::Parquet) if EncryptedFile::is_encrypted(&path) => {
+                let keys = Arc::new(keys);
+                Source::Encrypted(EncryptedFile { path, keys })
+            }
+            source => source,
+        };
+        Viewer::new(source, file_stem(path))
+    }
+}
+
+fn run_query(args: QueryArgs) -> Result<()> {
+    let mut engine = QueryEngine::new()?;
+    for path in args.files.iter() {
+        engine.register(&file_stem(path).unwrap_or_default(), path)?;
+    }
+    let results = engine.sql(&args.sql)?;
 
-    let viewer = Viewer::new(file, name)?;
-    start_tui(viewer)
+    if args.print {
+        let batches = results.collect::<std::result::Result<Vec<_>, _>>()?;
+        println!("{}", pretty_format_batches(&batches)?);
+        return Ok(());
+    }
+    let mut viewer = open_local(
+        &args.files[0],
+        &[],
+        TimeTravel::Latest,
+        Keys::default(),
+        false,
+    )?;
+    viewer.set_data(Box::new(results))?;
+    start_tui(
+        State::new(viewer)
+            .with_query_engine(engine)
+            .with_sql(&args.sql),
+    )
 }
 
-pub fn start_tui(viewer: Viewer) -> Result<()> {
-    let mut state = State::new(viewer);
+fn run_validate(args: ValidateArgs) -> Result<()> {
+    let mut failed = 0;
+    for path in args.files.iter() {
+        match Validation::new(path) {
+            Ok(validation) => {
+                print!("{validation}");
+                if !validation.passed() {
+                    failed += 1;
+                }
+            }
+            // Reported like a failed file, so the others are still validated.
+            Err(error) => {
+                println!("{}\n  {error}", path.display());
+                failed += 1;
+            }
+        }
+    }
+    match failed {
+        0 => Ok(()),
+        failed => Err(Error::ValidationFailed(failed, args.files.len())),
+    }
+}
+
+fn run_lint(args: LintArgs) -> Result<()> {
+    let config = match &args.config {
+        Some(path) => LintConfig::load(path)?,
+        None => LintConfig::default(),
+    };
+    let lint = |path: &Path| {
+        let viewer = Viewer::new(Source::file(path)?, file_stem(path))?;
+        if viewer.format != Format::Parquet {
+            return Err(Error::UnsupportedLintFormat(viewer.format.name()));
+        }
+        Lint::new(path.display().to_string(), &viewer, &config)
+    };
+    let mut failed = 0;
+    for path in args.files.iter() {
+        match lint(path) {
+            Ok(lint) => {
+                print!("{lint}");
+                if lint.num_errors() > 0 {
+                    failed += 1;
+                }
+            }
+            // Reported like a failed file, so the others are still linted.
+            Err(error) => {
+                println!("{}\n  {error}", path.display());
+                failed += 1;
+            }
+        }
+    }
+    match failed {
+        0 => Ok(()),
+        failed => Err(Error::LintFailed(failed, args.files.len())),
+    }
+}
+
+fn run_diff(args: DiffArgs) -> Result<()> {
+    let name = |path: &Path| file_stem(path).unwrap_or_default();
+    let left = read_footer(&args.left)?;
+    let right = read_footer(&args.right)?;
+    let diff = SchemaDiff::new(
+        (name(&args.left), &left),
+        (name(&args.right), &right),
+        args.stats,
+    );
+    let data_diff = if args.keys.is_empty() {
+        None
+    } else {
+        let options = DataDiffOptions {
+            keys: args.keys,
+            run_size: args.run_size,
+            limit: args.limit,
+        };
+        Some(DataDiff::new(&args.left, &args.right, &options)?)
+    };
+
+    if args.tui {
+        let left = Viewer::new(
+            Source::File(args.left.clone(), Format::Parquet),
+            file_stem(&args.left),
+        )?;
+        let mut state = State::new(left).with_diff(diff);
+        if let Some(data_diff) = data_diff {
+            state = state.with_data_diff(data_diff);
+        }
+        start_tui(state)
+    } else {
+        print!("{diff}");
+        let mut differ = diff.has_changes();
+        if let Some(data_diff) = data_diff {
+            print!("{data_diff}");
+            differ |= data_diff.has_changes();
+        }
+        if differ {
+            return Err(Error::FilesDiffer);
+        }
+        Ok(())
+    }
+}
+
+/// Reads only the footer of the Parquet file at `path`.
+fn read_footer(path: &Path) -> Result<ParquetMetaData> {
+    let file = File::open(path)?;
+    check_footer(&path.display().to_string(), &file)?;
+    Ok(ParquetMetaDataReader::new().parse_and_finish(&file)?)
+}
+
+pub(crate) fn file_stem(path: &Path) -> Option<String> {
+    path.file_stem().map(|s| s.to_string_lossy().to_string())
+}
+
+pub fn start_tui(mut state: State) -> Result<()> {
+    let config = Config::load()?;
+    let invalid = |e| Error::InvalidConfig(Config::path().unwrap_or_default(), e);
+    theme::init(&config).map_err(invalid)?;
+    // Not worth failing over, but not worth losing either.
+    let (sessions, message) = match Sessions::load() {
+        Ok(sessions) => (sessions, None),
+        Err(e) => (Sessions::unsaved(), Some(e.to_string())),
+    };
+    state = state
+        .with_keymap(Keymap::new(&config.keys).map_err(invalid)?)
+        .with_clipboard(config.clipboard)
+        .with_history(History::load())
+        .with_sessions(sessions);
+    if message.is_some() {
+        state.message = message;
+    }
 
     let backend = CrosstermBackend::new(io::stdout());
     let terminal = Terminal::new(backend)?;
@@ -44,15 +341,96 @@ pub fn start_tui(viewer: Viewer) -> Result<()> {
         match tui.events.next()? {
             Event::Tick => {}
             Event::Key(key_event) => {
-                let command = Command::from(key_event);
-                state.run_command(command, tui.events.sender.clone())?;
+                if state.input.is_some() {
+                    state.edit_input(key_event, tui.events.sender.clone())?;
+                } else if state.pending_mark.is_some() {
+                    state.mark_key(key_event)?;
+                } else {
+                    let command = state.keymap.command(key_event);
+                    state.run_command(command, tui.events.sender.clone())?;
+                }
             }
             Event::Mouse(mouse_event) => {
-                //
+                state.handle_mouse(mouse_event, tui.events.sender.clone())?
             }
             Event::Resize(_, _) => {}
+            Event::Profile(request, profile) => state.set_profile(request, *profile),
+            Event::ProfileProgress(request, rows) => state.set_profile_progress(request, rows),
         }
     }
     tui.exit()?;
+    // Losing the sessions isn't worth failing over.
+    let _ = state.save_sessions();
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{ArrayRef, Int64Array, RecordBatch};
+    use parquet::arrow::ArrowWriter;
+
+    use super::*;
+
+    fn parquet_bytes() -> Vec<u8> {
+        let batch = RecordBatch::try_from_iter([(
+            "id",
+            Arc::new(Int64Array::from_iter_values(0..100)) as ArrayRef,
+        )])
+        .unwrap();
+        let mut buf = vec![];
+        let mut writer = ArrowWriter::try_new(&mut buf, batch.schema(), None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+        buf
+    }
+
+    #[test]
+    fn validates_every_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let good = dir.path().join("good.parquet");
+        let truncated = dir.path().join("truncated.parquet");
+        let bytes = parquet_bytes();
+        std::fs::write(&good, &bytes).unwrap();
+        std::fs::write(&truncated, &bytes[..bytes.len() / 2]).unwrap();
+
+        // The truncated file comes first, and doesn't stop the good one from
+        // being validated.
+        let files = vec![truncated.clone(), good.clone()];
+        let error = run_validate(ValidateArgs { files }).unwrap_err();
+        assert!(matches!(error, Error::ValidationFailed(1, 2)), "{error}");
+        run_validate(ValidateArgs { files: vec![good] }).unwrap();
+    }
+
+    #[test]
+    fn lints_every_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let good = dir.path().join("good.parquet");
+        std::fs::write(&good, parquet_bytes()).unwrap();
+        let missing = dir.path().join("missing.parquet");
+        let csv = dir.path().join("data.csv");
+        std::fs::write(&csv, "id\n1\n").unwrap();
+
+        let files = vec![missing, csv, good];
+        let error = run_lint(LintArgs {
+            files,
+            config: None,
+        })
+        .unwrap_err();
+        assert!(matches!(error, Error::LintFailed(2, 3)), "{error}");
+    }
+
+    #[test]
+    fn queries_files_in_memory() {
+        let buf = parquet_bytes();
+
+        let viewer = Viewer::new(Source::bytes(buf.into()), Some("stdin".to_string())).unwrap();
+        let engine = query_engine(&viewer).unwrap().unwrap();
+        let batches = engine
+            .sql("SELECT sum(id) AS total FROM stdin WHERE id % 2 = 0")
+            .unwrap()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .unwrap();
+        let table = pretty_format_batches(&batches).unwrap().to_string();
+        assert!(table.contains("| 2450  |"), "{table}");
+    }
+}
@@ -0,0 +1,707 @@
+use arrow::{
+    compute::SortOptions,
+    error::ArrowError,
+    row::{OwnedRow, RowConverter, SortField},
+};
+use parquet::{
+    basic::{Compression, Type},
+    file::metadata::RowGroupMetaData,
+};
+use serde::Deserialize;
+
+use crate::{app::Viewer, error::Result, source::format_size};
+use std::{
+    fmt::{self, Display},
+    path::Path,
+};
+
+/// Rows read at a time when checking that declared sorting columns hold.
+const BATCH_SIZE: usize = 8192;
+/// Columns named in a finding before the rest are only counted.
+const MAX_NAMED_COLUMNS: usize = 5;
+
+/// How seriously a rule is taken: only errors fail `parqour lint`.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Error,
+    Warning,
+    Off,
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error => write!(f, "error"),
+            Self::Warning => write!(f, "warning"),
+            Self::Off => write!(f, "off"),
+        }
+    }
+}
+
+/// Rules of `parqour lint`, each a table of a TOML file named after the rule,
+/// e.g.
+///
+/// ```toml
+/// [small-row-groups]
+/// level = "error"
+/// min-bytes = 32000000
+///
+/// [missing-page-index]
+/// level = "off"
+/// ```
+///
+/// Rules left out keep their defaults.
+#[derive(Debug, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct LintConfig {
+    pub small_row_groups: SmallRowGroups,
+    pub large_row_groups: LargeRowGroups,
+    pub missing_statistics: Rule,
+    pub missing_page_index: Rule,
+    pub int96_timestamps: Rule,
+    pub buggy_writers: BuggyWriters,
+    pub unsorted_data: Rule,
+    pub uncompressed: Rule,
+    pub optional_without_nulls: OptionalWithoutNulls,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            small_row_groups: SmallRowGroups::default(),
+            large_row_groups: LargeRowGroups::default(),
+            missing_statistics: Rule::default(),
+            missing_page_index: Rule::default(),
+            int96_timestamps: Rule::default(),
+            buggy_writers: BuggyWriters::default(),
+            unsorted_data: Rule {
+                level: Level::Error,
+            },
+            uncompressed: Rule::default(),
+            optional_without_nulls: OptionalWithoutNulls::default(),
+        }
+    }
+}
+
+impl LintConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(toml::from_str(&std::fs::read_to_string(path)?)?)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rule {
+    pub level: Level,
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self {
+            level: Level::Warning,
+        }
+    }
+}
+
+/// Row groups, other than the last, smaller than `min_bytes` compressed.
+#[derive(Debug, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct SmallRowGroups {
+    pub level: Level,
+    pub min_bytes: u64,
+}
+
+impl Default for SmallRowGroups {
+    fn default() -> Self {
+        Self {
+            level: Level::Warning,
+            min_bytes: 16_000_000,
+        }
+    }
+}
+
+/// Row groups larger than `max_bytes` compressed.
+#[derive(Debug, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct LargeRowGroups {
+    pub level: Level,
+    pub max_bytes: u64,
+}
+
+impl Default for LargeRowGroups {
+    fn default() -> Self {
+        Self {
+            level: Level::Warning,
+            max_bytes: 1_000_000_000,
+        }
+    }
+}
+
+/// Files whose `created_by` names a writer version with a known bug.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BuggyWriters {
+    pub level: Level,
+    /// Replaces the defaults when given.
+    pub writers: Vec<BuggyWriter>,
+}
+
+impl Default for BuggyWriters {
+    fn default() -> Self {
+        let writer = |name: &str, below: &str, reason: &str| BuggyWriter {
+            name: name.to_string(),
+            below: below.to_string(),
+            reason: reason.to_string(),
+        };
+        Self {
+            level: Level::Warning,
+            writers: vec![
+                writer(
+                    "parquet-mr",
+                    "1.8.0",
+                    "its statistics of binary columns may be wrong (PARQUET-251)",
+                ),
+                writer(
+                    "parquet-mr",
+                    "1.10.0",
+                    "its min and max of strings are compared as signed bytes (PARQUET-686)",
+                ),
+            ],
+        }
+    }
+}
+
+/// Versions of a writer, as named before ` version ` in `created_by`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BuggyWriter {
+    pub name: String,
+    /// First version without the bug.
+    pub below: String,
+    pub reason: String,
+}
+
+/// Optional columns without a single null, when more than `max_fraction` of
+/// the columns are.
+#[derive(Debug, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct OptionalWithoutNulls {
+    pub level: Level,
+    pub max_fraction: f64,
+}
+
+impl Default for OptionalWithoutNulls {
+    fn default() -> Self {
+        Self {
+            level: Level::Warning,
+            max_fraction: 0.5,
+        }
+    }
+}
+
+/// A rule broken by a file.
+pub struct Finding {
+    pub rule: &'static str,
+    pub level: Level,
+    pub message: String,
+}
+
+/// Best-practice findings for a Parquet file.
+pub struct Lint {
+    pub name: String,
+    pub findings: Vec<Finding>,
+}
+
+impl Lint {
+    /// Checks the metadata collected by `viewer` against `config`, only
+    /// reading data to check declared sorting columns.
+    pub fn new(name: String, viewer: &Viewer, config: &LintConfig) -> Result<Self> {
+        let mut lint = Self {
+            name,
+            findings: vec![],
+        };
+        let row_groups = &viewer.row_groups;
+        // All but the last row group, which holds whatever is left.
+        let small: Vec<_> = row_groups
+            .iter()
+            .enumerate()
+            .take(row_groups.len().saturating_sub(1))
+            .filter(|(_, rg)| (rg.compressed_size() as u64) < config.small_row_groups.min_bytes)
+            .collect();
+        if !small.is_empty() {
+            let average = small
+                .iter()
+                .map(|(_, rg)| rg.compressed_size() as u64)
+                .sum::<u64>()
+                / small.len() as u64;
+            lint.add(
+                "small-row-groups",
+                config.small_row_groups.level,
+                format!(
+                    "{} of {} row groups are smaller than {}, averaging {}",
+                    small.len(),
+                    row_groups.len(),
+                    format_size(config.small_row_groups.min_bytes),
+                    format_size(average)
+                ),
+            );
+        }
+        for (i, rg) in row_groups.iter().enumerate() {
+            if rg.compressed_size() as u64 > config.large_row_groups.max_bytes {
+                lint.add(
+                    "large-row-groups",
+                    config.large_row_groups.level,
+                    format!(
+                        "row group {i} is {}, larger than {}",
+                        format_size(rg.compressed_size() as u64),
+                        format_size(config.large_row_groups.max_bytes)
+                    ),
+                );
+            }
+        }
+
+        let without_statistics = columns_where(row_groups, |rg, j| {
+            let chunk = rg.column(j);
+            match chunk.statistics() {
+                None => true,
+                // Columns of only nulls have no min or max.
+                Some(stats) => {
+                    stats.min_bytes_opt().is_none()
+                        && stats.null_count_opt() != Some(chunk.num_values() as u64)
+                }
+            }
+        });
+        if !without_statistics.is_empty() {
+            lint.add(
+                "missing-statistics",
+                config.missing_statistics.level,
+                format!(
+                    "{} have no min and max statistics, so readers can't skip row groups",
+                    list(&without_statistics)
+                ),
+            );
+        }
+        let without_page_index = columns_where(row_groups, |rg, j| {
+            let chunk = rg.column(j);
+            chunk.column_index_offset().is_none() || chunk.offset_index_offset().is_none()
+        });
+        if !without_page_index.is_empty() {
+            lint.add(
+                "missing-page-index",
+                config.missing_page_index.level,
+                format!(
+                    "{} have no page index, so readers can't skip pages",
+                    list(&without_page_index)
+                ),
+            );
+        }
+        let uncompressed = columns_where(row_groups, |rg, j| {
+            rg.column(j).compression() == Compression::UNCOMPRESSED
+        });
+        if !uncompressed.is_empty() {
+            lint.add(
+                "uncompressed",
+                config.uncompressed.level,
+                format!("{} aren't compressed", list(&uncompressed)),
+            );
+        }
+
+        let int96: Vec<_> = row_groups.first().map_or(vec![], |first| {
+            let descr = first.schema_descr();
+            (0..descr.num_columns())
+                .filter(|&j| descr.column(j).physical_type() == Type::INT96)
+                .map(|j| descr.column(j).path().string())
+                .collect()
+        });
+        if !int96.is_empty() {
+            lint.add(
+                "int96-timestamps",
+                config.int96_timestamps.level,
+                format!(
+                    "{} are deprecated INT96 timestamps, rather than INT64 with a timestamp \
+                     logical type",
+                    list(&int96)
+                ),
+            );
+        }
+
+        if let Some((name, version)) = writer_version(&viewer.created_by) {
+            for writer in config.buggy_writers.writers.iter() {
+                if writer.name == name && compare_versions(version, &writer.below).is_lt() {
+                    lint.add(
+                        "buggy-writers",
+                        config.buggy_writers.level,
+                        format!("written by {name} {version}: {}", writer.reason),
+                    );
+                }
+            }
+        }
+
+        if let Some(first) = row_groups.first() {
+            let descr = first.schema_descr();
+            // Nulls in lists are counted per value rather than per row.
+            let optional: Vec<_> = (0..descr.num_columns())
+                .filter(|&j| {
+                    let column = descr.column(j);
+                    column.self_type().is_optional() && column.max_rep_level() == 0
+                })
+                .collect();
+            let without_nulls: Vec<_> = optional
+                .iter()
+                .filter(|&&j| {
+                    row_groups.iter().all(|rg| {
+                        rg.column(j).statistics().and_then(|s| s.null_count_opt()) == Some(0)
+                    })
+                })
+                .map(|&j| descr.column(j).path().string())
+                .collect();
+            let fraction = without_nulls.len() as f64 / descr.num_columns() as f64;
+            if fraction > config.optional_without_nulls.max_fraction {
+                lint.add(
+                    "optional-without-nulls",
+                    config.optional_without_nulls.level,
+                    format!(
+                        "{} of {} columns are optional without a single null, which makes \
+                         readers track nulls for nothing: {}",
+                        without_nulls.len(),
+                        descr.num_columns(),
+                        list(&without_nulls)
+                    ),
+                );
+            }
+        }
+
+        if config.unsorted_data.level != Level::Off {
+            for message in check_sorted(viewer)? {
+                lint.add("unsorted-data", config.unsorted_data.level, message);
+            }
+        }
+        Ok(lint)
+    }
+
+    fn add(&mut self, rule: &'static str, level: Level, message: String) {
+        if level != Level::Off {
+            self.findings.push(Finding {
+                rule,
+                level,
+                message,
+            });
+        }
+    }
+
+    pub fn num_errors(&self) -> usize {
+        self.findings
+            .iter()
+            .filter(|f| f.level == Level::Error)
+            .count()
+    }
+
+    pub fn num_warnings(&self) -> usize {
+        self.findings
+            .iter()
+            .filter(|f| f.level == Level::Warning)
+            .count()
+    }
+}
+
+/// Paths of the columns for which `f` holds in any row group.
+fn columns_where(
+    row_groups: &[RowGroupMetaData],
+    f: impl Fn(&RowGroupMetaData, usize) -> bool,
+) -> Vec<String> {
+    let Some(first) = row_groups.first() else {
+        return vec![];
+    };
+    (0..first.num_columns())
+        .filter(|&j| row_groups.iter().any(|rg| f(rg, j)))
+        .map(|j| first.column(j).column_path().string())
+        .collect()
+}
+
+/// Columns to name in a finding, e.g. "`a`, `b` and 3 more columns".
+fn list(columns: &[String]) -> String {
+    let named: Vec<_> = columns
+        .iter()
+        .take(MAX_NAMED_COLUMNS)
+        .map(|c| format!("`{c}`"))
+        .collect();
+    match columns.len() {
+        1 => format!("column {}", named[0]),
+        n if n <= MAX_NAMED_COLUMNS => format!("columns {}", named.join(", ")),
+        n => format!(
+            "columns {} and {} more",
+            named.join(", "),
+            n - MAX_NAMED_COLUMNS
+        ),
+    }
+}
+
+/// Splits `created_by`, e.g. "parquet-mr version 1.8.1 (build 4aba4d)", into
+/// the writer and its version.
+fn writer_version(created_by: &str) -> Option<(&str, &str)> {
+    let (name, rest) = created_by.split_once(" version ")?;
+    Some((name.trim(), rest.split_whitespace().next()?))
+}
+
+/// Compares dotted versions numerically, ignoring suffixes like `-SNAPSHOT`.
+fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let parse = |v: &str| -> Vec<u64> {
+        v.split('.')
+            .map(|part| {
+                let digits: String = part.chars().take_while(char::is_ascii_digit).collect();
+                digits.parse().unwrap_or(0)
+            })
+            .collect()
+    };
+    parse(a).cmp(&parse(b))
+}
+
+/// Reads the sorting columns declared by each row group, returning where
+/// their rows aren't in order.
+///
+/// Only top-level columns can be read on their own, so row groups sorted by
+/// nested ones are skipped.
+fn check_sorted(viewer: &Viewer) -> Result<Vec<String>> {
+    // The declared sort order of each row group, as top-level columns.
+    let orders: Vec<Option<Vec<(String, SortOptions)>>> = viewer
+        .row_groups
+        .iter()
+        .map(|rg| {
+            let descr = rg.schema_descr();
+            rg.sorting_columns()?
+                .iter()
+                .map(|c| {
+                    let column = descr.columns().get(c.column_idx as usize)?;
+                    let options = SortOptions {
+                        descending: c.descending,
+                        nulls_first: c.nulls_first,
+                    };
+                    (column.path().parts().len() == 1).then(|| (column.name().to_string(), options))
+                })
+                .collect::<Option<Vec<_>>>()
+                .filter(|order| !order.is_empty())
+        })
+        .collect();
+    let mut names: Vec<String> = orders
+        .iter()
+        .flatten()
+        .flatten()
+        .map(|(n, _)| n.clone())
+        .collect();
+    names.sort();
+    names.dedup();
+    if names.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let (_, reader) = viewer.source.reader(Some(&names), BATCH_SIZE)?;
+    let mut messages = vec![];
+    let mut row_group = 0;
+    // Rows of the current row group already read, and the last of them.
+    let mut offset = 0;
+    let mut last: Option<OwnedRow> = None;
+    // Only the first unsorted row of a row group is reported.
+    let mut reported = false;
+    for batch in reader {
+        let batch = batch?;
+        // Batches may span row groups.
+        let mut start = 0;
+        while start < batch.num_rows() && row_group < viewer.row_groups.len() {
+            let num_rows = viewer.row_groups[row_group].num_rows() as usize;
+            let len = (num_rows - offset).min(batch.num_rows() - start);
+            if let (Some(order), false) = (&orders[row_group], reported) {
+                let columns = order
+                    .iter()
+                    .map(|(name, _)| {
+                        let column = batch.column_by_name(name).ok_or_else(|| {
+                            ArrowError::SchemaError(format!("sorting column `{name}` wasn't read"))
+                        })?;
+                        Ok(column.slice(start, len))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                let fields = order
+                    .iter()
+                    .zip(columns.iter())
+                    .map(|((_, options), c)| {
+                        SortField::new_with_options(c.data_type().clone(), *options)
+                    })
+                    .collect();
+                let rows = RowConverter::new(fields)?.convert_columns(&columns)?;
+                let mut previous = last.as_ref().map(|l| l.row());
+                for (i, row) in rows.iter().enumerate() {
+                    if previous.is_some_and(|p| p > row) {
+                        messages.push(format!(
+                            "row group {row_group} declares it's sorted by {}, but its row {} \
+                             is out of order after row {}",
+                            describe_order(order),
+                            offset + i,
+                            offset + i - 1
+                        ));
+                        reported = true;
+                        break;
+                    }
+                    previous = Some(row);
+                }
+                if let Some(i) = rows.num_rows().checked_sub(1) {
+                    last = Some(rows.row(i).owned());
+                }
+            }
+            offset += len;
+            start += len;
+            if offset == num_rows {
+                row_group += 1;
+                offset = 0;
+                last = None;
+                reported = false;
+            }
+        }
+    }
+    Ok(messages)
+}
+
+/// E.g. "`a` descending, `b`".
+fn describe_order(order: &[(String, SortOptions)]) -> String {
+    order
+        .iter()
+        .map(|(name, options)| match options.descending {
+            true => format!("`{name}` descending"),
+            false => format!("`{name}`"),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.name)?;
+        if self.findings.is_empty() {
+            return writeln!(f, "  No findings");
+        }
+        for finding in self.findings.iter() {
+            writeln!(
+                f,
+                "  {}[{}]: {}",
+                finding.level, finding.rule, finding.message
+            )?;
+        }
+        let plural = |n: usize, word: &str| match n {
+            1 => format!("1 {word}"),
+            n => format!("{n} {word}s"),
+        };
+        writeln!(
+            f,
+            "{}, {}",
+            plural(self.num_errors(), "error"),
+            plural(self.num_warnings(), "warning")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cmp::Ordering, fs::File, sync::Arc};
+
+    use arrow::array::{ArrayRef, Int32Array, RecordBatch};
+    use parquet::{
+        arrow::ArrowWriter,
+        data_type::{Int32Type, Int96, Int96Type},
+        file::{properties::WriterProperties, writer::SerializedFileWriter},
+        format::SortingColumn,
+        schema::parser::parse_message_type,
+    };
+
+    use super::*;
+    use crate::source::Source;
+
+    /// Writes `ids` in row groups of two rows, declared sorted by `id`.
+    fn sorted_by_id(path: &Path, ids: Vec<i32>) -> Viewer {
+        let batch =
+            RecordBatch::try_from_iter([("id", Arc::new(Int32Array::from(ids)) as ArrayRef)])
+                .unwrap();
+        let props = WriterProperties::builder()
+            .set_max_row_group_size(2)
+            .set_sorting_columns(Some(vec![SortingColumn {
+                column_idx: 0,
+                descending: false,
+                nulls_first: false,
+            }]))
+            .build();
+        let mut writer =
+            ArrowWriter::try_new(File::create(path).unwrap(), batch.schema(), Some(props)).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+        Viewer::new(Source::file(path).unwrap(), None).unwrap()
+    }
+
+    #[test]
+    fn compares_versions() {
+        assert_eq!(compare_versions("1.10.0", "1.8.1"), Ordering::Greater);
+        assert_eq!(compare_versions("1.8.0-SNAPSHOT", "1.8.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.8", "1.8.1"), Ordering::Less);
+        assert_eq!(compare_versions("2", "1.99.99"), Ordering::Greater);
+        assert_eq!(
+            writer_version("parquet-mr version 1.8.1 (build 4aba4d)"),
+            Some(("parquet-mr", "1.8.1"))
+        );
+        assert_eq!(writer_version("parquet-rs"), None);
+    }
+
+    #[test]
+    fn checks_sorted_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let sorted = sorted_by_id(&dir.path().join("sorted.parquet"), vec![1, 2, 2, 5, 3]);
+        assert!(check_sorted(&sorted).unwrap().is_empty());
+
+        // Row groups are [1, 3], [2, 1] and [4], so only the second is out of
+        // order.
+        let unsorted = sorted_by_id(&dir.path().join("unsorted.parquet"), vec![1, 3, 2, 1, 4]);
+        assert_eq!(
+            check_sorted(&unsorted).unwrap(),
+            ["row group 1 declares it's sorted by `id`, but its row 1 is out of order after row 0"]
+        );
+        let lint = Lint::new("unsorted".to_string(), &unsorted, &LintConfig::default()).unwrap();
+        assert_eq!(lint.num_errors(), 1);
+    }
+
+    #[test]
+    fn finds_int96_columns() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("int96.parquet");
+        let schema =
+            parse_message_type("message m { required int96 ts; required int32 id; }").unwrap();
+        let mut writer = SerializedFileWriter::new(
+            File::create(&path).unwrap(),
+            Arc::new(schema),
+            Default::default(),
+        )
+        .unwrap();
+        let mut row_group = writer.next_row_group().unwrap();
+        let mut column = row_group.next_column().unwrap().unwrap();
+        let mut ts = Int96::new();
+        ts.set_data(0, 0, 2_440_588);
+        column
+            .typed::<Int96Type>()
+            .write_batch(&[ts], None, None)
+            .unwrap();
+        column.close().unwrap();
+        let mut column = row_group.next_column().unwrap().unwrap();
+        column
+            .typed::<Int32Type>()
+            .write_batch(&[1], None, None)
+            .unwrap();
+        column.close().unwrap();
+        row_group.close().unwrap();
+        writer.close().unwrap();
+
+        let viewer = Viewer::new(Source::file(&path).unwrap(), None).unwrap();
+        let lint = Lint::new("int96".to_string(), &viewer, &LintConfig::default()).unwrap();
+        let int96: Vec<_> = lint
+            .findings
+            .iter()
+            .filter(|f| f.rule == "int96-timestamps")
+            .collect();
+        assert_eq!(int96.len(), 1);
+        assert!(int96[0].message.starts_with("column `ts` "));
+    }
+}
@@ -0,0 +1,129 @@
+use std::{fs::File, io, path::Path};
+
+use bytes::{buf::Reader, Bytes};
+use memmap2::Mmap;
+use parquet::{
+    errors::{ParquetError, Result},
+    file::reader::{ChunkReader, Length},
+};
+
+/// A local file mapped into memory, whose column chunks are read without
+/// copying them or seeking a shared file handle, so any number of threads
+/// can decode it at once. Only the pages read are loaded, by the OS, which
+/// also drops them again under memory pressure.
+#[derive(Clone)]
+pub struct MappedFile {
+    bytes: Bytes,
+}
+
+/// Magic numbers of the filesystems whose pages can fail to load, which
+/// mapped files aren't safe on: NFS, SMB, CIFS, SMB2, FUSE (e.g. sshfs), 9P,
+/// Ceph and AFS.
+#[cfg(target_os = "linux")]
+const NETWORK_FILESYSTEMS: [u32; 8] = [
+    0x6969,
+    0x517b,
+    0xff53_4d42,
+    0xfe53_4d42,
+    0x6573_5546,
+    0x0102_1997,
+    0x00c3_6400,
+    0x5346_414f,
+];
+
+impl MappedFile {
+    /// Maps the regular file at `path`, failing for anything else and for
+    /// files on network filesystems, which are read through a [`File`]
+    /// instead.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        if !file.metadata()?.is_file() || on_network_filesystem(&file) {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("`{}` can't be mapped safely", path.display()),
+            ));
+        }
+        // Safety: the file must not change while it's mapped. If another
+        // process truncates it, or a network filesystem fails to load a
+        // page, reading the page raises SIGBUS, which kills parqour without
+        // restoring the terminal. That's why only regular, local files are
+        // mapped; one rewritten under the reader can't be ruled out.
+        let mmap = unsafe { Mmap::map(&file)? };
+        Ok(Self {
+            bytes: Bytes::from_owner(mmap),
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn on_network_filesystem(file: &File) -> bool {
+    use std::{mem::MaybeUninit, os::fd::AsRawFd};
+
+    let mut stat = MaybeUninit::<libc::statfs>::uninit();
+    // Safety: `fstatfs` fills in `stat` when it succeeds.
+    let stat = unsafe {
+        if libc::fstatfs(file.as_raw_fd(), stat.as_mut_ptr()) != 0 {
+            return false;
+        }
+        stat.assume_init()
+    };
+    NETWORK_FILESYSTEMS.contains(&(stat.f_type as u32))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn on_network_filesystem(_file: &File) -> bool {
+    false
+}
+
+impl Length for MappedFile {
+    fn len(&self) -> u64 {
+        self.bytes.len() as u64
+    }
+}
+
+impl ChunkReader for MappedFile {
+    type T = Reader<Bytes>;
+
+    fn get_read(&self, start: u64) -> Result<Self::T> {
+        self.get_bytes(start, 0)?;
+        self.bytes.get_read(start)
+    }
+
+    /// Fails rather than panics past the end of the file, which damaged
+    /// metadata can point to.
+    fn get_bytes(&self, start: u64, length: usize) -> Result<Bytes> {
+        let end = start
+            .checked_add(length as u64)
+            .filter(|end| *end <= self.len());
+        match end {
+            Some(end) => Ok(self.bytes.slice(start as usize..end as usize)),
+            None => Err(ParquetError::EOF(format!(
+                "{length} bytes at offset {start} run past the end of the file, at {}",
+                self.len()
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn maps_only_regular_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let error = MappedFile::open(dir.path()).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+
+        let path = dir.path().join("data");
+        fs::write(&path, b"0123456789").unwrap();
+        let (mapped, read) = (MappedFile::open(&path).unwrap(), File::open(&path).unwrap());
+        assert_eq!(
+            mapped.get_bytes(2, 3).unwrap(),
+            read.get_bytes(2, 3).unwrap()
+        );
+        assert!(mapped.get_bytes(8, 3).is_err());
+    }
+}
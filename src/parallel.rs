@@ -0,0 +1,242 @@
+use std::sync::Arc;
+
+use arrow::{
+    array::{RecordBatch, RecordBatchReader},
+    datatypes::{Schema, SchemaRef},
+    error::ArrowError,
+};
+use parquet::arrow::{
+    arrow_reader::{
+        ArrowReaderMetadata, ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder,
+    },
+    ProjectionMask,
+};
+use rayon::prelude::*;
+
+use crate::{error::Result, mmap::MappedFile, source::projection};
+
+/// Rows decoded at a time by each column's reader, so that there's enough
+/// work per column to be worth sharing out, however few rows are asked for.
+const DECODE_BATCH_SIZE: usize = 8192;
+
+/// Reads a local Parquet file, decoding the top-level columns of each batch
+/// in parallel: one reader per column, all reading the same rows.
+///
+/// The columns are decoded [`DECODE_BATCH_SIZE`] rows at a time, then handed
+/// out `batch_size` rows at a time.
+pub struct ParallelReader {
+    schema: SchemaRef,
+    readers: Vec<ParquetRecordBatchReader>,
+    batch_size: usize,
+    /// The rows decoded last, and how many of them were handed out.
+    decoded: Option<(RecordBatch, usize)>,
+}
+
+impl ParallelReader {
+    /// Reads `columns` of `file`, or every column when `None`.
+    pub fn new(
+        file: &MappedFile,
+        metadata: &ArrowReaderMetadata,
+        columns: Option<&[String]>,
+        batch_size: usize,
+    ) -> Result<Self> {
+        let builder = |batch_size| {
+            ParquetRecordBatchReaderBuilder::new_with_metadata(file.clone(), metadata.clone())
+                .with_batch_size(batch_size)
+        };
+        let roots = roots(metadata, columns);
+        // Nothing to share out between threads.
+        if roots.len() < 2 {
+            let mask = projection(metadata.schema(), metadata.parquet_schema(), columns);
+            let reader = builder(batch_size).with_projection(mask).build()?;
+            return Ok(Self {
+                schema: reader.schema(),
+                readers: vec![reader],
+                batch_size,
+                decoded: None,
+            });
+        }
+        let readers = roots
+            .iter()
+            .map(|&i| {
+                let mask = ProjectionMask::roots(metadata.parquet_schema(), [i]);
+                builder(batch_size.max(DECODE_BATCH_SIZE))
+                    .with_projection(mask)
+                    .build()
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let schema = metadata.schema();
+        let fields: Vec<_> = roots.iter().map(|&i| schema.field(i).clone()).collect();
+        let schema = Schema::new_with_metadata(fields, schema.metadata().clone());
+        Ok(Self {
+            schema: Arc::new(schema),
+            readers,
+            batch_size,
+            decoded: None,
+        })
+    }
+
+    /// Decodes the next rows of every column in parallel.
+    fn decode(&mut self) -> Option<std::result::Result<RecordBatch, ArrowError>> {
+        let batches: Vec<_> = self.readers.par_iter_mut().map(|r| r.next()).collect();
+        // The readers run out together, as they read the same rows.
+        let batches = batches.into_iter().collect::<Option<Vec<_>>>()?;
+        let columns = batches
+            .into_iter()
+            .map(|batch| Ok(Arc::clone(batch?.column(0))))
+            .collect::<std::result::Result<Vec<_>, ArrowError>>();
+        Some(columns.and_then(|columns| RecordBatch::try_new(Arc::clone(&self.schema), columns)))
+    }
+}
+
+impl Iterator for ParallelReader {
+    type Item = std::result::Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let [reader] = self.readers.as_mut_slice() {
+            return reader.next();
+        }
+        loop {
+            if let Some((batch, offset)) = self.decoded.as_mut() {
+                if *offset < batch.num_rows() {
+                    let length = self.batch_size.min(batch.num_rows() - *offset);
+                    let rows = batch.slice(*offset, length);
+                    *offset += length;
+                    return Some(Ok(rows));
+                }
+            }
+            match self.decode()? {
+                Ok(batch) => self.decoded = Some((batch, 0)),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+impl RecordBatchReader for ParallelReader {
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+}
+
+/// A reader of `columns` of each row group of `file`, or of every column when
+/// `None`, with its number of rows, for whole-file scans to go through row
+/// groups in parallel.
+pub fn row_group_readers(
+    file: &MappedFile,
+    metadata: &ArrowReaderMetadata,
+    columns: Option<&[String]>,
+    batch_size: usize,
+) -> Result<Vec<(usize, ParquetRecordBatchReader)>> {
+    let mask = projection(metadata.schema(), metadata.parquet_schema(), columns);
+    metadata
+        .metadata()
+        .row_groups()
+        .iter()
+        .enumerate()
+        .map(|(i, row_group)| {
+            let reader =
+                ParquetRecordBatchReaderBuilder::new_with_metadata(file.clone(), metadata.clone())
+                    .with_projection(mask.clone())
+                    .with_row_groups(vec![i])
+                    .with_batch_size(batch_size)
+                    .build()?;
+            Ok((row_group.num_rows() as usize, reader))
+        })
+        .collect()
+}
+
+/// Indices of the top-level fields named in `columns`, or of every field.
+fn roots(metadata: &ArrowReaderMetadata, columns: Option<&[String]>) -> Vec<usize> {
+    let fields = metadata.schema().fields();
+    (0..fields.len())
+        .filter(|&i| columns.is_none_or(|columns| columns.contains(fields[i].name())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use arrow::{
+        array::{ArrayRef, Int64Array, ListArray, StringArray, StructArray},
+        compute::concat_batches,
+        datatypes::{DataType, Field, Int64Type},
+    };
+    use parquet::{arrow::ArrowWriter, file::properties::WriterProperties};
+
+    use super::*;
+
+    const NUM_ROWS: i64 = 20_000;
+
+    /// Writes flat, struct and list columns, in row groups of 3000 rows.
+    fn write(path: &std::path::Path) {
+        let ids = Int64Array::from_iter_values(0..NUM_ROWS);
+        let names =
+            StringArray::from_iter((0..NUM_ROWS).map(|i| (i % 3 > 0).then(|| i.to_string())));
+        let point = StructArray::from(vec![
+            (
+                Arc::new(Field::new("x", DataType::Int64, false)),
+                Arc::new(Int64Array::from_iter_values((0..NUM_ROWS).map(|i| i * 2))) as ArrayRef,
+            ),
+            (
+                Arc::new(Field::new("label", DataType::Utf8, true)),
+                Arc::new(names.clone()) as ArrayRef,
+            ),
+        ]);
+        let tags = ListArray::from_iter_primitive::<Int64Type, _, _>(
+            (0..NUM_ROWS).map(|i| (i % 5 > 0).then(|| (0..i % 4).map(Some).collect::<Vec<_>>())),
+        );
+        let batch = RecordBatch::try_from_iter([
+            ("id", Arc::new(ids) as ArrayRef),
+            ("name", Arc::new(names) as ArrayRef),
+            ("point", Arc::new(point) as ArrayRef),
+            ("tags", Arc::new(tags) as ArrayRef),
+        ])
+        .unwrap();
+        let properties = WriterProperties::builder()
+            .set_max_row_group_size(3000)
+            .build();
+        let mut writer = ArrowWriter::try_new(
+            File::create(path).unwrap(),
+            batch.schema(),
+            Some(properties),
+        )
+        .unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+    }
+
+    #[test]
+    fn reads_like_the_sequential_reader() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.parquet");
+        write(&path);
+        let file = MappedFile::open(&path).unwrap();
+        let metadata = ArrowReaderMetadata::load(&file, Default::default()).unwrap();
+
+        let columns = ["tags".to_string(), "id".to_string(), "point".to_string()];
+        for columns in [None, Some(&columns[..])] {
+            let reader = ParallelReader::new(&file, &metadata, columns, 64).unwrap();
+            assert_eq!(reader.readers.len(), columns.map_or(4, |c| c.len()));
+            let schema = reader.schema();
+            let batches = reader.collect::<std::result::Result<Vec<_>, _>>().unwrap();
+            assert!(batches
+                .iter()
+                .all(|b| b.num_rows() == 64 || b.num_rows() == NUM_ROWS as usize % 64));
+
+            let mask = projection(metadata.schema(), metadata.parquet_schema(), columns);
+            let expected =
+                ParquetRecordBatchReaderBuilder::new_with_metadata(file.clone(), metadata.clone())
+                    .with_projection(mask)
+                    .build()
+                    .unwrap()
+                    .collect::<std::result::Result<Vec<_>, _>>()
+                    .unwrap();
+            assert_eq!(
+                concat_batches(&schema, &batches).unwrap(),
+                concat_batches(&schema, &expected).unwrap()
+            );
+        }
+    }
+}
@@ -0,0 +1,583 @@
+use arrow::{
+    array::{
+        new_null_array, ArrayRef, RecordBatch, RecordBatchOptions, RecordBatchReader, StringArray,
+    },
+    compute::cast,
+    datatypes::{DataType, Field, Schema, SchemaRef},
+    error::ArrowError,
+};
+use parquet::{
+    arrow::arrow_reader::{
+        ArrowReaderMetadata, ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder,
+    },
+    file::metadata::ParquetMetaDataReader,
+};
+
+use crate::{
+    error::{Error, Result},
+    source::projection,
+};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs::{self, File},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+
+/// Directory name Hive uses for null partition values.
+const HIVE_DEFAULT_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// A Parquet file found under a partitioned directory.
+#[derive(Clone, Debug)]
+pub struct PartitionFile {
+    pub path: PathBuf,
+    /// Partition values, in the same order as [`Partitioning::keys`].
+    pub values: Vec<Option<String>>,
+    pub num_rows: i64,
+    pub size: u64,
+}
+
+/// Hive-style partitioned directory, e.g. `dt=2024-10-01/region=eu/part-0.parquet`.
+#[derive(Clone, Debug)]
+pub struct Partitioning {
+    pub root: PathBuf,
+    /// Partition keys, in the order they first appear in the directory tree.
+    pub keys: Vec<String>,
+    pub files: Vec<PartitionFile>,
+}
+
+/// Files, rows and bytes sharing a single partition value.
+#[derive(Clone, Debug)]
+pub struct PartitionSummary {
+    pub key: String,
+    pub value: Option<String>,
+    pub num_files: usize,
+    pub num_rows: i64,
+    pub size: u64,
+}
+
+impl Partitioning {
+    /// Walks `root` and collects every Parquet file together with the
+    /// `key=value` pairs found in its path.
+    pub fn discover(root: &Path) -> Result<Self> {
+        let mut paths = vec![];
+        collect_parquet_files(root, &mut paths)?;
+
+        let mut keys: Vec<String> = vec![];
+        let mut files = vec![];
+        for path in paths {
+            let mut pairs = vec![];
+            if let Some(parent) = path.strip_prefix(root).ok().and_then(Path::parent) {
+                for component in parent.iter() {
+                    if let Some((k, v)) = component.to_string_lossy().split_once('=') {
+                        pairs.push((k.to_string(), decode_value(v)));
+                    }
+                }
+            }
+            for (k, _) in pairs.iter() {
+                if !keys.contains(k) {
+                    keys.push(k.clone());
+                }
+            }
+
+            let file = File::open(&path)?;
+            let size = file.metadata()?.len();
+            let num_rows = ParquetMetaDataReader::new()
+                .parse_and_finish(&file)?
+                .file_metadata()
+                .num_rows();
+            files.push((path, pairs, num_rows, size));
+        }
+
+        if files.is_empty() {
+            return Err(Error::NoParquetFiles(root.to_path_buf()));
+        }
+
+        let files = files
+            .into_iter()
+            .map(|(path, pairs, num_rows, size)| {
+                let values = keys
+                    .iter()
+                    .map(|key| {
+                        pairs
+                            .iter()
+                            .find(|(k, _)| k == key)
+                            .and_then(|(_, v)| v.clone())
+                    })
+                    .collect();
+                PartitionFile {
+                    path,
+                    values,
+                    num_rows,
+                    size,
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            keys,
+            files,
+        })
+    }
+
+    /// Drops the files whose partition values don't satisfy every filter.
+    ///
+    /// Fails on filters on keys that aren't partition keys, which would
+    /// otherwise silently keep every file.
+    pub fn prune(&mut self, filters: &[PartitionFilter]) -> Result<()> {
+        let mut positions = vec![];
+        for filter in filters {
+            let position = self.keys.iter().position(|k| *k == filter.key);
+            let Some(i) = position else {
+                return Err(Error::UnknownPartitionKey {
+                    key: filter.key.clone(),
+                    keys: self.keys.clone(),
+                });
+            };
+            positions.push((i, filter));
+        }
+        self.files.retain(|file| {
+            positions
+                .iter()
+                .all(|(i, filter)| filter.matches(file.values[*i].as_deref()))
+        });
+        Ok(())
+    }
+
+    pub fn num_rows(&self) -> i64 {
+        self.files.iter().map(|f| f.num_rows).sum()
+    }
+
+    /// File counts, row counts and byte sizes per partition value.
+    pub fn summary(&self) -> Vec<PartitionSummary> {
+        let mut groups: BTreeMap<(usize, Option<&str>), (usize, i64, u64)> = BTreeMap::new();
+        for file in self.files.iter() {
+            for (i, value) in file.values.iter().enumerate() {
+                let entry = groups.entry((i, value.as_deref())).or_default();
+                entry.0 += 1;
+                entry.1 += file.num_rows;
+                entry.2 += file.size;
+            }
+        }
+        groups
+            .into_iter()
+            .map(|((i, value), (num_files, num_rows, size))| PartitionSummary {
+                key: self.keys[i].clone(),
+                value: value.map(str::to_string),
+                num_files,
+                num_rows,
+                size,
+            })
+            .collect()
+    }
+
+    /// Reader over all files, with the partition keys appended as `Utf8` columns.
+    ///
+    /// Only `columns` are read, file and partition columns alike, when given.
+    /// Keys that are also columns of the files, as with Iceberg identity
+    /// partitions, aren't appended again.
+    pub fn reader(
+        &self,
+        columns: Option<&[String]>,
+        batch_size: usize,
+    ) -> Result<PartitionedReader> {
+        let schema = self.schema()?;
+        let mut fields: Vec<_> = schema
+            .fields()
+            .iter()
+            .filter(|f| columns.is_none_or(|c| c.contains(f.name())))
+            .cloned()
+            .collect();
+        let file_fields = fields.len();
+        let keys: Vec<_> = (0..self.keys.len())
+            .filter(|&i| columns.is_none_or(|c| c.contains(&self.keys[i])))
+            .filter(|&i| schema.field_with_name(&self.keys[i]).is_err())
+            .collect();
+        for &i in keys.iter() {
+            fields.push(Arc::new(Field::new(&self.keys[i], DataType::Utf8, true)));
+        }
+
+        Ok(PartitionedReader {
+            schema: Arc::new(Schema::new(fields)),
+            files: self.files.iter().cloned().collect(),
+            current: None,
+            file_fields,
+            keys,
+            batch_size,
+        })
+    }
+
+    /// Columns of all files, those added by later files coming last and
+    /// nullable unless every file has them.
+    ///
+    /// Fails naming the first file with a column of another type than in the
+    /// files before it.
+    fn schema(&self) -> Result<Schema> {
+        if self.files.is_empty() {
+            return Err(Error::NoParquetFiles(self.root.clone()));
+        }
+        let mut merged = Schema::empty();
+        let mut schemas = vec![];
+        for file in self.files.iter() {
+            let schema = ArrowReaderMetadata::load(&File::open(&file.path)?, Default::default())?
+                .schema()
+                .as_ref()
+                .clone()
+                .with_metadata(Default::default());
+            merged = Schema::try_merge([merged, schema.clone()])
+                .map_err(|e| Error::MismatchedPartitionFile(file.path.clone(), e.to_string()))?;
+            schemas.push(schema);
+        }
+        let fields: Vec<_> = merged
+            .fields()
+            .iter()
+            .map(|field| {
+                let everywhere = schemas
+                    .iter()
+                    .all(|s| s.field_with_name(field.name()).is_ok());
+                field
+                    .as_ref()
+                    .clone()
+                    .with_nullable(field.is_nullable() || !everywhere)
+            })
+            .collect();
+        Ok(Schema::new(fields))
+    }
+}
+
+fn collect_parquet_files(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+    let mut entries = fs::read_dir(dir)?.collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        // Skip hidden files and markers such as `_SUCCESS`.
+        if name.starts_with('.') || name.starts_with('_') {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            collect_parquet_files(&path, paths)?;
+        } else if path.extension().is_some_and(|e| e == "parquet") {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Undoes the percent-encoding Hive applies to partition values.
+fn decode_value(value: &str) -> Option<String> {
+    if value == HIVE_DEFAULT_PARTITION {
+        return None;
+    }
+    Some(percent_decode(value))
+}
+
+/// Decodes `%XX` escapes, leaving malformed ones as they are.
+pub(crate) fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                decoded.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                decoded.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Filter on a partition key, e.g. `region=eu` or `region!=eu`.
+#[derive(Clone, Debug)]
+pub struct PartitionFilter {
+    pub key: String,
+    pub value: String,
+    pub negated: bool,
+}
+
+impl PartitionFilter {
+    fn matches(&self, value: Option<&str>) -> bool {
+        (value == Some(self.value.as_str())) != self.negated
+    }
+}
+
+impl FromStr for PartitionFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (key, value, negated) = if let Some((k, v)) = s.split_once("!=") {
+            (k, v, true)
+        } else if let Some((k, v)) = s.split_once('=') {
+            (k, v, false)
+        } else {
+            return Err(format!("expected `key=value` or `key!=value`, got `{s}`"));
+        };
+        Ok(Self {
+            key: key.trim().to_string(),
+            value: value.trim().to_string(),
+            negated,
+        })
+    }
+}
+
+/// Reads the files of a [`Partitioning`] one after the other.
+pub struct PartitionedReader {
+    schema: SchemaRef,
+    files: VecDeque<PartitionFile>,
+    current: Option<(ParquetRecordBatchReader, Vec<Option<String>>)>,
+    /// Number of fields of the schema read from the files, which come
+    /// before the partition keys. Files without some of them read as nulls.
+    file_fields: usize,
+    /// Indices of the partition keys to append.
+    keys: Vec<usize>,
+    batch_size: usize,
+}
+
+impl PartitionedReader {
+    fn open_next(&mut self) -> Option<std::result::Result<(), ArrowError>> {
+        let file = self.files.pop_front()?;
+        let columns: Vec<_> = self.schema.fields()[..self.file_fields]
+            .iter()
+            .map(|f| f.name().clone())
+            .collect();
+        let reader = File::open(&file.path)
+            .map_err(|e| ArrowError::ExternalError(Box::new(e)))
+            .and_then(|f| {
+                let builder = ParquetRecordBatchReaderBuilder::try_new(f)?;
+                let mask = projection(builder.schema(), builder.parquet_schema(), Some(&columns));
+                Ok(builder
+                    .with_projection(mask)
+                    .with_batch_size(self.batch_size)
+                    .build()?)
+            });
+        let values = self.keys.iter().map(|&i| file.values[i].clone()).collect();
+        Some(reader.map(|reader| self.current = Some((reader, values))))
+    }
+}
+
+impl Iterator for PartitionedReader {
+    type Item = std::result::Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((reader, values)) = self.current.as_mut() {
+                match reader.next() {
+                    Some(Ok(batch)) => {
+                        return Some(align(&self.schema, self.file_fields, batch, values));
+                    }
+                    Some(Err(e)) => return Some(Err(e)),
+                    None => self.current = None,
+                }
+            }
+            if let Err(e) = self.open_next()? {
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+/// Lines the columns of `batch` up with `schema`, filling the first
+/// `file_fields` ones its file doesn't have with nulls and appending the
+/// partition `values`.
+fn align(
+    schema: &SchemaRef,
+    file_fields: usize,
+    batch: RecordBatch,
+    values: &[Option<String>],
+) -> std::result::Result<RecordBatch, ArrowError> {
+    let num_rows = batch.num_rows();
+    let mut columns = vec![];
+    for field in schema.fields()[..file_fields].iter() {
+        let column = match batch.column_by_name(field.name()) {
+            Some(column) if column.data_type() == field.data_type() => Arc::clone(column),
+            Some(column) => cast(column, field.data_type())?,
+            None => new_null_array(field.data_type(), num_rows),
+        };
+        columns.push(column);
+    }
+    for value in values.iter() {
+        let array = StringArray::from(vec![value.as_deref(); num_rows]);
+        columns.push(Arc::new(array) as ArrayRef);
+    }
+    let options = RecordBatchOptions::new().with_row_count(Some(num_rows));
+    RecordBatch::try_new_with_options(Arc::clone(schema), columns, &options)
+}
+
+impl RecordBatchReader for PartitionedReader {
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::{
+        array::{Array, Int64Array},
+        util::pretty::pretty_format_batches,
+    };
+    use parquet::arrow::ArrowWriter;
+
+    use super::*;
+
+    fn write(path: &Path, batch: RecordBatch) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let mut writer =
+            ArrowWriter::try_new(File::create(path).unwrap(), batch.schema(), None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+    }
+
+    fn ids(ids: Vec<i64>) -> RecordBatch {
+        RecordBatch::try_from_iter([("id", Arc::new(Int64Array::from(ids)) as ArrayRef)]).unwrap()
+    }
+
+    fn read(partitioning: &Partitioning) -> String {
+        let batches = partitioning
+            .reader(None, 1024)
+            .unwrap()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .unwrap();
+        pretty_format_batches(&batches).unwrap().to_string()
+    }
+
+    #[test]
+    fn decodes_values() {
+        assert_eq!(decode_value("eu"), Some("eu".to_string()));
+        assert_eq!(decode_value("a%20b%3Dc"), Some("a b=c".to_string()));
+        assert_eq!(decode_value("100%"), Some("100%".to_string()));
+        assert_eq!(decode_value("%zz"), Some("%zz".to_string()));
+        assert_eq!(decode_value(HIVE_DEFAULT_PARTITION), None);
+    }
+
+    #[test]
+    fn parses_filters() {
+        let filter: PartitionFilter = " region = eu ".parse().unwrap();
+        assert_eq!(filter.key, "region");
+        assert_eq!(filter.value, "eu");
+        assert!(!filter.negated);
+        assert!(filter.matches(Some("eu")));
+        assert!(!filter.matches(Some("us")));
+        assert!(!filter.matches(None));
+
+        let filter: PartitionFilter = "region!=eu".parse().unwrap();
+        assert!(filter.negated);
+        assert!(!filter.matches(Some("eu")));
+        assert!(filter.matches(Some("us")));
+        assert!(filter.matches(None));
+
+        assert!("region".parse::<PartitionFilter>().is_err());
+    }
+
+    #[test]
+    fn discovers_and_prunes() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(
+            &root.join("dt=2024-10-01/region=eu/part-0.parquet"),
+            ids(vec![1, 2]),
+        );
+        write(
+            &root.join("dt=2024-10-01/region=us/part-0.parquet"),
+            ids(vec![3]),
+        );
+        write(
+            &root.join(format!(
+                "dt=2024-10-02/region={HIVE_DEFAULT_PARTITION}/part-0.parquet"
+            )),
+            ids(vec![4]),
+        );
+        fs::write(root.join("_SUCCESS"), "").unwrap();
+
+        let mut partitioning = Partitioning::discover(root).unwrap();
+        assert_eq!(partitioning.keys, ["dt", "region"]);
+        assert_eq!(partitioning.files.len(), 3);
+        assert_eq!(partitioning.num_rows(), 4);
+        assert_eq!(
+            partitioning.files[2].values,
+            [Some("2024-10-02".to_string()), None]
+        );
+
+        let filters = ["region!=us".parse().unwrap()];
+        partitioning.prune(&filters).unwrap();
+        assert_eq!(partitioning.num_rows(), 3);
+
+        let filters = ["country=fr".parse().unwrap()];
+        let error = partitioning.prune(&filters).unwrap_err();
+        assert!(matches!(error, Error::UnknownPartitionKey { key, .. } if key == "country"));
+        assert_eq!(partitioning.num_rows(), 3);
+
+        assert_eq!(
+            read(&partitioning),
+            "\
++----+------------+--------+
+| id | dt         | region |
++----+------------+--------+
+| 1  | 2024-10-01 | eu     |
+| 2  | 2024-10-01 | eu     |
+| 4  | 2024-10-02 |        |
++----+------------+--------+"
+        );
+    }
+
+    #[test]
+    fn merges_file_schemas() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(&root.join("n=1/part-0.parquet"), ids(vec![1]));
+        let batch = RecordBatch::try_from_iter([
+            ("id", Arc::new(Int64Array::from(vec![2])) as ArrayRef),
+            ("name", Arc::new(StringArray::from(vec!["b"])) as ArrayRef),
+        ])
+        .unwrap();
+        write(&root.join("n=2/part-0.parquet"), batch);
+
+        let partitioning = Partitioning::discover(root).unwrap();
+        let reader = partitioning
+            .reader(Some(&["name".to_string()]), 1024)
+            .unwrap();
+        let batches = reader.collect::<std::result::Result<Vec<_>, _>>().unwrap();
+        assert_eq!(batches[0].num_columns(), 1);
+        assert!(batches[0].column(0).is_null(0));
+        assert_eq!(
+            read(&partitioning),
+            "\
++----+------+---+
+| id | name | n |
++----+------+---+
+| 1  |      | 1 |
+| 2  | b    | 2 |
++----+------+---+"
+        );
+    }
+
+    #[test]
+    fn reports_mismatched_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(&root.join("n=1/part-0.parquet"), ids(vec![1]));
+        let batch = RecordBatch::try_from_iter([(
+            "id",
+            Arc::new(StringArray::from(vec!["2"])) as ArrayRef,
+        )])
+        .unwrap();
+        write(&root.join("n=2/part-0.parquet"), batch);
+
+        let partitioning = Partitioning::discover(root).unwrap();
+        let error = partitioning.reader(None, 1024).err().unwrap();
+        assert!(
+            matches!(error, Error::MismatchedPartitionFile(path, _) if path.ends_with("n=2/part-0.parquet"))
+        );
+    }
+}
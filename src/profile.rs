@@ -0,0 +1,665 @@
+use arrow::{
+    array::{Array, AsArray, RecordBatchReader},
+    compute::cast,
+    datatypes::{DataType, Field, Float64Type, SchemaRef},
+    util::display::{ArrayFormatter, FormatOptions},
+};
+
+use rayon::prelude::*;
+
+use crate::error::{Error, Result};
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{
+        atomic::{self, AtomicUsize},
+        Mutex,
+    },
+};
+
+/// Values kept to estimate quantiles and the histogram of numeric columns.
+const RESERVOIR_SIZE: usize = 100_000;
+/// Distinct values counted exactly before the rarest ones get dropped.
+const TOP_VALUES_CAPACITY: usize = 1024;
+pub const TOP_VALUES: usize = 10;
+pub const HISTOGRAM_BINS: usize = 20;
+/// Quantiles reported for numeric columns.
+pub const QUANTILES: [f64; 5] = [0.01, 0.25, 0.5, 0.75, 0.99];
+
+/// Summary of the values of a single column.
+#[derive(Clone, Debug)]
+pub struct ColumnProfile {
+    pub column: String,
+    pub data_type: DataType,
+    /// Rows scanned.
+    pub num_rows: usize,
+    pub null_count: usize,
+    /// HyperLogLog estimate of the number of distinct non-null values.
+    pub distinct: u64,
+    pub numeric: Option<NumericProfile>,
+    pub lengths: Option<LengthProfile>,
+    /// Most frequent values with their counts, most frequent first.
+    pub top_values: Vec<(String, u64)>,
+    /// Whether the counts of [`Self::top_values`] are lower bounds rather than
+    /// exact, because the column had too many distinct values to count them all.
+    pub top_values_approximate: bool,
+    /// Whether only the first rows were scanned rather than the whole column.
+    pub sampled: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct NumericProfile {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub stddev: f64,
+    /// Values at [`QUANTILES`], estimated from a sample of the values.
+    pub quantiles: Vec<f64>,
+    /// Counts over [`HISTOGRAM_BINS`] equal-width bins from `min` to `max`,
+    /// estimated from a sample of the values.
+    pub histogram: Vec<u64>,
+}
+
+/// Distribution of string lengths, in characters.
+#[derive(Clone, Debug)]
+pub struct LengthProfile {
+    pub min: usize,
+    pub max: usize,
+    pub mean: f64,
+    /// Number of values of each length from `min` to `max`.
+    pub counts: Vec<u64>,
+}
+
+impl ColumnProfile {
+    /// Scans `column` of the batches of `reader`, stopping after `limit` rows
+    /// when given, and telling `progress` how many rows it's scanned so far.
+    /// Scanning stops early, as if sampled, once `progress` returns `false`.
+    pub fn compute(
+        reader: impl RecordBatchReader,
+        column: &str,
+        limit: Option<usize>,
+        mut progress: impl FnMut(usize) -> bool,
+    ) -> Result<Self> {
+        let field = field(reader.schema(), column)?;
+        let mut profiler = Profiler::default();
+        let mut scanned = 0;
+        let sampled = profiler.scan(reader, column, limit, |rows| {
+            scanned += rows;
+            progress(scanned)
+        })?;
+        Ok(profiler.finish(column.to_string(), field.data_type().clone(), sampled))
+    }
+
+    /// Like [`Self::compute`], scanning row groups in parallel. `row_groups`
+    /// are readers of each, in file order, with their number of rows.
+    pub fn compute_parallel<R: RecordBatchReader + Send>(
+        row_groups: Vec<(usize, R)>,
+        column: &str,
+        limit: Option<usize>,
+        progress: impl FnMut(usize) -> bool + Send,
+    ) -> Result<Self> {
+        let Some((_, first)) = row_groups.first() else {
+            return Err(Error::UnknownColumn(column.to_string()));
+        };
+        let field = field(first.schema(), column)?;
+        // The first rows of the file, up to the limit.
+        let mut remaining = limit.unwrap_or(usize::MAX);
+        let mut sampled = false;
+        let row_groups: Vec<_> = row_groups
+            .into_iter()
+            .filter_map(|(num_rows, reader)| {
+                sampled |= num_rows > remaining;
+                let rows = num_rows.min(remaining);
+                remaining -= rows;
+                (rows > 0).then_some((rows, reader))
+            })
+            .collect();
+
+        let scanned = AtomicUsize::new(0);
+        let progress = Mutex::new(progress);
+        let profilers = row_groups
+            .into_par_iter()
+            .map(|(rows, reader)| {
+                let mut profiler = Profiler::default();
+                profiler.scan(reader, column, Some(rows), |rows| {
+                    let scanned = scanned.fetch_add(rows, atomic::Ordering::Relaxed) + rows;
+                    progress
+                        .lock()
+                        .map_or(true, |mut progress| progress(scanned))
+                })?;
+                Ok(profiler)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let profiler = profilers
+            .into_iter()
+            .reduce(Profiler::merge)
+            .unwrap_or_default();
+        Ok(profiler.finish(column.to_string(), field.data_type().clone(), sampled))
+    }
+}
+
+fn field(schema: SchemaRef, column: &str) -> Result<Field> {
+    schema
+        .field_with_name(column)
+        .cloned()
+        .map_err(|_| Error::UnknownColumn(column.to_string()))
+}
+
+#[derive(Default)]
+struct Profiler {
+    num_rows: usize,
+    null_count: usize,
+    hll: HyperLogLog,
+    // Welford's online mean and variance.
+    count: u64,
+    mean: f64,
+    m2: f64,
+    min: f64,
+    max: f64,
+    reservoir: Reservoir,
+    lengths: BTreeMap<usize, u64>,
+    values: HashMap<String, u64>,
+    values_pruned: bool,
+}
+
+impl Profiler {
+    /// Scans `column` of the batches of `reader`, stopping after `limit` rows
+    /// when given or once `progress`, told how many rows each batch added,
+    /// returns `false`. Returns whether rows were left unscanned.
+    fn scan(
+        &mut self,
+        mut reader: impl RecordBatchReader,
+        column: &str,
+        limit: Option<usize>,
+        mut progress: impl FnMut(usize) -> bool,
+    ) -> Result<bool> {
+        let mut remaining = limit.unwrap_or(usize::MAX);
+        for batch in reader.by_ref() {
+            let batch = batch?;
+            let array = batch
+                .column_by_name(column)
+                .ok_or_else(|| Error::UnknownColumn(column.to_string()))?;
+            if array.len() > remaining {
+                self.update(array.slice(0, remaining).as_ref())?;
+                progress(remaining);
+                return Ok(true);
+            }
+            self.update(array.as_ref())?;
+            remaining -= array.len();
+            if !progress(array.len()) {
+                return Ok(true);
+            }
+            if remaining == 0 {
+                return Ok(reader.next().is_some());
+            }
+        }
+        Ok(false)
+    }
+
+    /// Combines with the profiler of other rows of the same column.
+    fn merge(mut self, other: Self) -> Self {
+        // Chan et al.'s parallel variance.
+        if other.count > 0 {
+            if self.count == 0 {
+                self.min = other.min;
+                self.max = other.max;
+            }
+            let count = self.count + other.count;
+            let delta = other.mean - self.mean;
+            self.mean += delta * other.count as f64 / count as f64;
+            self.m2 += other.m2 + delta * delta * (self.count * other.count) as f64 / count as f64;
+            self.count = count;
+            self.min = self.min.min(other.min);
+            self.max = self.max.max(other.max);
+        }
+        self.num_rows += other.num_rows;
+        self.null_count += other.null_count;
+        self.hll.merge(&other.hll);
+        self.reservoir.merge(other.reservoir);
+        for (length, n) in other.lengths {
+            *self.lengths.entry(length).or_default() += n;
+        }
+        // Summed Misra-Gries counts are those of the rows together.
+        for (value, n) in other.values {
+            *self.values.entry(value).or_default() += n;
+        }
+        self.values_pruned |= other.values_pruned;
+        if self.values.len() > 2 * TOP_VALUES_CAPACITY {
+            self.prune_values();
+        }
+        self
+    }
+
+    fn update(&mut self, array: &dyn Array) -> Result<()> {
+        self.num_rows += array.len();
+        self.null_count += array.null_count();
+
+        let is_string = matches!(
+            array.data_type(),
+            DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View
+        );
+        let options = FormatOptions::default();
+        let formatter = ArrayFormatter::try_new(array, &options)?;
+        for i in (0..array.len()).filter(|&i| array.is_valid(i)) {
+            let value = formatter.value(i).to_string();
+            self.hll.insert(&value);
+            if is_string {
+                *self.lengths.entry(value.chars().count()).or_default() += 1;
+            }
+            *self.values.entry(value).or_default() += 1;
+        }
+        if self.values.len() > 2 * TOP_VALUES_CAPACITY {
+            self.prune_values();
+        }
+
+        if array.data_type().is_numeric() {
+            let floats = cast(array, &DataType::Float64)?;
+            for x in floats.as_primitive::<Float64Type>().iter().flatten() {
+                if x.is_nan() {
+                    continue;
+                }
+                if self.count == 0 {
+                    self.min = x;
+                    self.max = x;
+                }
+                self.min = self.min.min(x);
+                self.max = self.max.max(x);
+                self.count += 1;
+                let delta = x - self.mean;
+                self.mean += delta / self.count as f64;
+                self.m2 += delta * (x - self.mean);
+                self.reservoir.insert(x);
+            }
+        }
+        Ok(())
+    }
+
+    /// Keeps the counts of the most frequent values, in the manner of the
+    /// Misra-Gries algorithm: every count is lowered by that of the value
+    /// ranked [`TOP_VALUES_CAPACITY`], dropping the values it reaches zero for.
+    fn prune_values(&mut self) {
+        let mut counts: Vec<u64> = self.values.values().copied().collect();
+        let (_, &mut threshold, _) =
+            counts.select_nth_unstable_by(TOP_VALUES_CAPACITY, |a, b| b.cmp(a));
+        self.values.retain(|_, count| {
+            *count = count.saturating_sub(threshold);
+            *count > 0
+        });
+        self.values_pruned = true;
+    }
+
+    fn finish(self, column: String, data_type: DataType, sampled: bool) -> ColumnProfile {
+        let numeric = (self.count > 0).then(|| {
+            let mut sample = self.reservoir.values;
+            sample.sort_by(f64::total_cmp);
+            let quantiles = QUANTILES
+                .iter()
+                .map(|q| sample[((sample.len() - 1) as f64 * q).round() as usize])
+                .collect();
+
+            let mut histogram = vec![0; HISTOGRAM_BINS];
+            let width = (self.max - self.min) / HISTOGRAM_BINS as f64;
+            for x in sample.iter() {
+                let bin = if width > 0.0 {
+                    ((x - self.min) / width) as usize
+                } else {
+                    0
+                };
+                histogram[bin.min(HISTOGRAM_BINS - 1)] += 1;
+            }
+            // Scale the sample counts up to the whole column.
+            let scale = self.count as f64 / sample.len() as f64;
+            for count in histogram.iter_mut() {
+                *count = (*count as f64 * scale).round() as u64;
+            }
+
+            NumericProfile {
+                min: self.min,
+                max: self.max,
+                mean: self.mean,
+                stddev: (self.m2 / self.count as f64).sqrt(),
+                quantiles,
+                histogram,
+            }
+        });
+
+        let lengths = match (
+            self.lengths.first_key_value(),
+            self.lengths.last_key_value(),
+        ) {
+            (Some((&min, _)), Some((&max, _))) => {
+                let total: u64 = self.lengths.values().sum();
+                let sum: u64 = self.lengths.iter().map(|(&l, &n)| l as u64 * n).sum();
+                let counts = (min..=max)
+                    .map(|l| self.lengths.get(&l).copied().unwrap_or(0))
+                    .collect();
+                Some(LengthProfile {
+                    min,
+                    max,
+                    mean: sum as f64 / total as f64,
+                    counts,
+                })
+            }
+            _ => None,
+        };
+
+        let mut top_values: Vec<_> = self.values.into_iter().collect();
+        top_values.sort_by(|(a, m), (b, n)| n.cmp(m).then_with(|| a.cmp(b)));
+        top_values.truncate(TOP_VALUES);
+
+        ColumnProfile {
+            column,
+            data_type,
+            num_rows: self.num_rows,
+            null_count: self.null_count,
+            distinct: self.hll.estimate(),
+            numeric,
+            lengths,
+            top_values,
+            top_values_approximate: self.values_pruned,
+            sampled,
+        }
+    }
+}
+
+/// Bits of the hash that pick a register.
+const HLL_PRECISION: u32 = 12;
+
+/// Distinct count estimator, see "HyperLogLog: the analysis of a near-optimal
+/// cardinality estimation algorithm" (Flajolet et al., 2007).
+struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self {
+            registers: vec![0; 1 << HLL_PRECISION],
+        }
+    }
+}
+
+impl HyperLogLog {
+    fn insert(&mut self, value: &str) {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        let hash = hasher.finish();
+        let index = (hash >> (64 - HLL_PRECISION)) as usize;
+        let rank = ((hash << HLL_PRECISION) | (1 << (HLL_PRECISION - 1))).leading_zeros() + 1;
+        self.registers[index] = self.registers[index].max(rank as u8);
+    }
+
+    fn merge(&mut self, other: &Self) {
+        for (r, o) in self.registers.iter_mut().zip(&other.registers) {
+            *r = (*r).max(*o);
+        }
+    }
+
+    fn estimate(&self) -> u64 {
+        let m = self.registers.len() as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self.registers.iter().map(|&r| 2f64.powi(-(r as i32))).sum();
+        let estimate = alpha * m * m / sum;
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+        if estimate <= 2.5 * m && zeros > 0 {
+            // Linear counting is more accurate for small cardinalities.
+            (m * (m / zeros as f64).ln()).round() as u64
+        } else {
+            estimate.round() as u64
+        }
+    }
+}
+
+/// Uniform sample of a stream of values (Vitter's algorithm R).
+struct Reservoir {
+    values: Vec<f64>,
+    seen: u64,
+    rng: u64,
+}
+
+impl Default for Reservoir {
+    fn default() -> Self {
+        Self {
+            values: vec![],
+            seen: 0,
+            rng: 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+}
+
+impl Reservoir {
+    fn insert(&mut self, x: f64) {
+        self.seen += 1;
+        if self.values.len() < RESERVOIR_SIZE {
+            self.values.push(x);
+            return;
+        }
+        let j = self.next_random() % self.seen;
+        if (j as usize) < RESERVOIR_SIZE {
+            self.values[j as usize] = x;
+        }
+    }
+
+    /// Combines with the sample of another stream, each keeping a share of
+    /// the sample in proportion to the values it has seen.
+    fn merge(&mut self, mut other: Self) {
+        let seen = self.seen + other.seen;
+        if self.values.len() + other.values.len() > RESERVOIR_SIZE {
+            let share = (RESERVOIR_SIZE as f64 * self.seen as f64 / seen as f64).round() as usize;
+            let share = share
+                .max(RESERVOIR_SIZE.saturating_sub(other.values.len()))
+                .min(self.values.len());
+            self.keep_random(share);
+            other.keep_random(RESERVOIR_SIZE - share);
+        }
+        self.values.append(&mut other.values);
+        self.seen = seen;
+    }
+
+    /// Keeps `n` of the values, picked at random.
+    fn keep_random(&mut self, n: usize) {
+        // The first `n` steps of a Fisher-Yates shuffle.
+        for i in 0..n.min(self.values.len()) {
+            let j = i + (self.next_random() % (self.values.len() - i) as u64) as usize;
+            self.values.swap(i, j);
+        }
+        self.values.truncate(n);
+    }
+
+    fn next_random(&mut self) -> u64 {
+        // xorshift64, the sample only needs to be unbiased, not unpredictable.
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        self.rng
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::{ArrayRef, Int64Array, RecordBatch, RecordBatchIterator, StringArray};
+
+    use super::*;
+
+    fn reader(batches: Vec<RecordBatch>) -> impl RecordBatchReader {
+        let schema = batches[0].schema();
+        RecordBatchIterator::new(batches.into_iter().map(Ok), schema)
+    }
+
+    fn ints(values: impl IntoIterator<Item = Option<i64>>) -> RecordBatch {
+        let array = Int64Array::from_iter(values);
+        RecordBatch::try_from_iter([("x", Arc::new(array) as ArrayRef)]).unwrap()
+    }
+
+    #[test]
+    fn estimates_distinct_values() {
+        for n in [0, 1, 100, 10_000, 1_000_000] {
+            let mut hll = HyperLogLog::default();
+            for i in 0..n {
+                hll.insert(&i.to_string());
+                // Repeats don't count.
+                hll.insert(&i.to_string());
+            }
+            let error = (hll.estimate() as f64 - n as f64).abs() / (n as f64).max(1.0);
+            assert!(error < 0.05, "{} for {n}", hll.estimate());
+        }
+    }
+
+    #[test]
+    fn samples_uniformly() {
+        let mut reservoir = Reservoir::default();
+        let n = 10 * RESERVOIR_SIZE as u64;
+        for i in 0..n {
+            reservoir.insert(i as f64);
+        }
+        assert_eq!(reservoir.seen, n);
+        assert_eq!(reservoir.values.len(), RESERVOIR_SIZE);
+        // As many values from each half of the stream.
+        let first_half = reservoir
+            .values
+            .iter()
+            .filter(|&&x| x < n as f64 / 2.0)
+            .count();
+        let share = first_half as f64 / RESERVOIR_SIZE as f64;
+        assert!((share - 0.5).abs() < 0.01, "{share}");
+    }
+
+    #[test]
+    fn profiles_numbers() {
+        let batches = vec![
+            ints([Some(1), Some(2), None]),
+            ints([Some(3), Some(3), Some(4)]),
+        ];
+        let profile = ColumnProfile::compute(reader(batches), "x", None, |_| true).unwrap();
+        assert_eq!(profile.num_rows, 6);
+        assert_eq!(profile.null_count, 1);
+        assert_eq!(profile.distinct, 4);
+        assert!(!profile.sampled);
+        assert_eq!(profile.top_values[0], ("3".to_string(), 2));
+        let numeric = profile.numeric.unwrap();
+        assert_eq!((numeric.min, numeric.max, numeric.mean), (1.0, 4.0, 2.6));
+        assert_eq!(numeric.quantiles[2], 3.0);
+        assert_eq!(numeric.histogram.iter().sum::<u64>(), 5);
+        assert!(profile.lengths.is_none());
+    }
+
+    #[test]
+    fn profiles_strings() {
+        let array = StringArray::from(vec![Some("a"), Some("abc"), Some("abc"), None]);
+        let batch = RecordBatch::try_from_iter([("s", Arc::new(array) as ArrayRef)]).unwrap();
+        let profile = ColumnProfile::compute(reader(vec![batch]), "s", None, |_| true).unwrap();
+        assert!(profile.numeric.is_none());
+        let lengths = profile.lengths.unwrap();
+        assert_eq!((lengths.min, lengths.max), (1, 3));
+        assert_eq!(lengths.counts, [1, 0, 2]);
+        assert_eq!(profile.top_values[0], ("abc".to_string(), 2));
+
+        let error = ColumnProfile::compute(reader(vec![ints([Some(1)])]), "s", None, |_| true);
+        assert!(matches!(error, Err(Error::UnknownColumn(c)) if c == "s"));
+    }
+
+    #[test]
+    fn stops_early() {
+        let batches = || {
+            (0..10)
+                .map(|i| ints((0..100).map(|j| Some(i * 100 + j))))
+                .collect()
+        };
+
+        let profile = ColumnProfile::compute(reader(batches()), "x", Some(250), |_| true).unwrap();
+        assert_eq!(profile.num_rows, 250);
+        assert!(profile.sampled);
+        let profile = ColumnProfile::compute(reader(batches()), "x", Some(1000), |_| true).unwrap();
+        assert!(!profile.sampled);
+
+        let mut reported = vec![];
+        let profile = ColumnProfile::compute(reader(batches()), "x", None, |rows| {
+            reported.push(rows);
+            rows < 300
+        })
+        .unwrap();
+        assert_eq!(reported, [100, 200, 300]);
+        assert_eq!(profile.num_rows, 300);
+    }
+
+    #[test]
+    fn keeps_the_most_frequent_values() {
+        // One frequent value among many more distinct ones than are counted.
+        let values = (0..10 * TOP_VALUES_CAPACITY as i64).flat_map(|i| [Some(-1), Some(i)]);
+        let profile = ColumnProfile::compute(reader(vec![ints(values)]), "x", None, |_| true);
+        let profile = profile.unwrap();
+        assert!(profile.top_values_approximate);
+        assert_eq!(profile.top_values[0].0, "-1");
+    }
+
+    #[test]
+    fn merges_distinct_estimates() {
+        let (mut a, mut b) = (HyperLogLog::default(), HyperLogLog::default());
+        for i in 0..60_000 {
+            a.insert(&i.to_string());
+        }
+        for i in 40_000..100_000 {
+            b.insert(&i.to_string());
+        }
+        let estimate = a.estimate();
+        a.merge(&HyperLogLog::default());
+        assert_eq!(a.estimate(), estimate);
+        // Values in both count once.
+        a.merge(&b);
+        let error = (a.estimate() as f64 - 100_000.0).abs() / 100_000.0;
+        assert!(error < 0.05, "{}", a.estimate());
+    }
+
+    #[test]
+    fn merges_samples() {
+        // Small samples are kept whole.
+        let (mut a, mut b) = (Reservoir::default(), Reservoir::default());
+        a.insert(1.0);
+        b.insert(2.0);
+        a.merge(b);
+        assert_eq!((a.values, a.seen), (vec![1.0, 2.0], 2));
+
+        // Full ones in proportion to the values each has seen.
+        let (mut a, mut b) = (Reservoir::default(), Reservoir::default());
+        for _ in 0..3 * RESERVOIR_SIZE {
+            a.insert(-1.0);
+        }
+        for _ in 0..RESERVOIR_SIZE {
+            b.insert(1.0);
+        }
+        a.merge(b);
+        assert_eq!(a.seen, 4 * RESERVOIR_SIZE as u64);
+        assert_eq!(a.values.len(), RESERVOIR_SIZE);
+        let from_a = a.values.iter().filter(|&&x| x < 0.0).count();
+        assert_eq!(from_a, 3 * RESERVOIR_SIZE / 4);
+    }
+
+    #[test]
+    fn merges_profilers() {
+        let profile = |values: &[Option<i64>]| {
+            let mut profiler = Profiler::default();
+            let batch = ints(values.iter().copied());
+            profiler.update(batch.column(0).as_ref()).unwrap();
+            profiler
+        };
+        let values = [Some(5), None, Some(7), Some(5), Some(12), None, Some(9)];
+        let whole = profile(&values);
+        let merged = profile(&values[..3]).merge(profile(&values[3..]));
+        assert_eq!(merged.num_rows, whole.num_rows);
+        assert_eq!(merged.null_count, whole.null_count);
+        assert_eq!(merged.count, whole.count);
+        assert!((merged.mean - whole.mean).abs() < 1e-9);
+        assert!((merged.m2 - whole.m2).abs() < 1e-9);
+        assert_eq!((merged.min, merged.max), (5.0, 12.0));
+        assert_eq!(merged.values, whole.values);
+        assert_eq!(merged.reservoir.seen, whole.reservoir.seen);
+
+        // Rows without values leave the minimum and maximum be.
+        let merged = profile(&[None]).merge(profile(&values[..3]));
+        assert_eq!((merged.min, merged.max), (5.0, 7.0));
+        let merged = profile(&values[..3]).merge(profile(&[None]));
+        assert_eq!((merged.min, merged.max), (5.0, 7.0));
+    }
+}
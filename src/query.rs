@@ -0,0 +1,232 @@
+use arrow::{
+    array::{RecordBatch, RecordBatchReader},
+    datatypes::{DataType, SchemaRef},
+    error::ArrowError,
+};
+use datafusion::{
+    catalog::streaming::StreamingTable,
+    datasource::file_format::options::ArrowReadOptions,
+    error::DataFusionError,
+    execution::{SendableRecordBatchStream, TaskContext},
+    physical_plan::{stream::RecordBatchStreamAdapter, streaming::PartitionStream},
+    prelude::{CsvReadOptions, NdJsonReadOptions, ParquetReadOptions, SessionContext},
+    sql::TableReference,
+};
+use futures::StreamExt;
+use tokio::{runtime::Runtime, sync::mpsc};
+
+use crate::{
+    delta::{DeltaTable, TimeTravel},
+    error::{Error, Result},
+    iceberg::IcebergTable,
+    partition::Partitioning,
+    source::{Format, Source},
+};
+use std::{
+    fmt::{self, Debug, Formatter},
+    iter,
+    path::Path,
+    sync::Arc,
+    thread,
+};
+
+/// Rows read at a time from sources registered with [`QueryEngine::register_source`].
+const QUERY_BATCH_SIZE: usize = 8192;
+/// Batches read ahead of the query from sources registered with
+/// [`QueryEngine::register_source`].
+const SOURCE_STREAM_BUFFER: usize = 2;
+
+/// Embedded SQL engine with the opened files registered as tables.
+pub struct QueryEngine {
+    runtime: Arc<Runtime>,
+    ctx: SessionContext,
+    /// Names of the registered tables.
+    pub tables: Vec<String>,
+}
+
+impl QueryEngine {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            runtime: Arc::new(Runtime::new()?),
+            ctx: SessionContext::new(),
+            tables: vec![],
+        })
+    }
+
+    /// Registers a file, or a hive-partitioned directory of Parquet files, as
+    /// table `name`.
+    pub fn register(&mut self, name: &str, path: &Path) -> Result<()> {
+        let path = path.canonicalize()?;
+        let mut table_path = path.to_string_lossy().to_string();
+        if DeltaTable::is_table(&path) {
+            let table = DeltaTable::open(&path, TimeTravel::Latest)?;
+            return self.register_source(name, Source::Delta(Arc::new(table)));
+        }
+        if IcebergTable::is_table(&path) {
+            let table = IcebergTable::open(&path, TimeTravel::Latest)?;
+            return self.register_source(name, Source::Iceberg(Arc::new(table)));
+        }
+        let format = if path.is_dir() {
+            Format::Parquet
+        } else {
+            Source::file(&path)?.format()
+        };
+        // Listing tables skip files without the expected extension.
+        let extension = path
+            .extension()
+            .map_or("".to_string(), |e| format!(".{}", e.to_string_lossy()));
+
+        let registered = match format {
+            Format::Parquet => {
+                let mut partition_cols = vec![];
+                if path.is_dir() {
+                    table_path.push('/');
+                    for key in Partitioning::discover(&path)?.keys {
+                        partition_cols.push((key, DataType::Utf8));
+                    }
+                }
+                let mut options =
+                    ParquetReadOptions::default().table_partition_cols(partition_cols);
+                if !path.is_dir() {
+                    options = options.file_extension(&extension);
+                }
+                self.runtime.block_on(self.ctx.register_parquet(
+                    TableReference::bare(name),
+                    table_path,
+                    options,
+                ))
+            }
+            Format::Csv { delimiter } => {
+                let options = CsvReadOptions::new()
+                    .delimiter(delimiter)
+                    .file_extension(&extension);
+                self.runtime.block_on(self.ctx.register_csv(
+                    TableReference::bare(name),
+                    &table_path,
+                    options,
+                ))
+            }
+            Format::Json => {
+                let options = NdJsonReadOptions::default().file_extension(&extension);
+                self.runtime.block_on(self.ctx.register_json(
+                    TableReference::bare(name),
+                    &table_path,
+                    options,
+                ))
+            }
+            Format::ArrowFile => {
+                let options = ArrowReadOptions {
+                    file_extension: &extension,
+                    ..Default::default()
+                };
+                // Unlike the other formats, takes the name as a string to parse.
+                self.runtime.block_on(self.ctx.register_arrow(
+                    &TableReference::bare(name).to_quoted_string(),
+                    &table_path,
+                    options,
+                ))
+            }
+            Format::ArrowStream => return Err(Error::UnsupportedQueryFormat(format.name())),
+        };
+        registered?;
+        self.tables.push(name.to_string());
+        Ok(())
+    }
+
+    /// Registers everything `source` reads as table `name`.
+    ///
+    /// Unlike [`Self::register`], every query reads the whole source, as
+    /// nothing gets pushed down into the reader.
+    pub fn register_source(&mut self, name: &str, source: Source) -> Result<()> {
+        let (_, reader) = source.reader(None, 1)?;
+        let stream = SourceStream {
+            schema: reader.schema(),
+            source,
+        };
+        let table = StreamingTable::try_new(Arc::clone(&stream.schema), vec![Arc::new(stream)])?;
+        self.ctx
+            .register_table(TableReference::bare(name), Arc::new(table))?;
+        self.tables.push(name.to_string());
+        Ok(())
+    }
+
+    /// Plans `sql` and starts executing it.
+    pub fn sql(&self, sql: &str) -> Result<QueryReader> {
+        let stream = self.runtime.block_on(async {
+            let df = self.ctx.sql(sql).await?;
+            df.execute_stream().await
+        })?;
+        Ok(QueryReader {
+            runtime: Arc::clone(&self.runtime),
+            stream,
+        })
+    }
+}
+
+/// Blocking reader over the results of a query.
+pub struct QueryReader {
+    runtime: Arc<Runtime>,
+    stream: SendableRecordBatchStream,
+}
+
+impl Iterator for QueryReader {
+    type Item = std::result::Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runtime
+            .block_on(self.stream.next())
+            .map(|batch| batch.map_err(ArrowError::from))
+    }
+}
+
+impl RecordBatchReader for QueryReader {
+    fn schema(&self) -> SchemaRef {
+        self.stream.schema()
+    }
+}
+
+/// Partition of a [`StreamingTable`] reading a [`Source`] from the start.
+struct SourceStream {
+    schema: SchemaRef,
+    source: Source,
+}
+
+impl Debug for SourceStream {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SourceStream")
+            .field("schema", &self.schema)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartitionStream for SourceStream {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    /// Sources are read on a thread of their own, as their readers block,
+    /// remote ones on a runtime that can't be entered from the engine's.
+    fn execute(&self, _ctx: Arc<TaskContext>) -> SendableRecordBatchStream {
+        let (sender, receiver) = mpsc::channel(SOURCE_STREAM_BUFFER);
+        let source = self.source.clone();
+        thread::spawn(move || {
+            let batches: Box<dyn Iterator<Item = _>> = match source.reader(None, QUERY_BATCH_SIZE) {
+                Ok((_, reader)) => Box::new(reader.map(|b| b.map_err(DataFusionError::from))),
+                Err(e) => Box::new(iter::once(Err(DataFusionError::External(Box::new(e))))),
+            };
+            for batch in batches {
+                // The query was dropped.
+                if sender.blocking_send(batch).is_err() {
+                    break;
+                }
+            }
+        });
+        let batches = futures::stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|batch| (batch, receiver))
+        });
+        Box::pin(RecordBatchStreamAdapter::new(
+            Arc::clone(&self.schema),
+            batches,
+        ))
+    }
+}
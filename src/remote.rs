@@ -0,0 +1,273 @@
+use arrow::{
+    array::{RecordBatch, RecordBatchReader},
+    datatypes::SchemaRef,
+    error::ArrowError,
+};
+use futures::StreamExt;
+use object_store::{
+    aws::AmazonS3Builder, azure::MicrosoftAzureBuilder, gcp::GoogleCloudStorageBuilder,
+    http::HttpBuilder, ClientOptions, ObjectMeta, ObjectStore, ObjectStoreScheme,
+};
+use parquet::{
+    arrow::{
+        async_reader::{ParquetObjectReader, ParquetRecordBatchStream},
+        ParquetRecordBatchStreamBuilder,
+    },
+    file::metadata::ParquetMetaData,
+};
+use tokio::runtime::Runtime;
+use url::{Position, Url};
+
+use crate::{error::Result, source::projection};
+use std::sync::Arc;
+
+/// A Parquet file in object storage (S3-compatible, GCS, Azure or HTTP).
+///
+/// Only the footer and the column chunks that are read get fetched, using
+/// range requests.
+#[derive(Clone)]
+pub struct RemoteFile {
+    runtime: Arc<Runtime>,
+    store: Arc<dyn ObjectStore>,
+    meta: ObjectMeta,
+}
+
+impl RemoteFile {
+    /// Whether `s` is the URL of a supported object store, e.g. `s3://bucket/key`.
+    pub fn is_url(s: &str) -> bool {
+        Url::parse(s).is_ok_and(|url| ObjectStoreScheme::parse(&url).is_ok())
+    }
+
+    /// Connects to the store of `url`, configured from the variables its
+    /// builder reads from the environment (e.g. `AWS_ENDPOINT` and
+    /// `AWS_ALLOW_HTTP` for MinIO).
+    pub fn open(url: &str) -> Result<Self> {
+        let url = Url::parse(url)?;
+        let (scheme, path) = ObjectStoreScheme::parse(&url).map_err(object_store::Error::from)?;
+        let store: Box<dyn ObjectStore> = match scheme {
+            ObjectStoreScheme::AmazonS3 => {
+                Box::new(AmazonS3Builder::from_env().with_url(url).build()?)
+            }
+            ObjectStoreScheme::GoogleCloudStorage => Box::new(
+                GoogleCloudStorageBuilder::from_env()
+                    .with_url(url)
+                    .build()?,
+            ),
+            ObjectStoreScheme::MicrosoftAzure => {
+                Box::new(MicrosoftAzureBuilder::from_env().with_url(url).build()?)
+            }
+            ObjectStoreScheme::Http => {
+                // Asking for a plain HTTP URL is consent enough.
+                let options = ClientOptions::new().with_allow_http(true);
+                Box::new(
+                    HttpBuilder::new()
+                        .with_url(&url[..Position::BeforePath])
+                        .with_client_options(options)
+                        .build()?,
+                )
+            }
+            _ => object_store::parse_url(&url)?.0,
+        };
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let meta = runtime.block_on(store.head(&path))?;
+
+        Ok(Self {
+            runtime: Arc::new(runtime),
+            store: Arc::from(store),
+            meta,
+        })
+    }
+
+    pub fn name(&self) -> Option<String> {
+        let filename = self.meta.location.filename()?;
+        let stem = filename.rsplit_once('.').map_or(filename, |(stem, _)| stem);
+        Some(stem.to_string())
+    }
+
+    /// Fetches the footer and prepares a reader over `columns`, or the whole
+    /// file when `None`.
+    pub fn reader(
+        &self,
+        columns: Option<&[String]>,
+        batch_size: usize,
+    ) -> Result<(Arc<ParquetMetaData>, RemoteReader)> {
+        let object_reader =
+            ParquetObjectReader::new(Arc::clone(&self.store), self.meta.location.clone())
+                .with_file_size(self.meta.size);
+        let builder = self
+            .runtime
+            .block_on(ParquetRecordBatchStreamBuilder::new(object_reader))?;
+        let metadata = Arc::clone(builder.metadata());
+        let mask = projection(builder.schema(), builder.parquet_schema(), columns);
+        let stream = builder
+            .with_projection(mask)
+            .with_batch_size(batch_size)
+            .build()?;
+
+        Ok((
+            metadata,
+            RemoteReader {
+                runtime: Arc::clone(&self.runtime),
+                stream,
+            },
+        ))
+    }
+}
+
+/// Blocking reader over the async record batch stream of a [`RemoteFile`].
+pub struct RemoteReader {
+    runtime: Arc<Runtime>,
+    stream: ParquetRecordBatchStream<ParquetObjectReader>,
+}
+
+impl Iterator for RemoteReader {
+    type Item = std::result::Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runtime
+            .block_on(self.stream.next())
+            .map(|batch| batch.map_err(ArrowError::from))
+    }
+}
+
+impl RecordBatchReader for RemoteReader {
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(self.stream.schema())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    use arrow::{
+        array::{ArrayRef, Int64Array},
+        util::pretty::pretty_format_batches,
+    };
+    use parquet::arrow::ArrowWriter;
+
+    use super::*;
+    use crate::{app::Viewer, source::Source};
+
+    /// Serves `bytes` at `/data.parquet` from a local HTTP server, honouring
+    /// range requests as object stores do.
+    fn serve(bytes: Vec<u8>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut writer = stream;
+                loop {
+                    let mut request = String::new();
+                    if reader.read_line(&mut request).unwrap_or(0) == 0 {
+                        break;
+                    }
+                    let mut range = None;
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).unwrap();
+                        if line.trim().is_empty() {
+                            break;
+                        }
+                        let (name, value) = line.split_once(':').unwrap();
+                        if name.eq_ignore_ascii_case("range") {
+                            let (start, end) =
+                                value.trim()["bytes=".len()..].split_once('-').unwrap();
+                            let start: usize = start.parse().unwrap();
+                            let end = end.parse().map_or(bytes.len(), |end: usize| end + 1);
+                            range = Some(start..end.min(bytes.len()));
+                        }
+                    }
+                    let head = request.starts_with("HEAD");
+                    let found = request.split(' ').nth(1) == Some("/data.parquet");
+                    let (status, body, content_range) = match (found, range) {
+                        (false, _) => ("404 Not Found", &[][..], None),
+                        (true, Some(range)) => {
+                            let content_range =
+                                format!("{}-{}/{}", range.start, range.end - 1, bytes.len());
+                            ("206 Partial Content", &bytes[range], Some(content_range))
+                        }
+                        (true, None) => ("200 OK", &bytes[..], None),
+                    };
+                    write!(
+                        writer,
+                        "HTTP/1.1 {status}\r\nContent-Length: {}\r\n",
+                        body.len()
+                    )
+                    .unwrap();
+                    if let Some(content_range) = content_range {
+                        write!(writer, "Content-Range: bytes {content_range}\r\n").unwrap();
+                    }
+                    writer.write_all(b"\r\n").unwrap();
+                    if !head {
+                        writer.write_all(body).unwrap();
+                    }
+                }
+            }
+        });
+        format!("http://{address}/data.parquet")
+    }
+
+    fn parquet(ids: Vec<i64>) -> Vec<u8> {
+        let batch =
+            RecordBatch::try_from_iter([("id", Arc::new(Int64Array::from(ids)) as ArrayRef)])
+                .unwrap();
+        let mut bytes = vec![];
+        let mut writer = ArrowWriter::try_new(&mut bytes, batch.schema(), None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+        bytes
+    }
+
+    #[test]
+    fn reads_over_http() {
+        let url = serve(parquet((0..1000).collect()));
+        assert!(RemoteFile::is_url(&url));
+
+        let remote = RemoteFile::open(&url).unwrap();
+        assert_eq!(remote.name().as_deref(), Some("data"));
+        let (metadata, reader) = remote.reader(None, 300).unwrap();
+        assert_eq!(metadata.file_metadata().num_rows(), 1000);
+        let batches = reader.collect::<std::result::Result<Vec<_>, _>>().unwrap();
+        assert_eq!(batches.len(), 4);
+        let ids: Vec<_> = batches
+            .iter()
+            .flat_map(|b| {
+                let ids = b.column(0).as_any().downcast_ref::<Int64Array>().unwrap();
+                ids.values().to_vec()
+            })
+            .collect();
+        assert_eq!(ids, (0..1000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn queries_over_http() {
+        let url = serve(parquet((0..1000).collect()));
+        let remote = RemoteFile::open(&url).unwrap();
+        let name = remote.name();
+        let viewer = Viewer::new(Source::Remote(remote), name).unwrap();
+        let engine = crate::query_engine(&viewer).unwrap().unwrap();
+        let batches = engine
+            .sql("SELECT count(*) AS n, max(id) AS last FROM data")
+            .unwrap()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .unwrap();
+        let table = pretty_format_batches(&batches).unwrap().to_string();
+        assert!(table.contains("| 1000 | 999  |"), "{table}");
+    }
+
+    #[test]
+    fn fails_on_missing_files() {
+        let url = serve(parquet(vec![1]));
+        let url = url.replace("data.parquet", "missing.parquet");
+        assert!(RemoteFile::open(&url).is_err());
+    }
+}
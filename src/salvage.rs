@@ -0,0 +1,246 @@
+use arrow::{
+    array::{new_null_array, RecordBatch, RecordBatchOptions, RecordBatchReader},
+    datatypes::{Schema, SchemaRef},
+    error::ArrowError,
+};
+use parquet::{
+    arrow::{
+        arrow_reader::{
+            ArrowReaderMetadata, ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder,
+        },
+        ProjectionMask,
+    },
+    file::metadata::ParquetMetaData,
+};
+
+use crate::{
+    error::{catch_panic, Error, Result},
+    source::check_footer,
+};
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Rows decoded at a time while looking for damaged column chunks.
+const PROBE_BATCH_SIZE: usize = 8192;
+
+/// A column chunk that couldn't be decoded.
+#[derive(Clone, Debug)]
+pub struct DamagedChunk {
+    pub row_group: usize,
+    /// Top-level column, which is read as a whole.
+    pub column: String,
+    pub error: String,
+}
+
+/// A damaged Parquet file, read chunk by chunk so that every row group and
+/// column that can still be decoded is shown, and the others as nulls.
+///
+/// Only the footer has to be intact, as without it the row groups can't be
+/// found.
+#[derive(Clone, Debug)]
+pub struct Salvage {
+    pub path: PathBuf,
+    pub damaged: Vec<DamagedChunk>,
+    metadata: ArrowReaderMetadata,
+    /// Schema of the file, with the damaged columns made nullable.
+    schema: SchemaRef,
+}
+
+impl Salvage {
+    /// Reads the footer, then decodes every column chunk to find the damaged
+    /// ones.
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path)?;
+        check_footer(&path.display().to_string(), &file)?;
+        let metadata = ArrowReaderMetadata::load(&file, Default::default())?;
+        let mut salvage = Self {
+            path: path.to_path_buf(),
+            damaged: vec![],
+            schema: Arc::clone(metadata.schema()),
+            metadata,
+        };
+
+        // Corrupt pages can make the parquet crate panic rather than fail,
+        // which is no different from an error here.
+        for row_group in 0..salvage.metadata.metadata().num_row_groups() {
+            for (i, field) in salvage.schema.fields().iter().enumerate() {
+                let decode = || -> Result<()> {
+                    for batch in salvage.chunk_reader(row_group, i, PROBE_BATCH_SIZE)? {
+                        batch?;
+                    }
+                    Ok(())
+                };
+                let error = match catch_panic(decode) {
+                    Ok(Ok(())) => continue,
+                    // Without the advice to salvage the file.
+                    Ok(Err(Error::PageCrcMismatch)) => {
+                        "a page doesn't match its CRC checksum".to_string()
+                    }
+                    Ok(Err(e)) => e.to_string(),
+                    Err(_) => "the decoder panicked".to_string(),
+                };
+                salvage.damaged.push(DamagedChunk {
+                    row_group,
+                    column: field.name().clone(),
+                    error,
+                });
+            }
+        }
+
+        let fields = salvage.schema.fields().iter().map(|f| {
+            let damaged = salvage.damaged.iter().any(|c| &c.column == f.name());
+            f.as_ref().clone().with_nullable(f.is_nullable() || damaged)
+        });
+        salvage.schema = Arc::new(Schema::new_with_metadata(
+            fields.collect::<Vec<_>>(),
+            salvage.schema.metadata().clone(),
+        ));
+        Ok(salvage)
+    }
+
+    pub fn metadata(&self) -> &Arc<ParquetMetaData> {
+        self.metadata.metadata()
+    }
+
+    /// The reason the chunk of the top-level `column` in `row_group` couldn't
+    /// be decoded, if it couldn't.
+    pub fn damage(&self, row_group: usize, column: &str) -> Option<&DamagedChunk> {
+        self.damaged
+            .iter()
+            .find(|c| c.row_group == row_group && c.column == column)
+    }
+
+    /// Prepares a reader over `columns`, or every column when `None`, with
+    /// nulls in place of the damaged chunks.
+    pub fn reader(&self, columns: Option<&[String]>, batch_size: usize) -> Result<SalvageReader> {
+        let indices: Vec<_> = self
+            .schema
+            .fields()
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| columns.is_none_or(|c| c.contains(f.name())))
+            .map(|(i, _)| i)
+            .collect();
+        Ok(SalvageReader {
+            schema: Arc::new(self.schema.project(&indices)?),
+            salvage: self.clone(),
+            indices,
+            row_group: 0,
+            current: vec![],
+            remaining: 0,
+            batch_size,
+        })
+    }
+
+    /// Reads the `column`-th top-level column of `row_group` alone.
+    fn chunk_reader(
+        &self,
+        row_group: usize,
+        column: usize,
+        batch_size: usize,
+    ) -> Result<ParquetRecordBatchReader> {
+        let mask = ProjectionMask::roots(
+            self.metadata.metadata().file_metadata().schema_descr(),
+            [column],
+        );
+        let reader = ParquetRecordBatchReaderBuilder::new_with_metadata(
+            File::open(&self.path)?,
+            self.metadata.clone(),
+        )
+        .with_row_groups(vec![row_group])
+        .with_projection(mask)
+        .with_batch_size(batch_size)
+        .build()?;
+        Ok(reader)
+    }
+}
+
+/// Reads a [`Salvage`] one row group at a time, each column with its own
+/// reader so that a damaged chunk doesn't take the others down with it.
+pub struct SalvageReader {
+    schema: SchemaRef,
+    salvage: Salvage,
+    /// Top-level columns to read.
+    indices: Vec<usize>,
+    /// Next row group to open.
+    row_group: usize,
+    /// Readers of the columns of the current row group, `None` for the
+    /// damaged ones.
+    current: Vec<Option<ParquetRecordBatchReader>>,
+    /// Rows of the current row group left to read.
+    remaining: usize,
+    batch_size: usize,
+}
+
+impl SalvageReader {
+    fn open_next(&mut self) -> Option<Result<()>> {
+        let metadata = Arc::clone(self.salvage.metadata());
+        let row_group = metadata.row_groups().get(self.row_group)?;
+        let mut open = || {
+            self.current = self
+                .indices
+                .iter()
+                .map(|&i| {
+                    let name = self.salvage.schema.field(i).name();
+                    match self.salvage.damage(self.row_group, name) {
+                        Some(_) => Ok(None),
+                        None => Ok(Some(self.salvage.chunk_reader(
+                            self.row_group,
+                            i,
+                            self.batch_size,
+                        )?)),
+                    }
+                })
+                .collect::<Result<_>>()?;
+            self.remaining = row_group.num_rows() as usize;
+            self.row_group += 1;
+            Ok(())
+        };
+        Some(open())
+    }
+
+    fn next_batch(&mut self) -> std::result::Result<RecordBatch, ArrowError> {
+        let num_rows = self.remaining.min(self.batch_size);
+        self.remaining -= num_rows;
+        let columns = self
+            .current
+            .iter_mut()
+            .zip(self.schema.fields())
+            .map(|(reader, field)| match reader {
+                Some(reader) => match reader.next() {
+                    Some(batch) => Ok(Arc::clone(batch?.column(0))),
+                    None => Err(ArrowError::ParquetError(format!(
+                        "column `{}` ended before its row group",
+                        field.name()
+                    ))),
+                },
+                None => Ok(new_null_array(field.data_type(), num_rows)),
+            })
+            .collect::<std::result::Result<_, _>>()?;
+        // The row count is needed when no column is read.
+        let options = RecordBatchOptions::new().with_row_count(Some(num_rows));
+        RecordBatch::try_new_with_options(Arc::clone(&self.schema), columns, &options)
+    }
+}
+
+impl Iterator for SalvageReader {
+    type Item = std::result::Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining == 0 {
+            if let Err(e) = self.open_next()? {
+                return Some(Err(ArrowError::ExternalError(Box::new(e))));
+            }
+        }
+        Some(self.next_batch())
+    }
+}
+
+impl RecordBatchReader for SalvageReader {
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+}
@@ -0,0 +1,759 @@
+use arrow::{
+    array::RecordBatchReader,
+    csv,
+    datatypes::Schema,
+    ipc::reader::{FileReader, StreamReader},
+    json,
+};
+use bytes::Bytes;
+use parquet::{
+    arrow::{
+        arrow_reader::{
+            ArrowReaderMetadata, ArrowReaderOptions, ParquetRecordBatchReader,
+            ParquetRecordBatchReaderBuilder, RowSelection, RowSelector,
+        },
+        ProjectionMask,
+    },
+    file::{metadata::ParquetMetaData, reader::ChunkReader},
+    schema::types::SchemaDescriptor,
+};
+
+use crate::{
+    delta::DeltaTable,
+    encryption::{EncryptedFile, Encryption},
+    error::{Error, Result},
+    iceberg::IcebergTable,
+    mmap::MappedFile,
+    parallel::{row_group_readers, ParallelReader},
+    partition::Partitioning,
+    remote::RemoteFile,
+    salvage::Salvage,
+};
+use std::{
+    fs::File,
+    io::{BufReader, Cursor, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Bytes looked at to detect the format of a file.
+const HEAD_SIZE: usize = 1024;
+/// Magic at the start and end of Parquet files.
+pub(crate) const MAGIC_SIZE: usize = 4;
+/// Metadata length and magic at the end of Parquet files.
+pub(crate) const FOOTER_SIZE: usize = 8;
+/// Records read to infer the schema of CSV and JSON files.
+const INFER_SCHEMA_RECORDS: usize = 1000;
+
+/// File formats that can be viewed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    Parquet,
+    /// Comma or tab separated values, with a header.
+    Csv {
+        delimiter: u8,
+    },
+    /// Newline-delimited JSON.
+    Json,
+    ArrowFile,
+    ArrowStream,
+}
+
+impl Format {
+    /// Recognises the format from the first bytes of a file, falling back to
+    /// its extension. Unrecognised text is assumed to be CSV, unless named
+    /// `.parquet`, so that damaged Parquet files are reported as such, and
+    /// anything else Parquet.
+    pub fn detect(extension: Option<&str>, head: &[u8]) -> Self {
+        // Files with an encrypted footer start with `PARE` instead.
+        if head.starts_with(b"PAR1") || head.starts_with(b"PARE") {
+            return Self::Parquet;
+        }
+        if head.starts_with(b"ARROW1") {
+            return Self::ArrowFile;
+        }
+        // Stream messages start with a continuation marker.
+        if head.starts_with(&[0xff; 4]) {
+            return Self::ArrowStream;
+        }
+        match extension.map(str::to_ascii_lowercase).as_deref() {
+            Some("parquet" | "parq") => Self::Parquet,
+            Some("csv") => Self::Csv { delimiter: b',' },
+            Some("tsv") => Self::Csv { delimiter: b'\t' },
+            Some("json" | "jsonl" | "ndjson") => Self::Json,
+            Some("arrow" | "feather" | "ipc") => Self::ArrowFile,
+            Some("arrows") => Self::ArrowStream,
+            _ if head.trim_ascii_start().starts_with(b"{") => Self::Json,
+            // E.g. CSV piped through stdin.
+            _ if is_text(head) => {
+                let first_line = head.split(|&b| b == b'\n').next().unwrap_or_default();
+                let count = |c| first_line.iter().filter(|&&b| b == c).count();
+                let delimiter = if count(b'\t') > count(b',') {
+                    b'\t'
+                } else {
+                    b','
+                };
+                Self::Csv { delimiter }
+            }
+            _ => Self::Parquet,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Parquet => "Parquet",
+            Self::Csv { delimiter: b'\t' } => "TSV",
+            Self::Csv { .. } => "CSV",
+            Self::Json => "JSON Lines",
+            Self::ArrowFile => "Arrow IPC file",
+            Self::ArrowStream => "Arrow IPC stream",
+        }
+    }
+}
+
+/// Whether `head` looks like the start of a UTF-8 text file.
+fn is_text(head: &[u8]) -> bool {
+    match std::str::from_utf8(head) {
+        Ok(s) => !s.is_empty() && !s.contains('\0'),
+        // The head may end halfway through a character.
+        Err(e) => e.error_len().is_none() && e.valid_up_to() > 0,
+    }
+}
+
+/// Where the viewed data is read from.
+///
+/// Kept by the [`Viewer`](crate::app::Viewer) so the data can be read again
+/// from the start, e.g. to profile a column in the background.
+#[derive(Clone)]
+pub enum Source {
+    File(PathBuf, Format),
+    /// A file buffered in memory, e.g. read from stdin.
+    Bytes(Bytes, Format),
+    Partitioned(Partitioning),
+    Delta(Arc<DeltaTable>),
+    Iceberg(Arc<IcebergTable>),
+    Remote(RemoteFile),
+    /// A local Parquet file written with modular encryption.
+    Encrypted(EncryptedFile),
+    /// A damaged Parquet file, read with `--salvage`.
+    Salvaged(Arc<Salvage>),
+}
+
+/// What is known about a source before reading its rows.
+pub enum Metadata {
+    Parquet(Arc<ParquetMetaData>),
+    /// The footer of an encrypted file, with the metadata of the columns that
+    /// couldn't be decrypted left empty.
+    Encrypted(Arc<ParquetMetaData>, Encryption),
+    /// Formats without a footer, described by a few format-specific properties.
+    Other(Vec<(String, String)>),
+}
+
+impl Source {
+    /// A local file, in the format detected from its contents and extension.
+    pub fn file(path: &Path) -> Result<Self> {
+        let mut head = vec![];
+        File::open(path)?
+            .take(HEAD_SIZE as u64)
+            .read_to_end(&mut head)?;
+        let extension = path.extension().and_then(|e| e.to_str());
+        Ok(Self::File(
+            path.to_path_buf(),
+            Format::detect(extension, &head),
+        ))
+    }
+
+    /// A file held in memory, in the format detected from its contents.
+    pub fn bytes(bytes: Bytes) -> Self {
+        let format = Format::detect(None, &bytes[..bytes.len().min(HEAD_SIZE)]);
+        Self::Bytes(bytes, format)
+    }
+
+    pub fn format(&self) -> Format {
+        match self {
+            Self::File(_, format) | Self::Bytes(_, format) => *format,
+            Self::Partitioned(_)
+            | Self::Delta(_)
+            | Self::Iceberg(_)
+            | Self::Remote(_)
+            | Self::Encrypted(_)
+            | Self::Salvaged(_) => Format::Parquet,
+        }
+    }
+
+    /// Reads the metadata and prepares a reader over `columns`, or every
+    /// column when `None`.
+    ///
+    /// For a partitioned directory, the metadata is that of the first file.
+    pub fn reader(
+        &self,
+        columns: Option<&[String]>,
+        batch_size: usize,
+    ) -> Result<(Metadata, Box<dyn RecordBatchReader + Send>)> {
+        match self {
+            Self::File(path, Format::Parquet) => {
+                let name = path.display().to_string();
+                match MappedFile::open(path) {
+                    Ok(file) => {
+                        check_footer(&name, &file)?;
+                        parallel_reader(file, columns, batch_size)
+                    }
+                    // E.g. a named pipe, or a file on a network filesystem,
+                    // which aren't mapped.
+                    Err(_) => {
+                        let file = File::open(path)?;
+                        check_footer(&name, &file)?;
+                        parquet_reader(file, columns, batch_size)
+                    }
+                }
+            }
+            Self::File(path, format) => {
+                let file = File::open(path)?;
+                let size = file.metadata()?.len();
+                arrow_reader(file, size, *format, columns, batch_size)
+            }
+            Self::Bytes(bytes, format) => match format {
+                Format::Parquet => {
+                    check_footer("stdin", bytes)?;
+                    parquet_reader(bytes.clone(), columns, batch_size)
+                }
+                format => {
+                    let size = bytes.len() as u64;
+                    let cursor = Cursor::new(bytes.clone());
+                    arrow_reader(cursor, size, *format, columns, batch_size)
+                }
+            },
+            Self::Partitioned(partitioning) => {
+                partitioned_reader(partitioning, columns, batch_size)
+            }
+            Self::Delta(table) => partitioned_reader(&table.partitioning, columns, batch_size),
+            Self::Iceberg(table) => partitioned_reader(&table.partitioning, columns, batch_size),
+            Self::Remote(remote) => {
+                let (metadata, reader) = remote.reader(columns, batch_size)?;
+                Ok((Metadata::Parquet(metadata), Box::new(reader)))
+            }
+            Self::Encrypted(file) => {
+                let (metadata, encryption, reader) = file.reader(columns, batch_size)?;
+                Ok((Metadata::Encrypted(metadata, encryption), Box::new(reader)))
+            }
+            Self::Salvaged(salvage) => {
+                let metadata = Metadata::Parquet(Arc::clone(salvage.metadata()));
+                Ok((metadata, Box::new(salvage.reader(columns, batch_size)?)))
+            }
+        }
+    }
+
+    /// A reader of each row group of a local Parquet file, with its number of
+    /// rows, for whole-file scans to go through in parallel. `None` for other
+    /// sources, and files with a single row group, which are read in order.
+    pub fn row_group_readers(
+        &self,
+        columns: Option<&[String]>,
+        batch_size: usize,
+    ) -> Option<Result<Vec<(usize, ParquetRecordBatchReader)>>> {
+        let Self::File(path, Format::Parquet) = self else {
+            return None;
+        };
+        let file = MappedFile::open(path).ok()?;
+        let metadata = match ArrowReaderMetadata::load(&file, ArrowReaderOptions::default()) {
+            Ok(metadata) => metadata,
+            Err(e) => return Some(Err(e.into())),
+        };
+        // Nothing to share out between threads.
+        if metadata.metadata().num_row_groups() < 2 {
+            return None;
+        }
+        Some(row_group_readers(&file, &metadata, columns, batch_size))
+    }
+
+    /// A reader of a local Parquet file from its row `row` on, starting at
+    /// the row group it's in rather than reading the ones before. `None` for
+    /// other sources, and rows past the end.
+    pub fn reader_from(
+        &self,
+        columns: Option<&[String]>,
+        batch_size: usize,
+        row: usize,
+    ) -> Option<Result<Box<dyn RecordBatchReader + Send>>> {
+        let Self::File(path, Format::Parquet) = self else {
+            return None;
+        };
+        let file = MappedFile::open(path).ok()?;
+        let metadata = match ArrowReaderMetadata::load(&file, ArrowReaderOptions::default()) {
+            Ok(metadata) => metadata,
+            Err(e) => return Some(Err(e.into())),
+        };
+        // The row group `row` is in, and the rows before it.
+        let mut skipped = 0;
+        let row_group = metadata.metadata().row_groups().iter().position(|rg| {
+            let num_rows = rg.num_rows() as usize;
+            let found = row < skipped + num_rows;
+            if !found {
+                skipped += num_rows;
+            }
+            found
+        })?;
+        Some(reader_from(
+            file,
+            metadata,
+            columns,
+            batch_size,
+            row_group,
+            row - skipped,
+        ))
+    }
+
+    /// The local file or directory read, if any.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::File(path, _) => Some(path),
+            Self::Partitioned(partitioning) => Some(&partitioning.root),
+            Self::Delta(table) => Some(&table.root),
+            Self::Iceberg(table) => Some(&table.root),
+            Self::Encrypted(file) => Some(&file.path),
+            Self::Salvaged(salvage) => Some(&salvage.path),
+            Self::Bytes(..) | Self::Remote(_) => None,
+        }
+    }
+
+    pub fn partitions(&self) -> Option<&Partitioning> {
+        match self {
+            Self::Partitioned(partitioning) => Some(partitioning),
+            Self::Delta(table) => Some(&table.partitioning),
+            Self::Iceberg(table) => Some(&table.partitioning),
+            _ => None,
+        }
+    }
+
+    pub fn delta(&self) -> Option<&DeltaTable> {
+        match self {
+            Self::Delta(table) => Some(table),
+            _ => None,
+        }
+    }
+
+    pub fn iceberg(&self) -> Option<&IcebergTable> {
+        match self {
+            Self::Iceberg(table) => Some(table),
+            _ => None,
+        }
+    }
+
+    pub fn salvage(&self) -> Option<&Salvage> {
+        match self {
+            Self::Salvaged(salvage) => Some(salvage),
+            _ => None,
+        }
+    }
+}
+
+/// Checks the end of a Parquet file, which the parquet crate only reports as
+/// a corrupt footer, for the usual signs of damage.
+pub(crate) fn check_footer<R: ChunkReader>(name: &str, file: &R) -> Result<()> {
+    let size = file.len();
+    // The magic at the start, then the metadata length and magic at the end.
+    if size < (MAGIC_SIZE + FOOTER_SIZE) as u64 {
+        return Err(Error::TruncatedFooter {
+            file: name.to_string(),
+            size,
+        });
+    }
+    let tail = file.get_bytes(size - FOOTER_SIZE as u64, FOOTER_SIZE)?;
+    let magic = &tail[FOOTER_SIZE - MAGIC_SIZE..];
+    if magic != b"PAR1" && magic != b"PARE" {
+        return Err(Error::BadMagic {
+            file: name.to_string(),
+            found: format!("{:?}", String::from_utf8_lossy(magic)),
+        });
+    }
+    let length = u32::from_le_bytes(tail[..4].try_into().unwrap()) as u64;
+    if length + ((MAGIC_SIZE + FOOTER_SIZE) as u64) > size {
+        return Err(Error::InvalidMetadataLength {
+            file: name.to_string(),
+            length,
+            size,
+        });
+    }
+    Ok(())
+}
+
+/// Reads the files of `partitioning`, with the metadata of the first one.
+fn partitioned_reader(
+    partitioning: &Partitioning,
+    columns: Option<&[String]>,
+    batch_size: usize,
+) -> Result<(Metadata, Box<dyn RecordBatchReader + Send>)> {
+    let first = partitioning
+        .files
+        .first()
+        .ok_or_else(|| Error::NoParquetFiles(partitioning.root.clone()))?;
+    let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(&first.path)?)?;
+    let reader = partitioning.reader(columns, batch_size)?;
+    let metadata = Metadata::Parquet(Arc::clone(builder.metadata()));
+    Ok((metadata, Box::new(reader)))
+}
+
+/// Reads a mapped Parquet file, decoding columns in parallel.
+fn parallel_reader(
+    file: MappedFile,
+    columns: Option<&[String]>,
+    batch_size: usize,
+) -> Result<(Metadata, Box<dyn RecordBatchReader + Send>)> {
+    let metadata = ArrowReaderMetadata::load(&file, ArrowReaderOptions::default())?;
+    let reader = ParallelReader::new(&file, &metadata, columns, batch_size)?;
+    let metadata = Metadata::Parquet(Arc::clone(metadata.metadata()));
+    Ok((metadata, Box::new(reader)))
+}
+
+fn parquet_reader<R: ChunkReader + 'static>(
+    file: R,
+    columns: Option<&[String]>,
+    batch_size: usize,
+) -> Result<(Metadata, Box<dyn RecordBatchReader + Send>)> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+    let metadata = Metadata::Parquet(Arc::clone(builder.metadata()));
+    let mask = projection(builder.schema(), builder.parquet_schema(), columns);
+    let reader = builder
+        .with_projection(mask)
+        .with_batch_size(batch_size)
+        .build()?;
+    Ok((metadata, Box::new(reader)))
+}
+
+/// Reads a mapped Parquet file from its `row_group`th row group on, skipping
+/// its first `skip` rows.
+fn reader_from(
+    file: MappedFile,
+    metadata: ArrowReaderMetadata,
+    columns: Option<&[String]>,
+    batch_size: usize,
+    row_group: usize,
+    skip: usize,
+) -> Result<Box<dyn RecordBatchReader + Send>> {
+    let row_groups = row_group..metadata.metadata().num_row_groups();
+    let num_rows: usize = metadata.metadata().row_groups()[row_groups.clone()]
+        .iter()
+        .map(|rg| rg.num_rows() as usize)
+        .sum();
+    let selection = RowSelection::from(vec![
+        RowSelector::skip(skip),
+        RowSelector::select(num_rows - skip),
+    ]);
+    let mask = projection(metadata.schema(), metadata.parquet_schema(), columns);
+    let reader = ParquetRecordBatchReaderBuilder::new_with_metadata(file, metadata)
+        .with_projection(mask)
+        .with_row_groups(row_groups.collect())
+        .with_row_selection(selection)
+        .with_batch_size(batch_size)
+        .build()?;
+    Ok(Box::new(reader))
+}
+
+fn arrow_reader<R: Read + Seek + Send + 'static>(
+    mut file: R,
+    size: u64,
+    format: Format,
+    columns: Option<&[String]>,
+    batch_size: usize,
+) -> Result<(Metadata, Box<dyn RecordBatchReader + Send>)> {
+    let mut details = vec![("Size".to_string(), format_size(size))];
+    let reader: Box<dyn RecordBatchReader + Send> = match format {
+        Format::Csv { delimiter } => {
+            let csv_format = csv::reader::Format::default()
+                .with_header(true)
+                .with_delimiter(delimiter);
+            let (schema, records) =
+                csv_format.infer_schema(&mut file, Some(INFER_SCHEMA_RECORDS))?;
+            file.seek(SeekFrom::Start(0))?;
+            details.push(("Delimiter".to_string(), format!("{:?}", delimiter as char)));
+            details.push(inferred_from(records));
+
+            let indices = indices(&schema, columns);
+            let mut builder = csv::ReaderBuilder::new(Arc::new(schema))
+                .with_format(csv_format)
+                .with_batch_size(batch_size);
+            if let Some(indices) = indices {
+                builder = builder.with_projection(indices);
+            }
+            Box::new(builder.build(file)?)
+        }
+        Format::Json => {
+            let mut file = BufReader::new(file);
+            let (schema, records) = json::reader::infer_json_schema_from_seekable(
+                &mut file,
+                Some(INFER_SCHEMA_RECORDS),
+            )?;
+            details.push(inferred_from(records));
+
+            // Fields left out of the schema are skipped.
+            let schema = match indices(&schema, columns) {
+                Some(indices) => schema.project(&indices)?,
+                None => schema,
+            };
+            let reader = json::ReaderBuilder::new(Arc::new(schema))
+                .with_batch_size(batch_size)
+                .build(file)?;
+            Box::new(reader)
+        }
+        Format::ArrowFile => {
+            let schema = FileReader::try_new_buffered(&mut file, None)?.schema();
+            file.seek(SeekFrom::Start(0))?;
+            let reader = FileReader::try_new_buffered(file, indices(&schema, columns))?;
+            details.push((
+                "# record batches".to_string(),
+                reader.num_batches().to_string(),
+            ));
+            let mut custom_metadata: Vec<_> = reader.custom_metadata().iter().collect();
+            custom_metadata.sort();
+            for (k, v) in custom_metadata {
+                details.push((k.clone(), v.clone()));
+            }
+            Box::new(reader)
+        }
+        Format::ArrowStream => {
+            let schema = StreamReader::try_new_buffered(&mut file, None)?.schema();
+            file.seek(SeekFrom::Start(0))?;
+            Box::new(StreamReader::try_new_buffered(
+                file,
+                indices(&schema, columns),
+            )?)
+        }
+        Format::Parquet => unreachable!("Parquet files are read by `parquet_reader`"),
+    };
+    Ok((Metadata::Other(details), reader))
+}
+
+fn inferred_from(records: usize) -> (String, String) {
+    (
+        "Schema inferred from".to_string(),
+        format!("{records} records"),
+    )
+}
+
+/// Indices of the top-level `columns` in `schema`, or `None` for all of them.
+fn indices(schema: &Schema, columns: Option<&[String]>) -> Option<Vec<usize>> {
+    let columns = columns?;
+    Some(
+        schema
+            .fields()
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| columns.contains(f.name()))
+            .map(|(i, _)| i)
+            .collect(),
+    )
+}
+
+/// Mask selecting the top-level `columns` of a file, or all of them when `None`.
+pub(crate) fn projection(
+    schema: &Schema,
+    parquet_schema: &SchemaDescriptor,
+    columns: Option<&[String]>,
+) -> ProjectionMask {
+    let Some(columns) = columns else {
+        return ProjectionMask::all();
+    };
+    let indices = schema
+        .fields()
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| columns.contains(f.name()))
+        .map(|(i, _)| i);
+    ProjectionMask::roots(parquet_schema, indices)
+}
+
+pub(crate) fn format_size(bytes: u64) -> String {
+    let mut size = bytes as f64;
+    for unit in ["B", "kB", "MB", "GB"] {
+        if size < 1000.0 {
+            return format!("{size:.1}{unit}");
+        }
+        size /= 1000.0;
+    }
+    format!("{size:.1}TB")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use arrow::{
+        array::{ArrayRef, Int64Array, RecordBatch},
+        ipc::writer::{FileWriter, StreamWriter},
+        util::pretty::pretty_format_batches,
+    };
+    use parquet::arrow::ArrowWriter;
+
+    use super::*;
+
+    fn batch() -> RecordBatch {
+        let ids = Int64Array::from(vec![1, 2, 3]);
+        RecordBatch::try_from_iter([("id", Arc::new(ids) as ArrayRef)]).unwrap()
+    }
+
+    fn read(source: &Source) -> String {
+        let (_, reader) = source.reader(None, 2).unwrap();
+        let batches = reader.collect::<std::result::Result<Vec<_>, _>>().unwrap();
+        pretty_format_batches(&batches).unwrap().to_string()
+    }
+
+    #[test]
+    fn detects_formats_from_contents() {
+        assert_eq!(
+            Format::detect(Some("csv"), b"PAR1\x15\x04"),
+            Format::Parquet
+        );
+        assert_eq!(Format::detect(None, b"PARE"), Format::Parquet);
+        assert_eq!(
+            Format::detect(Some("parquet"), b"ARROW1\0\0"),
+            Format::ArrowFile
+        );
+        assert_eq!(Format::detect(None, &[0xff; 8]), Format::ArrowStream);
+        assert_eq!(Format::detect(None, b"  {\"id\": 1}\n"), Format::Json);
+        assert_eq!(
+            Format::detect(None, b"id,name\n1,a\n"),
+            Format::Csv { delimiter: b',' }
+        );
+        assert_eq!(
+            Format::detect(None, b"id\tname\tnote\n1\ta\tb,c\n"),
+            Format::Csv { delimiter: b'\t' }
+        );
+        assert_eq!(Format::detect(None, &[0, 1, 2, 3]), Format::Parquet);
+    }
+
+    #[test]
+    fn detects_formats_from_extensions() {
+        assert_eq!(
+            Format::detect(Some("TSV"), b"id,name\n"),
+            Format::Csv { delimiter: b'\t' }
+        );
+        assert_eq!(Format::detect(Some("ndjson"), b"[]"), Format::Json);
+        assert_eq!(Format::detect(Some("feather"), b""), Format::ArrowFile);
+        assert_eq!(Format::detect(Some("arrows"), b""), Format::ArrowStream);
+        assert_eq!(
+            Format::detect(Some("bin"), &[0, 159, 146, 150]),
+            Format::Parquet
+        );
+        assert_eq!(
+            Format::detect(
+                Some("parquet"),
+                b"id,name
+"
+            ),
+            Format::Parquet
+        );
+    }
+
+    #[test]
+    fn reports_damaged_parquet_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.parquet");
+        // E.g. a download that failed with a text error page.
+        fs::write(
+            &path,
+            "upstream request timeout, please retry later
+",
+        )
+        .unwrap();
+        let source = Source::file(&path).unwrap();
+        assert_eq!(source.format(), Format::Parquet);
+        let error = source.reader(None, 1024).err().unwrap();
+        assert!(matches!(error, Error::BadMagic { .. }), "{error}");
+    }
+
+    #[test]
+    fn reads_every_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let expected = pretty_format_batches(&[batch()]).unwrap().to_string();
+
+        let path = dir.path().join("data.parquet");
+        let mut writer =
+            ArrowWriter::try_new(File::create(&path).unwrap(), batch().schema(), None).unwrap();
+        writer.write(&batch()).unwrap();
+        writer.close().unwrap();
+        let source = Source::file(&path).unwrap();
+        assert_eq!(source.format(), Format::Parquet);
+        assert_eq!(read(&source), expected);
+        let bytes = Source::bytes(fs::read(&path).unwrap().into());
+        assert_eq!(read(&bytes), expected);
+
+        let path = dir.path().join("data");
+        fs::write(&path, "id\n1\n2\n3\n").unwrap();
+        assert_eq!(read(&Source::file(&path).unwrap()), expected);
+
+        let path = dir.path().join("data.jsonl");
+        fs::write(&path, "{\"id\": 1}\n{\"id\": 2}\n{\"id\": 3}\n").unwrap();
+        assert_eq!(read(&Source::file(&path).unwrap()), expected);
+
+        let path = dir.path().join("data.arrow");
+        let mut writer =
+            FileWriter::try_new(File::create(&path).unwrap(), &batch().schema()).unwrap();
+        writer.write(&batch()).unwrap();
+        writer.finish().unwrap();
+        assert_eq!(read(&Source::file(&path).unwrap()), expected);
+
+        let path = dir.path().join("data.bin");
+        let mut writer =
+            StreamWriter::try_new(File::create(&path).unwrap(), &batch().schema()).unwrap();
+        writer.write(&batch()).unwrap();
+        writer.finish().unwrap();
+        let source = Source::file(&path).unwrap();
+        assert_eq!(source.format(), Format::ArrowStream);
+        assert_eq!(read(&source), expected);
+    }
+
+    fn parquet_bytes(batch: &RecordBatch) -> Vec<u8> {
+        let mut buf = vec![];
+        let mut writer = ArrowWriter::try_new(&mut buf, batch.schema(), None).unwrap();
+        writer.write(batch).unwrap();
+        writer.close().unwrap();
+        buf
+    }
+
+    #[test]
+    fn checks_footers() {
+        let check = |bytes: Vec<u8>| check_footer("test.parquet", &Bytes::from(bytes));
+        let file = parquet_bytes(&batch());
+        check(file.clone()).unwrap();
+
+        assert!(matches!(
+            check(b"PAR1PAR1".to_vec()),
+            Err(Error::TruncatedFooter { size: 8, .. })
+        ));
+
+        // Cut off in the middle of the metadata.
+        let cut = file[..file.len() - 10].to_vec();
+        match check(cut) {
+            Err(Error::BadMagic { found, .. }) => assert_ne!(found, "\"PAR1\""),
+            result => panic!("expected a bad magic, got {result:?}"),
+        }
+
+        // A metadata length past the start of the file.
+        let mut long = file.clone();
+        let at = long.len() - FOOTER_SIZE;
+        long[at..at + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            check(long),
+            Err(Error::InvalidMetadataLength {
+                length: 0xffff_ffff,
+                ..
+            })
+        ));
+
+        // The footer of an encrypted file is only looked at by its magic.
+        let mut encrypted = file;
+        let at = encrypted.len() - MAGIC_SIZE;
+        encrypted[at..].copy_from_slice(b"PARE");
+        check(encrypted).unwrap();
+    }
+
+    #[test]
+    fn reads_files_without_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("empty.parquet");
+        fs::write(&path, parquet_bytes(&batch().slice(0, 0))).unwrap();
+        let (_, reader) = Source::file(&path).unwrap().reader(None, 2).unwrap();
+        assert_eq!(reader.schema(), batch().schema());
+        assert_eq!(reader.map(|b| b.unwrap().num_rows()).sum::<usize>(), 0);
+    }
+}
@@ -0,0 +1,197 @@
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+use arrow::ipc::reader::FileReader;
+use parquet::file::metadata::ParquetMetaDataReader;
+use ratatui::widgets::TableState;
+
+use crate::source::Format;
+
+/// Extensions of the files listed, those of the formats that can be viewed.
+const EXTENSIONS: &[&str] = &[
+    "parquet", "pq", "csv", "tsv", "json", "jsonl", "ndjson", "arrow", "feather", "ipc", "arrows",
+];
+
+/// What is shown of a file before opening it, read from its footer only.
+#[derive(Clone, Debug)]
+pub struct Preview {
+    pub format: Format,
+    pub size: u64,
+    /// Unknown for formats without a footer counting them.
+    pub rows: Option<i64>,
+    pub columns: Option<usize>,
+}
+
+impl Preview {
+    fn read(path: &Path) -> Option<Self> {
+        let size = fs::metadata(path).ok()?.len();
+        let extension = path.extension().and_then(|e| e.to_str());
+        let format = Format::detect(extension, &[]);
+        let mut preview = Self {
+            format,
+            size,
+            rows: None,
+            columns: None,
+        };
+        let Ok(file) = File::open(path) else {
+            return Some(preview);
+        };
+        match format {
+            Format::Parquet => {
+                // Files with an encrypted footer are only listed.
+                if let Ok(metadata) = ParquetMetaDataReader::new().parse_and_finish(&file) {
+                    let metadata = metadata.file_metadata();
+                    preview.rows = Some(metadata.num_rows());
+                    preview.columns = Some(metadata.schema_descr().num_columns());
+                }
+            }
+            Format::ArrowFile => {
+                if let Ok(reader) = FileReader::try_new(file, None) {
+                    preview.columns = Some(reader.schema().fields().len());
+                }
+            }
+            Format::Csv { .. } | Format::Json | Format::ArrowStream => {}
+        }
+        Some(preview)
+    }
+}
+
+/// An entry of the directory tree.
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub path: PathBuf,
+    /// Number of directories between the root and the entry.
+    pub depth: usize,
+    pub kind: EntryKind,
+}
+
+#[derive(Clone, Debug)]
+pub enum EntryKind {
+    Directory { expanded: bool },
+    File(Option<Preview>),
+}
+
+impl Entry {
+    pub fn name(&self) -> String {
+        let name = self.path.file_name().map_or_else(
+            || self.path.display().to_string(),
+            |n| n.to_string_lossy().to_string(),
+        );
+        match self.kind {
+            EntryKind::Directory { .. } => format!("{name}/"),
+            EntryKind::File(_) => name,
+        }
+    }
+}
+
+/// The directory tree of the Files tab, listing the files that can be viewed.
+#[derive(Debug)]
+pub struct Browser {
+    pub root: PathBuf,
+    /// Entries of the root and of the expanded directories, in tree order.
+    pub entries: Vec<Entry>,
+    pub table_state: TableState,
+}
+
+impl Browser {
+    pub fn new(root: &Path) -> Self {
+        let root = fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+        Self {
+            entries: list(&root, 0),
+            root,
+            table_state: TableState::default().with_selected(0),
+        }
+    }
+
+    pub fn selected(&self) -> Option<&Entry> {
+        self.entries.get(self.table_state.selected()?)
+    }
+
+    /// Expands the selected directory, or collapses it when expanded.
+    pub fn toggle(&mut self) {
+        let Some(i) = self.table_state.selected() else {
+            return;
+        };
+        let Some(entry) = self.entries.get_mut(i) else {
+            return;
+        };
+        let EntryKind::Directory { expanded } = &mut entry.kind else {
+            return;
+        };
+        *expanded = !*expanded;
+        let depth = entry.depth;
+        if *expanded {
+            let children = list(&entry.path.clone(), depth + 1);
+            self.entries.splice(i + 1..i + 1, children);
+        } else {
+            let end = self.entries[i + 1..]
+                .iter()
+                .position(|e| e.depth <= depth)
+                .map_or(self.entries.len(), |n| i + 1 + n);
+            self.entries.drain(i + 1..end);
+        }
+    }
+
+    /// Expands the selected directory.
+    pub fn expand(&mut self) {
+        if let Some(EntryKind::Directory { expanded: false }) = self.selected().map(|e| &e.kind) {
+            self.toggle();
+        }
+    }
+
+    /// Collapses the selected directory, or selects the one the selected
+    /// entry is in.
+    pub fn collapse(&mut self) {
+        let Some(i) = self.table_state.selected() else {
+            return;
+        };
+        let Some(entry) = self.entries.get(i) else {
+            return;
+        };
+        if let EntryKind::Directory { expanded: true } = entry.kind {
+            return self.toggle();
+        }
+        let depth = entry.depth;
+        if let Some(parent) = self.entries[..i].iter().rposition(|e| e.depth < depth) {
+            self.table_state.select(Some(parent));
+        }
+    }
+}
+
+/// Directories and viewable files in `dir`, directories first, leaving out
+/// hidden ones.
+fn list(dir: &Path, depth: usize) -> Vec<Entry> {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut paths: Vec<_> = read_dir
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            !path
+                .file_name()
+                .is_some_and(|n| n.to_string_lossy().starts_with('.'))
+        })
+        .collect();
+    paths.sort();
+    let (dirs, files): (Vec<_>, Vec<_>) = paths.into_iter().partition(|path| path.is_dir());
+    let dirs = dirs.into_iter().map(|path| Entry {
+        path,
+        depth,
+        kind: EntryKind::Directory { expanded: false },
+    });
+    let files = files
+        .into_iter()
+        .filter(|path| {
+            path.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+        })
+        .map(|path| Entry {
+            kind: EntryKind::File(Preview::read(&path)),
+            path,
+            depth,
+        });
+    dirs.chain(files).collect()
+}
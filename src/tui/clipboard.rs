@@ -0,0 +1,113 @@
+use std::{
+    env,
+    io::{self, Write},
+    process::{Command, Stdio},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Deserialize;
+
+use crate::error::{Error, Result};
+
+/// How yanked text reaches the system clipboard.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// An OSC 52 escape sequence, which the terminal turns into a copy even
+    /// over SSH.
+    #[default]
+    Osc52,
+    /// The first of `pbcopy`, `wl-copy`, `xclip`, `xsel` and `clip.exe` found.
+    Native,
+}
+
+/// Format of a row yanked with `Y`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RowFormat {
+    #[default]
+    Tsv,
+    Json,
+}
+
+/// Format of a rectangle yanked in visual mode.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SelectionFormat {
+    #[default]
+    Tsv,
+    Markdown,
+}
+
+/// The `[clipboard]` table of the config file.
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Clipboard {
+    pub backend: Backend,
+    pub row_format: RowFormat,
+    pub selection_format: SelectionFormat,
+}
+
+/// Clipboard commands, each reading the text from stdin.
+const NATIVE_COMMANDS: &[&[&str]] = &[
+    &["pbcopy"],
+    &["wl-copy"],
+    &["xclip", "-selection", "clipboard"],
+    &["xsel", "--clipboard", "--input"],
+    &["clip.exe"],
+];
+
+impl Clipboard {
+    pub fn copy(&self, text: &str) -> Result<()> {
+        match self.backend {
+            Backend::Osc52 => copy_osc52(text),
+            Backend::Native => copy_native(text),
+        }
+    }
+}
+
+fn copy_osc52(text: &str) -> Result<()> {
+    let mut sequence = format!("\x1b]52;c;{}\x07", STANDARD.encode(text));
+    // tmux only passes sequences on to the outer terminal when wrapped, and
+    // with `allow-passthrough` on.
+    if env::var_os("TMUX").is_some() {
+        sequence = format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"));
+    }
+    let mut stdout = io::stdout();
+    stdout.write_all(sequence.as_bytes())?;
+    stdout.flush()?;
+    Ok(())
+}
+
+fn copy_native(text: &str) -> Result<()> {
+    for command in NATIVE_COMMANDS {
+        let Ok(mut child) = Command::new(command[0])
+            .args(&command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        child.wait()?;
+        return Ok(());
+    }
+    Err(Error::NoClipboard)
+}
+
+/// Escapes what would break a TSV cell.
+pub fn tsv_escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// Escapes what would break a Markdown table cell.
+pub fn markdown_escape(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', "<br>")
+}
@@ -1,31 +1,358 @@
-use crossterm::event::{KeyCode, KeyEvent};
+use std::{collections::BTreeMap, fmt, str::FromStr};
 
-#[derive(Debug, PartialEq, Eq)]
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::Direction;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Command {
     Exit,
+    /// Open the `:` command line.
+    CommandLine,
+    /// Open the selected item, e.g. a data file of an Iceberg table.
+    Select,
+    /// Show or hide the key bindings.
+    Help,
+    /// Copy the selected cell, or the visual selection.
+    Yank,
+    /// Copy the selected row.
+    YankRow,
+    /// Start or cancel selecting a rectangle of cells.
+    Visual,
+    /// Show or hide the list of open files.
+    Buffers,
+    /// Close the file shown, unless it's the only one open.
+    CloseBuffer,
+    /// Split the pane, laying the panes out in `Direction`.
+    Split(Direction),
+    /// Close the pane, unless the screen isn't split.
+    ClosePane,
+    /// Close every pane but this one.
+    OnlyPane,
+    /// Link the rows selected in every pane, or unlink them.
+    SyncScroll,
+    /// Bookmark the selected row under the letter pressed next.
+    Mark,
+    /// Go to the row bookmarked under the letter pressed next.
+    JumpToMark,
     Nothing,
     Next(ScrollType),
     Previous(ScrollType),
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ScrollType {
     Tab,
+    /// Switch between open files.
+    Buffer,
+    /// Move between the panes of a split screen.
+    Pane,
     Vertical,
     Horizontal,
 }
 
-impl From<KeyEvent> for Command {
+/// Names of the commands in the config file, in the order the help lists
+/// them.
+const COMMANDS: &[(&str, Command)] = &[
+    ("down", Command::Next(ScrollType::Vertical)),
+    ("up", Command::Previous(ScrollType::Vertical)),
+    ("right", Command::Next(ScrollType::Horizontal)),
+    ("left", Command::Previous(ScrollType::Horizontal)),
+    ("next-tab", Command::Next(ScrollType::Tab)),
+    ("previous-tab", Command::Previous(ScrollType::Tab)),
+    ("next-buffer", Command::Next(ScrollType::Buffer)),
+    ("previous-buffer", Command::Previous(ScrollType::Buffer)),
+    ("buffers", Command::Buffers),
+    ("close-buffer", Command::CloseBuffer),
+    // Named as in vim, where `split` stacks panes and `vsplit` puts them
+    // side by side.
+    ("split", Command::Split(Direction::Vertical)),
+    ("vsplit", Command::Split(Direction::Horizontal)),
+    ("next-pane", Command::Next(ScrollType::Pane)),
+    ("previous-pane", Command::Previous(ScrollType::Pane)),
+    ("close-pane", Command::ClosePane),
+    ("only-pane", Command::OnlyPane),
+    ("sync-scroll", Command::SyncScroll),
+    ("mark", Command::Mark),
+    ("jump-to-mark", Command::JumpToMark),
+    ("select", Command::Select),
+    ("yank", Command::Yank),
+    ("yank-row", Command::YankRow),
+    ("visual", Command::Visual),
+    ("command-line", Command::CommandLine),
+    ("help", Command::Help),
+    ("quit", Command::Exit),
+    // Unbinds a key.
+    ("nothing", Command::Nothing),
+];
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        COMMANDS
+            .iter()
+            .find(|(name, _)| *name == s)
+            .map(|(_, c)| *c)
+            .ok_or_else(|| {
+                let names: Vec<_> = COMMANDS.iter().map(|(name, _)| *name).collect();
+                format!(
+                    "unknown command `{s}`, expected one of {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Names of the commands the command line can run too, leaving out those
+/// that make no sense there.
+pub fn names() -> impl Iterator<Item = &'static str> {
+    COMMANDS
+        .iter()
+        .filter(|(_, c)| !matches!(c, Command::Nothing | Command::CommandLine))
+        .map(|(name, _)| *name)
+}
+
+/// A key with its modifiers, e.g. `ctrl-d`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl From<KeyEvent> for Key {
     fn from(key_event: KeyEvent) -> Self {
-        match key_event.code {
-            KeyCode::Esc | KeyCode::Char('q') => Self::Exit,
-            KeyCode::BackTab => Self::Previous(ScrollType::Tab),
-            KeyCode::Tab => Self::Next(ScrollType::Tab),
-            KeyCode::Char('j') | KeyCode::Down => Self::Next(ScrollType::Vertical),
-            KeyCode::Char('k') | KeyCode::Up => Self::Previous(ScrollType::Vertical),
-            KeyCode::Char('l') | KeyCode::Right => Self::Next(ScrollType::Horizontal),
-            KeyCode::Char('h') | KeyCode::Left => Self::Previous(ScrollType::Horizontal),
-            _ => Self::Nothing,
+        let mut modifiers = key_event.modifiers;
+        // Shifted characters arrive as upper case already, and back tab with
+        // shift held.
+        if matches!(key_event.code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self {
+            code: key_event.code,
+            modifiers,
+        }
+    }
+}
+
+/// Named keys, besides single characters and `f1` to `f12`.
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("esc", KeyCode::Esc),
+    ("enter", KeyCode::Enter),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("space", KeyCode::Char(' ')),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+];
+
+impl FromStr for Key {
+    type Err = String;
+
+    /// Parses e.g. `j`, `ctrl-d`, `alt-shift-left` or `f5`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+        // A trailing `-` is the minus key rather than a separator.
+        while let Some((modifier, key)) = rest.split_once('-').filter(|(_, k)| !k.is_empty()) {
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier `{modifier}` in `{s}`")),
+            };
+            rest = key;
+        }
+        let lower = rest.to_ascii_lowercase();
+        let code = if let Some((_, code)) = KEY_NAMES.iter().find(|(name, _)| *name == lower) {
+            *code
+        } else if let Some(n) = lower.strip_prefix('f').and_then(|n| n.parse().ok()) {
+            KeyCode::F(n)
+        } else {
+            let mut chars = rest.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => KeyCode::Char(c),
+                _ => return Err(format!("unknown key `{s}`")),
+            }
+        };
+        // Shift is part of the character, e.g. `J` rather than `shift-j`.
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::Char(c.to_ascii_uppercase())
+            }
+            KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::BackTab
+            }
+            code => code,
+        };
+        Ok(Self { code, modifiers })
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl-"),
+            (KeyModifiers::ALT, "alt-"),
+            (KeyModifiers::SHIFT, "shift-"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{name}")?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(n) => write!(f, "f{n}"),
+            code => {
+                let name = KEY_NAMES.iter().find(|(_, c)| *c == code);
+                write!(f, "{}", name.map_or("?", |(name, _)| name))
+            }
         }
     }
 }
+
+/// Keys pressed one after another, e.g. `g g`.
+pub type Chord = Vec<Key>;
+
+/// Vim-style defaults, with the arrow keys as alternatives.
+const DEFAULT_BINDINGS: &[(&str, &str)] = &[
+    ("j", "down"),
+    ("down", "down"),
+    ("k", "up"),
+    ("up", "up"),
+    ("l", "right"),
+    ("right", "right"),
+    ("h", "left"),
+    ("left", "left"),
+    ("tab", "next-tab"),
+    ("backtab", "previous-tab"),
+    ("] b", "next-buffer"),
+    ("[ b", "previous-buffer"),
+    ("b", "buffers"),
+    ("ctrl-w s", "split"),
+    ("ctrl-w v", "vsplit"),
+    ("ctrl-w w", "next-pane"),
+    ("ctrl-w W", "previous-pane"),
+    ("ctrl-w c", "close-pane"),
+    ("ctrl-w o", "only-pane"),
+    ("m", "mark"),
+    ("'", "jump-to-mark"),
+    ("enter", "select"),
+    ("y", "yank"),
+    ("Y", "yank-row"),
+    ("v", "visual"),
+    (":", "command-line"),
+    ("?", "help"),
+    ("q", "quit"),
+    ("esc", "quit"),
+];
+
+/// Maps keys and chords to commands: the defaults, overridden by the `[keys]`
+/// table of the config file.
+#[derive(Debug)]
+pub struct Keymap {
+    bindings: Vec<(Chord, Command)>,
+    /// Keys of a chord pressed so far.
+    pending: Chord,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = DEFAULT_BINDINGS
+            .iter()
+            .map(|(keys, command)| (parse_chord(keys).unwrap(), command.parse().unwrap()))
+            .collect();
+        Self {
+            bindings,
+            pending: vec![],
+        }
+    }
+}
+
+impl Keymap {
+    /// Adds `bindings` of chords to command names to the defaults, replacing
+    /// those bound to the same chord.
+    pub fn new(bindings: &BTreeMap<String, String>) -> Result<Self, String> {
+        let mut keymap = Self::default();
+        for (keys, command) in bindings {
+            let chord = parse_chord(keys)?;
+            let command: Command = command.parse()?;
+            keymap.bindings.retain(|(c, _)| *c != chord);
+            if command != Command::Nothing {
+                keymap.bindings.push((chord, command));
+            }
+        }
+        Ok(keymap)
+    }
+
+    /// Resolves a key press, waiting for the rest of a chord when it starts
+    /// one.
+    pub fn command(&mut self, key_event: KeyEvent) -> Command {
+        self.pending.push(Key::from(key_event));
+        if let Some((_, command)) = self.bindings.iter().find(|(c, _)| *c == self.pending) {
+            self.pending.clear();
+            return *command;
+        }
+        if self
+            .bindings
+            .iter()
+            .any(|(c, _)| c.starts_with(&self.pending))
+        {
+            return Command::Nothing;
+        }
+        // A key that breaks off a chord may start another one.
+        let restart = self.pending.len() > 1;
+        self.pending.clear();
+        if restart {
+            self.command(key_event)
+        } else {
+            Command::Nothing
+        }
+    }
+
+    /// Chords bound to each command, in the order the help lists them.
+    pub fn help(&self) -> Vec<(&'static str, Vec<String>)> {
+        COMMANDS
+            .iter()
+            .filter(|(_, command)| *command != Command::Nothing)
+            .map(|(name, command)| {
+                let chords = self
+                    .bindings
+                    .iter()
+                    .filter(|(_, c)| c == command)
+                    .map(|(chord, _)| {
+                        chord
+                            .iter()
+                            .map(Key::to_string)
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    })
+                    .collect();
+                (*name, chords)
+            })
+            .collect()
+    }
+}
+
+/// Parses space-separated keys, e.g. `g g` or `ctrl-w l`.
+fn parse_chord(s: &str) -> Result<Chord, String> {
+    let chord = s
+        .split_whitespace()
+        .map(Key::from_str)
+        .collect::<Result<Chord, _>>()?;
+    if chord.is_empty() {
+        return Err("empty key binding".to_string());
+    }
+    Ok(chord)
+}
@@ -1,4 +1,4 @@
-use crate::error::Result;
+use crate::{error::Result, profile::ColumnProfile};
 use ratatui::crossterm::event::{self, Event as CrosstermEvent, KeyEvent, MouseEvent};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
@@ -18,6 +18,12 @@ pub enum Event {
     Mouse(MouseEvent),
     /// Terminal resize.
     Resize(u16, u16),
+    /// Result of profiling a column in the background, with the id of the
+    /// request, telling which buffer it's for.
+    Profile(u64, Box<Result<ColumnProfile>>),
+    /// Rows of a column profiled so far in the background, with the id of
+    /// the request.
+    ProfileProgress(u64, usize),
     // /// Result of `strings` call.
     // FileStrings(Result<Vec<(String, u64)>>),
     // /// Trace system calls.
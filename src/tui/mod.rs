@@ -7,13 +7,28 @@ pub mod event;
 /// Widget renderer.
 pub mod ui;
 
-/// Custom widgets.
+// /// Custom widgets.
 // pub mod widgets;
 
 /// Possible commands.
 pub mod command;
 
-use crate::error::Result;
+/// Commands, completion and history of the `:` command line.
+pub mod palette;
+
+/// Directory tree of the Files tab.
+pub mod browser;
+
+/// View state and bookmarks kept across runs.
+pub mod session;
+
+/// Copying cells to the system clipboard.
+pub mod clipboard;
+
+/// Colours of the built-in and configured themes.
+pub mod theme;
+
+use crate::error::{set_panic_hook, Result};
 use event::EventHandler;
 use ratatui::backend::{Backend, CrosstermBackend};
 use ratatui::crossterm::event::{DisableMouseCapture, EnableMouseCapture};
@@ -21,8 +36,8 @@ use ratatui::crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScr
 use ratatui::Terminal;
 use state::State;
 // use state::State;
+use std::io;
 use std::sync::atomic::Ordering;
-use std::{io, panic};
 
 /// Representation of a terminal user interface.
 ///
@@ -54,7 +69,7 @@ impl<B: Backend> Tui<B> {
     pub fn init(&mut self) -> Result<()> {
         terminal::enable_raw_mode()?;
         ratatui::crossterm::execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
-        panic::set_hook(Box::new(move |panic| {
+        set_panic_hook(Box::new(move |panic| {
             Self::reset().expect("failed to reset the terminal");
             better_panic::Settings::auto()
                 .most_recent_first(false)
@@ -72,7 +87,10 @@ impl<B: Backend> Tui<B> {
     /// [`Draw`]: tui::Terminal::draw
     /// [`rendering`]: crate::ui:render
     pub fn draw(&mut self, app: &mut State) -> Result<()> {
-        self.terminal.draw(|frame| ui::render(app, frame))?;
+        self.terminal.draw(|frame| {
+            ui::render(app, frame);
+            theme::fall_back(frame.buffer_mut());
+        })?;
         Ok(())
     }
 
@@ -0,0 +1,305 @@
+use std::{fs, io, path::PathBuf};
+
+use super::command;
+use crate::config;
+
+/// Commands of the `:` command line, besides those keys can be bound to,
+/// with the arguments they take.
+pub const COMMANDS: &[(&str, &str)] = &[
+    ("goto", "<row>"),
+    ("filter", "<SQL predicate>"),
+    ("sort", "<column> [desc]"),
+    ("toggle", "<column>"),
+    ("reset", ""),
+    ("sql", "<query>"),
+    ("export", "<path>"),
+    ("theme", "<name>"),
+    ("edit", "<path>"),
+    ("buffer", "<number or name>"),
+    ("source", "<path>"),
+];
+
+/// What an argument of a command is completed with.
+enum Argument {
+    Column,
+    Theme,
+    Buffer,
+    Path,
+}
+
+fn argument(command: &str) -> Option<Argument> {
+    match command {
+        "sort" | "toggle" => Some(Argument::Column),
+        "theme" => Some(Argument::Theme),
+        "b" | "buffer" => Some(Argument::Buffer),
+        "e" | "edit" | "open" | "export" | "export!" | "source" => Some(Argument::Path),
+        _ => None,
+    }
+}
+
+/// A line the command line can be completed to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Completion {
+    pub line: String,
+    /// Shown next to it, e.g. the arguments of a command.
+    pub hint: &'static str,
+}
+
+/// What the arguments of commands are completed with, besides paths.
+#[derive(Debug, Default)]
+pub struct Candidates {
+    pub columns: Vec<String>,
+    pub themes: Vec<String>,
+    /// Names of the open files.
+    pub buffers: Vec<String>,
+}
+
+/// Completes the command name being typed on `line`, or its first argument,
+/// best matches first.
+pub fn complete(line: &str, candidates: &Candidates) -> Vec<Completion> {
+    let Some((name, arg)) = line.split_once(' ') else {
+        let names = COMMANDS
+            .iter()
+            .copied()
+            .chain(command::names().map(|name| (name, "")));
+        // Ready for the arguments, if the command takes any.
+        return best_matches(line, names)
+            .map(|(name, hint)| Completion {
+                line: match hint {
+                    "" => name.to_string(),
+                    _ => format!("{name} "),
+                },
+                hint,
+            })
+            .collect();
+    };
+    // Only the first argument is completed.
+    if arg.contains(' ') {
+        return vec![];
+    }
+    let candidates = match argument(name) {
+        Some(Argument::Column) => &candidates.columns,
+        Some(Argument::Theme) => &candidates.themes,
+        Some(Argument::Buffer) => &candidates.buffers,
+        Some(Argument::Path) => return complete_path(name, arg),
+        None => return vec![],
+    };
+    best_matches(arg, candidates.iter().map(|c| (c.as_str(), "")))
+        .map(|(candidate, _)| Completion {
+            line: format!("{name} {candidate}"),
+            hint: "",
+        })
+        .collect()
+}
+
+/// Entries of the directory `arg` is in that match the rest of it.
+fn complete_path(name: &str, arg: &str) -> Vec<Completion> {
+    let (dir, file) = match arg.rfind('/') {
+        Some(i) => arg.split_at(i + 1),
+        None => ("", arg),
+    };
+    let Ok(entries) = fs::read_dir(if dir.is_empty() { "." } else { dir }) else {
+        return vec![];
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let mut name = entry.file_name().to_str()?.to_string();
+            if entry.path().is_dir() {
+                name.push('/');
+            }
+            Some(name)
+        })
+        // Hidden files only when asked for.
+        .filter(|n| !n.starts_with('.') || file.starts_with('.'))
+        .collect();
+    names.sort();
+    best_matches(file, names.iter().map(|n| (n.as_str(), "")))
+        .map(|(n, _)| Completion {
+            line: format!("{name} {dir}{n}"),
+            hint: "",
+        })
+        .collect()
+}
+
+/// Candidates matching `pattern`, best first, and in their original order
+/// when as good.
+fn best_matches<'a, T>(
+    pattern: &str,
+    candidates: impl Iterator<Item = (&'a str, T)>,
+) -> impl Iterator<Item = (&'a str, T)> {
+    let mut matches: Vec<_> = candidates
+        .filter_map(|(c, extra)| fuzzy_score(pattern, c).map(|score| (score, c, extra)))
+        .collect();
+    if !pattern.is_empty() {
+        matches.sort_by_key(|(score, _, _)| -score);
+    }
+    matches.into_iter().map(|(_, c, extra)| (c, extra))
+}
+
+/// How well `candidate` matches `pattern`, if it contains its characters in
+/// order, ignoring case: higher when they're next to each other or start
+/// words, and when the candidate is shorter.
+pub fn fuzzy_score(pattern: &str, candidate: &str) -> Option<i64> {
+    let mut score = 0;
+    let mut previous = None;
+    let mut chars = candidate.char_indices();
+    for p in pattern.chars().map(|c| c.to_ascii_lowercase()) {
+        let (i, _) = chars.by_ref().find(|(_, c)| c.to_ascii_lowercase() == p)?;
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == i) {
+            score += 4;
+        }
+        let start_of_word = candidate[..i]
+            .chars()
+            .next_back()
+            .is_none_or(|c| matches!(c, '-' | '_' | ' ' | '/' | '.'));
+        if start_of_word {
+            score += 8;
+        }
+        previous = Some(i);
+    }
+    Some(score * 100 - candidate.len() as i64)
+}
+
+/// Lines run on the command line stay this many runs in the history.
+const MAX_HISTORY: usize = 1000;
+
+/// Lines run on the command line, oldest first, kept across sessions in
+/// `$XDG_STATE_HOME/parqour/history`.
+#[derive(Debug, Default)]
+pub struct History {
+    lines: Vec<String>,
+    /// Line shown while going through the history with up and down.
+    position: Option<usize>,
+    /// What was typed before going through the history, which only lines
+    /// starting with it are shown for.
+    prefix: String,
+}
+
+impl History {
+    /// `$XDG_STATE_HOME/parqour/history`, falling back to `~/.local/state`.
+    pub fn path() -> Option<PathBuf> {
+        Some(config::state_dir()?.join("history"))
+    }
+
+    /// Reads the history file, starting afresh if there's none or it can't
+    /// be read.
+    pub fn load() -> Self {
+        let lines = Self::path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|text| text.lines().map(String::from).collect())
+            .unwrap_or_default();
+        Self {
+            lines,
+            ..Default::default()
+        }
+    }
+
+    /// Adds `line` as the latest, dropping earlier runs of it, and saves the
+    /// history.
+    pub fn push(&mut self, line: &str) -> io::Result<()> {
+        self.reset();
+        if line.trim().is_empty() {
+            return Ok(());
+        }
+        self.lines.retain(|l| l != line);
+        self.lines.push(line.to_string());
+        let excess = self.lines.len().saturating_sub(MAX_HISTORY);
+        self.lines.drain(..excess);
+
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut text = self.lines.join("\n");
+        text.push('\n');
+        fs::write(path, text)
+    }
+
+    /// The line run before the one shown, starting with what `input` was when
+    /// going through the history began.
+    pub fn older(&mut self, input: &str) -> Option<&str> {
+        if self.position.is_none() {
+            self.prefix = input.to_string();
+        }
+        let end = self.position.unwrap_or(self.lines.len());
+        let i = self.lines[..end]
+            .iter()
+            .rposition(|l| l.starts_with(&self.prefix))?;
+        self.position = Some(i);
+        Some(&self.lines[i])
+    }
+
+    /// The line run after the one shown, or what was typed past the latest.
+    pub fn newer(&mut self) -> Option<&str> {
+        let start = self.position? + 1;
+        match self.lines[start..]
+            .iter()
+            .position(|l| l.starts_with(&self.prefix))
+        {
+            Some(i) => {
+                self.position = Some(start + i);
+                Some(&self.lines[start + i])
+            }
+            None => {
+                self.position = None;
+                Some(&self.prefix)
+            }
+        }
+    }
+
+    /// Stops going through the history.
+    pub fn reset(&mut self) {
+        self.position = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scores_fuzzy_matches() {
+        assert_eq!(fuzzy_score("xyz", "export"), None);
+        assert_eq!(fuzzy_score("pe", "export"), None);
+        assert!(fuzzy_score("", "export").is_some());
+        // Matching is case-insensitive.
+        assert_eq!(fuzzy_score("EXP", "export"), fuzzy_score("exp", "export"));
+        // Adjacent characters beat scattered ones, word starts beat the
+        // middle of words, and shorter candidates beat longer ones.
+        assert!(fuzzy_score("ex", "exit") > fuzzy_score("ex", "erux"));
+        assert!(fuzzy_score("n", "first_name") > fuzzy_score("n", "anna"));
+        assert!(fuzzy_score("sort", "sort") > fuzzy_score("sort", "sorted"));
+
+        let names = [("theme", ""), ("toggle", ""), ("goto", "")];
+        let best: Vec<_> = best_matches("to", names.into_iter())
+            .map(|(n, _)| n)
+            .collect();
+        assert_eq!(best, ["toggle", "goto"]);
+    }
+
+    #[test]
+    fn goes_through_history() {
+        let mut history = History {
+            lines: ["sort a", "filter x > 1", "sort b", "goto 5"]
+                .map(String::from)
+                .to_vec(),
+            ..Default::default()
+        };
+        assert_eq!(history.older("so"), Some("sort b"));
+        assert_eq!(history.older("ignored"), Some("sort a"));
+        // Nothing older starts with `so`.
+        assert_eq!(history.older("ignored"), None);
+        assert_eq!(history.newer(), Some("sort b"));
+        // Back to what was typed.
+        assert_eq!(history.newer(), Some("so"));
+        assert_eq!(history.newer(), None);
+
+        assert_eq!(history.older(""), Some("goto 5"));
+        history.reset();
+        assert_eq!(history.older("f"), Some("filter x > 1"));
+    }
+}
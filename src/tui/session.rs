@@ -0,0 +1,263 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
+
+use crate::{
+    config,
+    error::{Error, Result},
+};
+
+/// Files whose view is remembered, dropping the least recently saved.
+const MAX_SESSIONS: usize = 500;
+
+/// How a file was being viewed when parqour last closed it.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Session {
+    /// When the file was last modified, in nanoseconds since the epoch: once
+    /// it changes, the rows saved may no longer be the same.
+    pub modified: u64,
+    /// When the session was saved, in seconds since the epoch.
+    pub saved: u64,
+    /// Row selected on the Data tab, counting from 0.
+    pub row: usize,
+    /// Name of the column selected on the Data tab.
+    pub column: Option<String>,
+    /// Columns hidden with `:toggle`.
+    pub hidden: Vec<String>,
+    pub filter: Option<String>,
+    /// Column the rows are sorted by.
+    pub sort: Option<String>,
+    pub descending: bool,
+    /// Rows marked with `m` and a letter, by letter.
+    pub bookmarks: BTreeMap<char, usize>,
+}
+
+/// Sessions of the files viewed, kept across runs in
+/// `$XDG_STATE_HOME/parqour/sessions.toml` and keyed by their path.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Sessions {
+    files: BTreeMap<PathBuf, Session>,
+    /// Set when the sessions file couldn't be read, so as not to save over
+    /// what it had.
+    #[serde(skip)]
+    unsaved: bool,
+}
+
+impl Sessions {
+    /// `$XDG_STATE_HOME/parqour/sessions.toml`, falling back to
+    /// `~/.local/state`.
+    pub fn path() -> Option<PathBuf> {
+        Some(config::state_dir()?.join("sessions.toml"))
+    }
+
+    /// Reads the sessions file, starting afresh if there's none.
+    pub fn load() -> Result<Self> {
+        match Self::path() {
+            Some(path) => Self::read(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// No sessions, which aren't saved either, for when the sessions file
+    /// can't be read.
+    pub fn unsaved() -> Self {
+        Self {
+            unsaved: true,
+            ..Default::default()
+        }
+    }
+
+    fn read(path: &Path) -> Result<Self> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(Error::InvalidSessions(path.into(), e.to_string())),
+        };
+        toml::from_str(&text)
+            .map_err(|e| Error::InvalidSessions(path.into(), e.message().to_string()))
+    }
+
+    /// The session of the file at `path`, unless it was modified since.
+    pub fn get(&self, path: &Path) -> Option<&Session> {
+        let path = fs::canonicalize(path).ok()?;
+        let session = self.files.get(&path)?;
+        (modified(&path) == Some(session.modified)).then_some(session)
+    }
+
+    /// Remembers `session` as that of the file at `path`.
+    pub fn set(&mut self, path: &Path, mut session: Session) {
+        let Ok(path) = fs::canonicalize(path) else {
+            return;
+        };
+        let Some(modified) = modified(&path) else {
+            return;
+        };
+        session.modified = modified;
+        session.saved = now();
+        self.files.insert(path, session);
+        while self.files.len() > MAX_SESSIONS {
+            let oldest = self
+                .files
+                .iter()
+                .min_by_key(|(_, s)| s.saved)
+                .map(|(path, _)| path.clone());
+            if let Some(path) = oldest {
+                self.files.remove(&path);
+            }
+        }
+    }
+
+    /// Writes the sessions file, unless it couldn't be read.
+    pub fn save(&self) -> io::Result<()> {
+        match Self::path() {
+            Some(path) if !self.unsaved => self.write(&path),
+            _ => Ok(()),
+        }
+    }
+
+    /// Writes a temporary file next to `path` first, which only replaces it
+    /// once complete, so a crash can't leave it half-written.
+    fn write(&self, path: &Path) -> io::Result<()> {
+        let dir = path.parent().unwrap_or(Path::new("."));
+        fs::create_dir_all(dir)?;
+        let text = toml::to_string(self).map_err(io::Error::other)?;
+        let mut file = NamedTempFile::new_in(dir)?;
+        file.write_all(text.as_bytes())?;
+        file.persist(path)?;
+        Ok(())
+    }
+}
+
+/// When the file at `path` was last modified, in nanoseconds since the epoch.
+///
+/// Tables and partitioned directories were last modified along with the
+/// newest file or directory in them, e.g. the last commit of a Delta log, as
+/// adding files doesn't change the time of the table's own directory.
+fn modified(path: &Path) -> Option<u64> {
+    newest(path, &fs::metadata(path).ok()?)
+}
+
+fn newest(path: &Path, metadata: &fs::Metadata) -> Option<u64> {
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    let mut newest = modified.as_nanos() as u64;
+    if metadata.is_dir() {
+        for entry in fs::read_dir(path).ok()?.flatten() {
+            // Symbolic links aren't followed, so can't loop.
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            newest = newest.max(self::newest(&entry.path(), &metadata)?);
+        }
+    }
+    Some(newest)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, time::Duration};
+
+    use super::*;
+
+    #[test]
+    fn remembers_unmodified_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.csv");
+        fs::write(&path, "id\n1\n").unwrap();
+        let mut sessions = Sessions::default();
+        assert!(sessions.get(&path).is_none());
+
+        let session = Session {
+            row: 42,
+            column: Some("id".to_string()),
+            ..Default::default()
+        };
+        sessions.set(&path, session);
+        // By another path to the same file.
+        let session = sessions
+            .get(&dir.path().join(".").join("data.csv"))
+            .unwrap();
+        assert_eq!(session.row, 42);
+        assert_eq!(session.column.as_deref(), Some("id"));
+
+        // The rows saved may have changed since, even within the same second.
+        let modified = fs::metadata(&path).unwrap().modified().unwrap() + Duration::from_millis(1);
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        assert!(sessions.get(&path).is_none());
+    }
+
+    #[test]
+    fn notices_changes_to_tables() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("_delta_log");
+        fs::create_dir(&log).unwrap();
+        fs::write(log.join("00000000000000000000.json"), "{}").unwrap();
+        let mut sessions = Sessions::default();
+        sessions.set(
+            dir.path(),
+            Session {
+                row: 42,
+                ..Default::default()
+            },
+        );
+        assert_eq!(sessions.get(dir.path()).unwrap().row, 42);
+
+        // A commit only changes the time of the log.
+        let commit = log.join("00000000000000000001.json");
+        fs::write(&commit, "{}").unwrap();
+        let modified = SystemTime::now() + Duration::from_millis(1);
+        File::options()
+            .write(true)
+            .open(&commit)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        assert!(sessions.get(dir.path()).is_none());
+    }
+
+    #[test]
+    fn saves_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = dir.path().join("data.csv");
+        fs::write(&data, "id\n1\n").unwrap();
+        let path = dir.path().join("state").join("sessions.toml");
+        assert!(Sessions::read(&path).unwrap().files.is_empty());
+
+        let mut sessions = Sessions::default();
+        sessions.set(
+            &data,
+            Session {
+                row: 7,
+                ..Default::default()
+            },
+        );
+        sessions.write(&path).unwrap();
+        assert_eq!(Sessions::read(&path).unwrap().get(&data).unwrap().row, 7);
+        // Nothing's left besides the sessions file.
+        assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+
+        fs::write(&path, "not = [toml").unwrap();
+        assert!(matches!(
+            Sessions::read(&path),
+            Err(Error::InvalidSessions(..))
+        ));
+    }
+}
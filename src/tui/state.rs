@@ -1,12 +1,157 @@
-use std::sync::mpsc;
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BTreeMap, HashMap},
+    fs, io, mem,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{self, AtomicBool, AtomicU64},
+        mpsc, Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use arrow::{
+    array::{RecordBatch, RecordBatchReader},
+    json::LineDelimitedWriter,
+    util::display::{ArrayFormatter, FormatOptions},
+};
+use ratatui::{
+    crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind},
+    layout::{Direction, Position, Rect},
+    widgets::TableState,
+};
+
+use crate::{
+    app::Viewer,
+    diff::{data::DataDiff, schema::SchemaDiff},
+    export::export,
+    open_path,
+    profile::ColumnProfile,
+    query::QueryEngine,
+    query_engine,
+};
 
-use ratatui::widgets::TableState;
+/// Rows read at a time when profiling a column.
+const PROFILE_BATCH_SIZE: usize = 8192;
+/// How often profiling reports how far it's got.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+/// Width of the Data tab's columns, until resized.
+pub const DEFAULT_COLUMN_WIDTH: u16 = 21;
+/// Columns can't be dragged narrower than this.
+const MIN_COLUMN_WIDTH: u16 = 4;
+/// Or wider than this.
+const MAX_COLUMN_WIDTH: u16 = 200;
+/// Files run with `:source` can run others, but not endlessly.
+const MAX_SOURCE_DEPTH: usize = 8;
+
+/// Id of the next profile requested, by any buffer.
+static NEXT_PROFILE_REQUEST: AtomicU64 = AtomicU64::new(1);
+
+/// Where the last frame drew what the mouse can click.
+#[derive(Debug, Default)]
+pub struct ClickAreas {
+    pub tabs: Vec<(Rect, Tab)>,
+    /// Rows of the Metadata tab's schema table, below its header.
+    pub schema_rows: Rect,
+    /// Columns of the Data tab, header included, with their index in the
+    /// batch.
+    pub data_columns: Vec<(Rect, usize)>,
+    /// Rows of the Files tab's tree, below its header.
+    pub file_rows: Rect,
+    /// Panes of a split screen.
+    pub panes: Vec<Rect>,
+}
+
+/// Part of a split screen, showing a tab of an open file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Pane {
+    /// Position of the file in the buffer list.
+    pub buffer: usize,
+    pub tab: Tab,
+    /// Where the pane is in the file's rows, which panes showing the same
+    /// file don't share.
+    pub scroll: Scroll,
+}
+
+/// What's selected on the Data tab and how far it's scrolled.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Scroll {
+    /// Row selected, counting from the file's first.
+    pub row: usize,
+    /// Rows shown above the selected one.
+    pub height: usize,
+    pub selected_col: usize,
+    pub col_offset: usize,
+}
+
+/// A column of the Data tab whose header border is being dragged.
+#[derive(Debug)]
+struct Resize {
+    column: String,
+    start_x: u16,
+    start_width: u16,
+}
+
+/// What the Data tab shows of the file, as changed from the command line.
+#[derive(Clone, Debug, Default)]
+pub struct DataView {
+    /// Query run with `:sql`, whose results are shown instead of the file.
+    pub sql: Option<String>,
+    /// SQL predicate rows have to match.
+    pub filter: Option<String>,
+    /// Column the rows are sorted by, and whether in descending order.
+    pub sort: Option<(String, bool)>,
+    /// Columns hidden with `:toggle`.
+    pub hidden: Vec<String>,
+}
+
+impl DataView {
+    /// Whether the rows are read with SQL, rather than from the file.
+    pub fn is_query(&self) -> bool {
+        self.sql.is_some() || self.filter.is_some() || self.sort.is_some()
+    }
+
+    /// SQL selecting what this view shows of `table`, unless it's the file as
+    /// it is but for hidden columns.
+    fn query(&self, table: &str) -> Option<String> {
+        if !self.is_query() {
+            return None;
+        }
+        let mut query = "SELECT *".to_string();
+        if !self.hidden.is_empty() {
+            let hidden: Vec<_> = self.hidden.iter().map(|c| quote(c)).collect();
+            query += &format!(" EXCLUDE ({})", hidden.join(", "));
+        }
+        match &self.sql {
+            Some(sql) => query += &format!(" FROM ({sql})"),
+            None => query += &format!(" FROM {}", quote(table)),
+        }
+        if let Some(filter) = &self.filter {
+            query += &format!(" WHERE {filter}");
+        }
+        if let Some((column, descending)) = &self.sort {
+            let order = if *descending { "DESC" } else { "ASC" };
+            query += &format!(" ORDER BY {} {order}", quote(column));
+        }
+        Some(query)
+    }
+}
 
-use crate::app::Viewer;
+/// Quotes an SQL identifier.
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
 
 use super::{
-    command::{Command, ScrollType},
+    browser::{Browser, EntryKind},
+    clipboard::{markdown_escape, tsv_escape, Clipboard, RowFormat, SelectionFormat},
+    command::{Command, Keymap, ScrollType},
     event::Event,
+    palette::{self, Candidates, Completion, History},
+    session::{Session, Sessions},
+    theme,
     ui::{Tab, N_TABS},
     Result,
 };
@@ -17,7 +162,80 @@ pub struct State {
     pub tab: Tab,
     pub table_state: TableState,
     pub data_table_state: TableState,
+    pub partition_table_state: TableState,
+    pub history_table_state: TableState,
+    pub snapshot_table_state: TableState,
+    pub field_table_state: TableState,
+    pub file_table_state: TableState,
     pub chunk_ind: usize,
+    /// Set when comparing the opened file with another one.
+    pub diff: Option<SchemaDiff>,
+    pub diff_table_state: TableState,
+    /// Set when comparing rows of the opened file with another one.
+    pub data_diff: Option<DataDiff>,
+    pub row_diff_table_state: TableState,
+    pub row_diff_col_offset: usize,
+    /// Text typed after `:`, while the command line is open.
+    pub input: Option<String>,
+    /// Feedback on the last command, e.g. a failed query.
+    pub message: Option<String>,
+    /// Runs `:sql` queries, once the first one is run on a local file.
+    pub query_engine: Option<QueryEngine>,
+    /// Profile of the column last selected on the Profile tab.
+    pub profile: Option<ColumnProfile>,
+    /// Column being profiled in the background.
+    pub profiling: Option<String>,
+    /// Rows of the column being profiled scanned so far.
+    pub profile_progress: usize,
+    /// Rows to profile, all of them when `None`.
+    pub profile_limit: Option<usize>,
+    /// Id of the latest profile requested, unique across buffers, telling
+    /// those superseded since and those of other buffers apart.
+    profile_request: u64,
+    /// Set to stop the profile being computed.
+    profile_cancelled: Arc<AtomicBool>,
+    pub keymap: Keymap,
+    /// Whether the key bindings are shown over the current tab.
+    pub help: bool,
+    /// Widths of the Data tab's columns resized with the mouse, by name.
+    pub column_widths: HashMap<String, u16>,
+    pub click_areas: ClickAreas,
+    resize: Option<Resize>,
+    pub clipboard: Clipboard,
+    /// Cell where the visual selection started, as row and column of the
+    /// batch, while selecting.
+    pub visual: Option<(usize, usize)>,
+    pub history: History,
+    /// Completions of the command line, best first.
+    pub completions: Vec<Completion>,
+    /// Completion the command line was last completed to with tab.
+    pub completion: Option<usize>,
+    pub view: DataView,
+    /// Files being run with `:source`, one within another.
+    sourcing: usize,
+    /// The other open files, in the order of the buffer list but for this
+    /// one.
+    pub buffers: Vec<State>,
+    /// Position of this file in the buffer list.
+    pub buffer_index: usize,
+    /// Entry selected in the buffer list, while it's shown.
+    pub buffer_list: Option<usize>,
+    /// Directory tree of the Files tab, listed when first shown.
+    pub browser: Option<Browser>,
+    /// How the files viewed were last left, shared by every buffer.
+    pub sessions: Sessions,
+    /// Rows marked with `m`, by letter.
+    pub bookmarks: BTreeMap<char, usize>,
+    /// `mark` or `jump-to-mark`, while waiting for the letter.
+    pub pending_mark: Option<Command>,
+    /// Panes the screen is split into, none when it isn't.
+    pub panes: Vec<Pane>,
+    /// Pane showing this file, which keys act on.
+    pub active_pane: usize,
+    /// Whether the panes are side by side or stacked.
+    pub split: Direction,
+    /// Whether the other panes follow the row selected in this one.
+    pub sync_scroll: bool,
 }
 
 impl State {
@@ -28,40 +246,204 @@ impl State {
             tab: Default::default(),
             table_state: TableState::default().with_selected(Some(0)),
             data_table_state: TableState::default().with_selected(Some(0)),
+            partition_table_state: TableState::default().with_selected(Some(0)),
+            history_table_state: TableState::default().with_selected(Some(0)),
+            snapshot_table_state: TableState::default().with_selected(Some(0)),
+            field_table_state: TableState::default().with_selected(Some(0)),
+            file_table_state: TableState::default().with_selected(Some(0)),
             chunk_ind: 0,
+            diff: None,
+            diff_table_state: TableState::default().with_selected(Some(0)),
+            data_diff: None,
+            row_diff_table_state: TableState::default().with_selected(Some(0)),
+            row_diff_col_offset: 0,
+            input: None,
+            message: None,
+            query_engine: None,
+            profile: None,
+            profiling: None,
+            profile_progress: 0,
+            profile_limit: None,
+            profile_request: 0,
+            profile_cancelled: Arc::new(AtomicBool::new(false)),
+            keymap: Keymap::default(),
+            help: false,
+            column_widths: HashMap::new(),
+            click_areas: ClickAreas::default(),
+            resize: None,
+            clipboard: Clipboard::default(),
+            visual: None,
+            history: History::default(),
+            completions: vec![],
+            completion: None,
+            view: DataView::default(),
+            sourcing: 0,
+            buffers: vec![],
+            buffer_index: 0,
+            buffer_list: None,
+            browser: None,
+            sessions: Sessions::default(),
+            bookmarks: BTreeMap::new(),
+            pending_mark: None,
+            panes: vec![],
+            active_pane: 0,
+            split: Direction::Horizontal,
+            sync_scroll: false,
         }
     }
 
+    /// Opens the files at `paths` in buffers after this one.
+    pub fn with_buffers(mut self, paths: &[PathBuf]) -> Result<Self> {
+        for path in paths {
+            self.buffers.push(Self::new(open_path(path)?));
+        }
+        Ok(self)
+    }
+
+    /// Recalls the lines run on the command line in earlier sessions.
+    pub fn with_history(mut self, history: History) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// Goes back to how each open file was last left, unless it changed since.
+    pub fn with_sessions(mut self, sessions: Sessions) -> Self {
+        for buffer in self.buffers.iter_mut() {
+            buffer.restore_session(&sessions);
+        }
+        self.restore_session(&sessions);
+        self.sessions = sessions;
+        self
+    }
+
+    /// Shows the results of `sql`, which the Data tab was set to, such that
+    /// they can be filtered and sorted.
+    pub fn with_sql(mut self, sql: &str) -> Self {
+        self.view.sql = Some(sql.to_string());
+        self.tab = Tab::Data;
+        self
+    }
+
+    /// Copies to the clipboard as set in the config file.
+    pub fn with_clipboard(mut self, clipboard: Clipboard) -> Self {
+        self.clipboard = clipboard;
+        self
+    }
+
+    /// Binds keys as set in the config file, rather than the defaults.
+    pub fn with_keymap(mut self, keymap: Keymap) -> Self {
+        self.keymap = keymap;
+        self
+    }
+
+    /// Profiles only the first `limit` rows of a column, when given.
+    pub fn with_profile_limit(mut self, limit: Option<usize>) -> Self {
+        self.profile_limit = limit;
+        self
+    }
+
+    /// Enables `:sql` queries against the tables registered in `engine`.
+    pub fn with_query_engine(mut self, engine: QueryEngine) -> Self {
+        self.query_engine = Some(engine);
+        self
+    }
+
+    /// Opens on the Diff tab, comparing the viewed file with another one.
+    pub fn with_diff(mut self, diff: SchemaDiff) -> Self {
+        self.diff = Some(diff);
+        self.tab = Tab::Diff;
+        self
+    }
+
+    /// Adds the Row diff tab, listing rows that differ from another file.
+    pub fn with_data_diff(mut self, data_diff: DataDiff) -> Self {
+        self.data_diff = Some(data_diff);
+        self
+    }
+
+    /// Tabs that apply to the opened file, in display order.
+    pub fn tabs(&self) -> Vec<Tab> {
+        (0..=N_TABS)
+            .map(Tab::from)
+            .filter(|tab| match tab {
+                Tab::Partitions => self.viewer.partitions().is_some(),
+                Tab::History => self.viewer.delta().is_some(),
+                Tab::Snapshots | Tab::Schemas | Tab::DataFiles => self.viewer.iceberg().is_some(),
+                Tab::Diff => self.diff.is_some(),
+                Tab::RowDiff => self.data_diff.is_some(),
+                _ => true,
+            })
+            .collect()
+    }
+
     pub fn run_command(
         &mut self,
         command: Command,
         event_sender: mpsc::Sender<Event>,
     ) -> Result<()> {
         match command {
+            // Closes the help or cancels the selection rather than closing
+            // the file.
+            Command::Exit if self.help => self.help = false,
+            Command::Exit | Command::Buffers if self.buffer_list.is_some() => {
+                self.buffer_list = None
+            }
+            Command::Exit if self.visual.is_some() => self.visual = None,
             Command::Exit => {
                 self.running = false;
             }
+            Command::Help => self.help = !self.help,
+            Command::Buffers => self.buffer_list = Some(self.buffer_index),
+            Command::Next(ScrollType::Vertical) if self.buffer_list.is_some() => {
+                self.buffer_list = self.buffer_list.map(|i| (i + 1) % self.num_buffers());
+            }
+            Command::Previous(ScrollType::Vertical) if self.buffer_list.is_some() => {
+                let n = self.num_buffers();
+                self.buffer_list = self.buffer_list.map(|i| (i + n - 1) % n);
+            }
+            Command::Select if self.buffer_list.is_some() => {
+                if let Some(i) = self.buffer_list.take() {
+                    self.switch_buffer(i);
+                }
+            }
+            Command::CloseBuffer => self.close_buffer(),
+            Command::Split(direction) => self.split_pane(direction),
+            Command::ClosePane => self.close_pane(),
+            Command::OnlyPane => self.panes.clear(),
+            Command::SyncScroll => {
+                self.sync_scroll = !self.sync_scroll;
+                let state = if self.sync_scroll { "on" } else { "off" };
+                self.message = Some(format!("sync scroll {state}"));
+            }
             Command::Next(scroll_type) => match scroll_type {
                 ScrollType::Tab => {
-                    let new_tab = self.tab as usize + 1;
-                    if new_tab > N_TABS {
-                        self.tab = 0.into();
-                    } else {
-                        self.tab = new_tab.into();
-                    }
+                    let tabs = self.tabs();
+                    let i = tabs.iter().position(|t| *t == self.tab).unwrap_or(0);
+                    self.tab = tabs[(i + 1) % tabs.len()];
+                }
+                ScrollType::Buffer => {
+                    self.switch_buffer((self.buffer_index + 1) % self.num_buffers())
                 }
+                ScrollType::Pane if self.panes.len() > 1 => {
+                    self.focus_pane((self.active_pane + 1) % self.panes.len())
+                }
+                ScrollType::Pane => {}
                 ScrollType::Vertical => match self.tab {
                     Tab::Metadata => {
+                        let n = self.viewer.num_cols.max(1);
                         if let Some(selection) = self.table_state.selected_mut() {
-                            *selection = (*selection + 1) % self.viewer.num_cols;
+                            *selection = (*selection + 1) % n;
                         }
                     }
                     Tab::Data => {
-                        if self.viewer.selected_row == (self.viewer.batch_size - 1) {
-                            // TODO: what to do when reached end of batch?
-                            // read in next batch but might need to be in separate thread?
-                            self.viewer.batch = self.viewer.reader.next().unwrap()?;
-                            // self.viewer.reader.
+                        if self.viewer.selected_row + 1 >= self.viewer.batch.num_rows() {
+                            // TODO: read the next batch in a separate thread?
+                            match self.viewer.next_batch() {
+                                // Selections don't span batches.
+                                Ok(true) => self.visual = None,
+                                Ok(false) => {}
+                                Err(e) => self.message = Some(e.to_string()),
+                            }
                         } else {
                             self.viewer.selected_row += 1;
                             if self.viewer.selected_row
@@ -71,13 +453,51 @@ impl State {
                             }
                         }
                     }
+                    Tab::Profile => {}
+                    Tab::Partitions => {
+                        let n = self.num_partition_rows();
+                        if let Some(selection) = self.partition_table_state.selected_mut() {
+                            *selection = (*selection + 1) % n;
+                        }
+                    }
+                    Tab::History => {
+                        let n = self.num_history_rows();
+                        if let Some(selection) = self.history_table_state.selected_mut() {
+                            *selection = (*selection + 1) % n;
+                        }
+                    }
+                    Tab::Snapshots | Tab::Schemas | Tab::DataFiles => {
+                        let (n, table_state) = self.iceberg_rows();
+                        if let Some(selection) = table_state.selected_mut() {
+                            *selection = (*selection + 1) % n;
+                        }
+                    }
+                    Tab::Diff => {
+                        let n = self.num_diff_rows();
+                        if let Some(selection) = self.diff_table_state.selected_mut() {
+                            *selection = (*selection + 1) % n;
+                        }
+                    }
+                    Tab::RowDiff => {
+                        let n = self.num_row_diff_rows();
+                        if let Some(selection) = self.row_diff_table_state.selected_mut() {
+                            *selection = (*selection + 1) % n;
+                        }
+                    }
+                    Tab::Files => {
+                        let browser = self.browser();
+                        let n = browser.entries.len().max(1);
+                        if let Some(selection) = browser.table_state.selected_mut() {
+                            *selection = (*selection + 1) % n;
+                        }
+                    }
                 },
                 ScrollType::Horizontal => match self.tab {
-                    Tab::Metadata => {
+                    Tab::Metadata if self.viewer.num_row_groups > 0 => {
                         self.chunk_ind = (self.chunk_ind + 1) % self.viewer.num_row_groups;
                     }
-                    Tab::Data => {
-                        if self.viewer.selected_col == (self.viewer.num_cols - 1) {
+                    Tab::Data | Tab::Profile => {
+                        if self.viewer.selected_col + 1 >= self.viewer.batch.num_columns() {
                             self.viewer.selected_col = 0;
                             self.viewer.col_offset = 0;
                         } else {
@@ -89,20 +509,40 @@ impl State {
                             }
                         }
                     }
+                    Tab::RowDiff => {
+                        let n = self.data_diff.as_ref().map_or(1, |d| d.columns.len());
+                        self.row_diff_col_offset = (self.row_diff_col_offset + 1).min(n - 1);
+                    }
+                    Tab::Files => self.browser().expand(),
+                    Tab::Metadata
+                    | Tab::Partitions
+                    | Tab::History
+                    | Tab::Snapshots
+                    | Tab::Schemas
+                    | Tab::DataFiles
+                    | Tab::Diff => {}
                 },
             },
             Command::Previous(scroll_type) => match scroll_type {
                 ScrollType::Tab => {
-                    self.tab = (self.tab as usize).checked_sub(1).unwrap_or(N_TABS).into();
+                    let tabs = self.tabs();
+                    let i = tabs.iter().position(|t| *t == self.tab).unwrap_or(0);
+                    self.tab = tabs[(i + tabs.len() - 1) % tabs.len()];
                 }
+                ScrollType::Buffer => {
+                    let n = self.num_buffers();
+                    self.switch_buffer((self.buffer_index + n - 1) % n)
+                }
+                ScrollType::Pane if self.panes.len() > 1 => {
+                    let n = self.panes.len();
+                    self.focus_pane((self.active_pane + n - 1) % n)
+                }
+                ScrollType::Pane => {}
                 ScrollType::Vertical => match self.tab {
                     Tab::Metadata => {
+                        let n = self.viewer.num_cols.max(1);
                         if let Some(selection) = self.table_state.selected_mut() {
-                            *selection = if *selection == 0 {
-                                self.viewer.num_cols - 1
-                            } else {
-                                (*selection - 1) % self.viewer.num_cols
-                            }
+                            *selection = (*selection + n - 1) % n;
                         }
                     }
 
@@ -114,31 +554,1380 @@ impl State {
                             }
                         }
                     }
+                    Tab::Profile => {}
+                    Tab::Partitions => {
+                        let n = self.num_partition_rows();
+                        if let Some(selection) = self.partition_table_state.selected_mut() {
+                            *selection = (*selection + n - 1) % n;
+                        }
+                    }
+                    Tab::History => {
+                        let n = self.num_history_rows();
+                        if let Some(selection) = self.history_table_state.selected_mut() {
+                            *selection = (*selection + n - 1) % n;
+                        }
+                    }
+                    Tab::Snapshots | Tab::Schemas | Tab::DataFiles => {
+                        let (n, table_state) = self.iceberg_rows();
+                        if let Some(selection) = table_state.selected_mut() {
+                            *selection = (*selection + n - 1) % n;
+                        }
+                    }
+                    Tab::Diff => {
+                        let n = self.num_diff_rows();
+                        if let Some(selection) = self.diff_table_state.selected_mut() {
+                            *selection = (*selection + n - 1) % n;
+                        }
+                    }
+                    Tab::RowDiff => {
+                        let n = self.num_row_diff_rows();
+                        if let Some(selection) = self.row_diff_table_state.selected_mut() {
+                            *selection = (*selection + n - 1) % n;
+                        }
+                    }
+                    Tab::Files => {
+                        let browser = self.browser();
+                        let n = browser.entries.len().max(1);
+                        if let Some(selection) = browser.table_state.selected_mut() {
+                            *selection = (*selection + n - 1) % n;
+                        }
+                    }
                 },
                 ScrollType::Horizontal => match self.tab {
-                    Tab::Metadata => {
+                    Tab::Metadata if self.viewer.num_row_groups > 0 => {
                         self.chunk_ind = if self.chunk_ind == 0 {
                             self.viewer.num_row_groups - 1
                         } else {
                             (self.chunk_ind - 1) % self.viewer.num_row_groups
                         }
                     }
-                    Tab::Data => {
+                    Tab::Data | Tab::Profile => {
                         if self.viewer.selected_col != 0 {
                             self.viewer.selected_col -= 1;
                             if self.viewer.selected_col < self.viewer.col_offset {
                                 self.viewer.col_offset -= 1;
                             }
                         } else {
-                            self.viewer.selected_col = self.viewer.num_cols - 1;
+                            let num_cols = self.viewer.batch.num_columns();
+                            self.viewer.selected_col = num_cols.saturating_sub(1);
                             self.viewer.col_offset =
-                                self.viewer.num_cols - self.viewer.visible_cols;
+                                num_cols.saturating_sub(self.viewer.visible_cols);
                         }
                     }
+                    Tab::RowDiff => {
+                        self.row_diff_col_offset = self.row_diff_col_offset.saturating_sub(1);
+                    }
+                    Tab::Files => self.browser().collapse(),
+                    Tab::Metadata
+                    | Tab::Partitions
+                    | Tab::History
+                    | Tab::Snapshots
+                    | Tab::Schemas
+                    | Tab::DataFiles
+                    | Tab::Diff => {}
                 },
             },
+            Command::CommandLine => {
+                self.input = Some(String::new());
+                self.message = None;
+                self.update_completions();
+            }
+            Command::Select => match self.tab {
+                Tab::DataFiles => self.open_data_file(),
+                Tab::Files => self.open_browser_entry(),
+                _ => {}
+            },
+            Command::Yank
+            | Command::YankRow
+            | Command::Visual
+            | Command::Mark
+            | Command::JumpToMark
+                if self.tab != Tab::Data => {}
+            Command::Yank => self.yank()?,
+            Command::YankRow => self.yank_row()?,
+            Command::Visual => {
+                self.visual = match self.visual {
+                    Some(_) => None,
+                    None => Some((self.viewer.selected_row, self.viewer.selected_col)),
+                }
+            }
+            Command::Mark => {
+                self.pending_mark = Some(command);
+                self.message = Some("mark the row as (a-z, A-Z)".to_string());
+            }
+            Command::JumpToMark => {
+                self.pending_mark = Some(command);
+                self.message = Some(match self.bookmarks.is_empty() {
+                    true => "jump to mark (none set)".to_string(),
+                    false => {
+                        let marks: String = self.bookmarks.keys().collect();
+                        format!("jump to mark ({marks})")
+                    }
+                });
+            }
             Command::Nothing => {}
         }
+        if self.tab == Tab::Profile {
+            self.profile_selected_column(event_sender);
+        }
+        self.sync_panes();
+        Ok(())
+    }
+
+    /// Scrolls with the wheel, horizontally with shift held, selects what's
+    /// clicked and resizes Data tab columns by dragging their header border.
+    pub fn handle_mouse(
+        &mut self,
+        event: MouseEvent,
+        event_sender: mpsc::Sender<Event>,
+    ) -> Result<()> {
+        if self.input.is_some() {
+            return Ok(());
+        }
+        let scroll = if event.modifiers.contains(KeyModifiers::SHIFT) {
+            ScrollType::Horizontal
+        } else {
+            ScrollType::Vertical
+        };
+        let position = Position::new(event.column, event.row);
+        match event.kind {
+            MouseEventKind::ScrollDown => self.run_command(Command::Next(scroll), event_sender)?,
+            MouseEventKind::ScrollUp => {
+                self.run_command(Command::Previous(scroll), event_sender)?
+            }
+            MouseEventKind::ScrollRight => {
+                self.run_command(Command::Next(ScrollType::Horizontal), event_sender)?
+            }
+            MouseEventKind::ScrollLeft => {
+                self.run_command(Command::Previous(ScrollType::Horizontal), event_sender)?
+            }
+            MouseEventKind::Down(MouseButton::Left) if self.help => self.help = false,
+            MouseEventKind::Down(MouseButton::Left) if self.buffer_list.is_some() => {
+                self.buffer_list = None
+            }
+            MouseEventKind::Down(MouseButton::Left) => self.click(position, event_sender),
+            MouseEventKind::Drag(MouseButton::Left) => {
+                if let Some(resize) = &self.resize {
+                    let width = (resize.start_width as i32 + event.column as i32
+                        - resize.start_x as i32)
+                        .clamp(MIN_COLUMN_WIDTH as i32, MAX_COLUMN_WIDTH as i32);
+                    self.column_widths
+                        .insert(resize.column.clone(), width as u16);
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => self.resize = None,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn click(&mut self, position: Position, event_sender: mpsc::Sender<Event>) {
+        // A click on another pane only focuses it.
+        let pane = self
+            .click_areas
+            .panes
+            .iter()
+            .position(|r| r.contains(position));
+        if let Some(i) = pane.filter(|i| *i != self.active_pane) {
+            self.focus_pane(i);
+            return;
+        }
+        if let Some((_, tab)) = self
+            .click_areas
+            .tabs
+            .iter()
+            .find(|(r, _)| r.contains(position))
+        {
+            self.tab = *tab;
+        } else if self.tab == Tab::Metadata && self.click_areas.schema_rows.contains(position) {
+            let row =
+                self.table_state.offset() + (position.y - self.click_areas.schema_rows.y) as usize;
+            if row < self.viewer.num_cols {
+                self.table_state.select(Some(row));
+            }
+        } else if self.tab == Tab::Files && self.click_areas.file_rows.contains(position) {
+            let y = self.click_areas.file_rows.y;
+            let browser = self.browser();
+            let row = browser.table_state.offset() + (position.y - y) as usize;
+            if row < browser.entries.len() {
+                browser.table_state.select(Some(row));
+            }
+        } else if self.tab == Tab::Data {
+            let Some((rect, col)) = self
+                .click_areas
+                .data_columns
+                .iter()
+                .find(|(r, _)| r.contains(position))
+                .copied()
+            else {
+                return;
+            };
+            self.viewer.selected_col = col;
+            if position.y > rect.y {
+                let row = self.viewer.row_offset + (position.y - rect.y - 1) as usize;
+                if row < self.viewer.batch.num_rows() {
+                    self.viewer.selected_row = row;
+                }
+            } else if position.x == rect.right() - 1 {
+                // The blank cell closing a header is its border.
+                let column = self.viewer.batch.schema().field(col).name().clone();
+                self.resize = Some(Resize {
+                    start_x: position.x,
+                    start_width: rect.width,
+                    column,
+                });
+            }
+        }
+        if self.tab == Tab::Profile {
+            self.profile_selected_column(event_sender);
+        }
+    }
+
+    /// Rows and columns of the visual selection, or of the selected cell.
+    pub fn selection(&self) -> (Range<usize>, Range<usize>) {
+        let (row, col) = (self.viewer.selected_row, self.viewer.selected_col);
+        let (anchor_row, anchor_col) = self.visual.unwrap_or((row, col));
+        (
+            row.min(anchor_row)..row.max(anchor_row) + 1,
+            col.min(anchor_col)..col.max(anchor_col) + 1,
+        )
+    }
+
+    /// Copies the selected cell, or the visual selection, ending it.
+    fn yank(&mut self) -> Result<()> {
+        let batch = &self.viewer.batch;
+        if batch.num_rows() == 0 {
+            return Ok(());
+        }
+        let (rows, cols) = self.selection();
+        let values = format_cells(batch, rows.clone(), cols.clone())?;
+        let text = if self.visual.is_none() {
+            values[0][0].clone()
+        } else {
+            match self.clipboard.selection_format {
+                SelectionFormat::Tsv => values
+                    .iter()
+                    .map(|row| {
+                        row.iter()
+                            .map(|v| tsv_escape(v))
+                            .collect::<Vec<_>>()
+                            .join("\t")
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                SelectionFormat::Markdown => {
+                    let schema = batch.schema();
+                    let header: Vec<_> = cols
+                        .clone()
+                        .map(|i| markdown_escape(schema.field(i).name()))
+                        .collect();
+                    let mut lines = vec![
+                        format!("| {} |", header.join(" | ")),
+                        format!("|{}", "---|".repeat(header.len())),
+                    ];
+                    lines.extend(values.iter().map(|row| {
+                        let row: Vec<_> = row.iter().map(|v| markdown_escape(v)).collect();
+                        format!("| {} |", row.join(" | "))
+                    }));
+                    lines.join("\n")
+                }
+            }
+        };
+        self.clipboard.copy(&text)?;
+        self.message = Some(match (rows.len(), cols.len()) {
+            (1, 1) => "Copied 1 cell".to_string(),
+            (r, c) => format!("Copied {r} rows × {c} columns"),
+        });
+        self.visual = None;
+        Ok(())
+    }
+
+    /// Copies the selected row as TSV or JSON.
+    fn yank_row(&mut self) -> Result<()> {
+        let batch = &self.viewer.batch;
+        let row = self.viewer.selected_row;
+        if row >= batch.num_rows() {
+            return Ok(());
+        }
+        let text = match self.clipboard.row_format {
+            RowFormat::Tsv => format_cells(batch, row..row + 1, 0..batch.num_columns())?[0]
+                .iter()
+                .map(|v| tsv_escape(v))
+                .collect::<Vec<_>>()
+                .join("\t"),
+            RowFormat::Json => {
+                let mut writer = LineDelimitedWriter::new(vec![]);
+                writer.write(&batch.slice(row, 1))?;
+                writer.finish()?;
+                String::from_utf8_lossy(&writer.into_inner())
+                    .trim_end()
+                    .to_string()
+            }
+        };
+        self.clipboard.copy(&text)?;
+        self.message = Some("Copied 1 row".to_string());
+        Ok(())
+    }
+
+    /// Starts profiling the column selected in the Data tab in the background,
+    /// unless it already is or has been.
+    fn profile_selected_column(&mut self, event_sender: mpsc::Sender<Event>) {
+        let schema = self.viewer.batch.schema();
+        let Some(field) = schema.fields().get(self.viewer.selected_col) else {
+            return;
+        };
+        let column = field.name().clone();
+        if self.profiling.as_ref() == Some(&column)
+            || self.profile.as_ref().is_some_and(|p| p.column == column)
+        {
+            return;
+        }
+        // Filtered, sorted and queried rows are profiled as the Data tab shows
+        // them, through the query engine, and the file's rows straight from
+        // it, row groups in parallel when it's local.
+        let table = self.query_engine.as_ref().and_then(|e| e.tables.first());
+        let query = table.and_then(|t| self.view.query(t));
+        let reader = match (&self.query_engine, query) {
+            (Some(engine), Some(query)) => {
+                let query = format!("SELECT {} FROM ({query})", quote(&column));
+                match engine.sql(&query) {
+                    Ok(reader) => Some(reader),
+                    Err(e) => {
+                        self.message = Some(e.to_string());
+                        return;
+                    }
+                }
+            }
+            _ => None,
+        };
+        self.cancel_profile();
+        self.profiling = Some(column.clone());
+        self.profile_progress = 0;
+
+        let request = self.profile_request;
+        let cancelled = Arc::clone(&self.profile_cancelled);
+        let source = self.viewer.source.clone();
+        let limit = self.profile_limit;
+        thread::spawn(move || {
+            let mut reported = Instant::now();
+            let progress = |rows| {
+                if cancelled.load(atomic::Ordering::Relaxed) {
+                    return false;
+                }
+                if reported.elapsed() >= PROGRESS_INTERVAL {
+                    reported = Instant::now();
+                    let _ = event_sender.send(Event::ProfileProgress(request, rows));
+                }
+                true
+            };
+            let profile = match reader {
+                Some(reader) => ColumnProfile::compute(reader, &column, limit, progress),
+                None => {
+                    let columns = [column.clone()];
+                    match source.row_group_readers(Some(&columns), PROFILE_BATCH_SIZE) {
+                        Some(row_groups) => row_groups.and_then(|row_groups| {
+                            ColumnProfile::compute_parallel(row_groups, &column, limit, progress)
+                        }),
+                        None => source.reader(Some(&columns), PROFILE_BATCH_SIZE).and_then(
+                            |(_, reader)| ColumnProfile::compute(reader, &column, limit, progress),
+                        ),
+                    }
+                }
+            };
+            // The receiver is gone once the TUI exits.
+            let _ = event_sender.send(Event::Profile(request, Box::new(profile)));
+        });
+    }
+
+    /// Stops the profile being computed, if any, such that nothing it sends
+    /// is shown.
+    fn cancel_profile(&mut self) {
+        self.profile_cancelled
+            .store(true, atomic::Ordering::Relaxed);
+        self.profile_cancelled = Arc::new(AtomicBool::new(false));
+        self.profile_request = NEXT_PROFILE_REQUEST.fetch_add(1, atomic::Ordering::Relaxed);
+        self.profiling = None;
+    }
+
+    /// Forgets the profile shown and stops the one being computed, as the
+    /// rows they're of changed.
+    fn invalidate_profile(&mut self) {
+        self.cancel_profile();
+        self.profile = None;
+    }
+
+    /// The open file, shown or not, whose latest profile is `request`,
+    /// unless it was cancelled since.
+    fn profile_requester(&mut self, request: u64) -> Option<&mut State> {
+        if self.profile_request == request {
+            return Some(self).filter(|s| s.profiling.is_some());
+        }
+        self.buffers
+            .iter_mut()
+            .find(|b| b.profile_request == request && b.profiling.is_some())
+    }
+
+    /// Notes how far the profile `request` has got.
+    pub fn set_profile_progress(&mut self, request: u64, rows: usize) {
+        if let Some(buffer) = self.profile_requester(request) {
+            buffer.profile_progress = rows;
+        }
+    }
+
+    /// Shows the profile `request` computed in the background, or why it
+    /// failed, in the file it was requested for.
+    pub fn set_profile(&mut self, request: u64, profile: Result<ColumnProfile>) {
+        let Some(buffer) = self.profile_requester(request) else {
+            return;
+        };
+        buffer.profiling = None;
+        match profile {
+            Ok(profile) => buffer.profile = Some(profile),
+            Err(e) => buffer.message = Some(e.to_string()),
+        }
+    }
+
+    /// Edits the open command line, running it on enter, completing it with
+    /// tab and going through the history with up and down.
+    pub fn edit_input(
+        &mut self,
+        key_event: KeyEvent,
+        event_sender: mpsc::Sender<Event>,
+    ) -> Result<()> {
+        let Some(input) = self.input.as_mut() else {
+            return Ok(());
+        };
+        match key_event.code {
+            KeyCode::Esc => self.input = None,
+            KeyCode::Backspace if input.is_empty() => self.input = None,
+            KeyCode::Backspace => {
+                input.pop();
+                self.update_completions();
+            }
+            KeyCode::Enter => {
+                let line = self.input.take().unwrap_or_default();
+                // The history is a convenience, not worth failing a command
+                // over.
+                let _ = self.history.push(&line);
+                self.execute_line(&line, event_sender)?;
+            }
+            KeyCode::Tab | KeyCode::BackTab if !self.completions.is_empty() => {
+                let n = self.completions.len();
+                let i = match (self.completion, key_event.code) {
+                    (None, KeyCode::Tab) => 0,
+                    (None, _) => n - 1,
+                    (Some(i), KeyCode::Tab) => (i + 1) % n,
+                    (Some(i), _) => (i + n - 1) % n,
+                };
+                *input = self.completions[i].line.clone();
+                self.completion = Some(i);
+                // Carries on with e.g. the arguments of the only command, or
+                // the entries of the only directory, that matched.
+                if n == 1 {
+                    self.update_completions();
+                }
+            }
+            KeyCode::Up => {
+                if let Some(line) = self.history.older(input) {
+                    *input = line.to_string();
+                    self.completions.clear();
+                }
+            }
+            KeyCode::Down => {
+                if let Some(line) = self.history.newer() {
+                    *input = line.to_string();
+                    self.completions.clear();
+                }
+            }
+            KeyCode::Char(c) => {
+                input.push(c);
+                self.update_completions();
+            }
+            _ => {}
+        }
         Ok(())
     }
+
+    fn update_completions(&mut self) {
+        self.completion = None;
+        self.history.reset();
+        let Some(input) = self.input.as_ref() else {
+            self.completions.clear();
+            return;
+        };
+        // Hidden columns can be toggled back.
+        let mut columns: Vec<_> = self
+            .viewer
+            .batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| f.name().clone())
+            .collect();
+        columns.extend(self.view.hidden.iter().cloned());
+        let candidates = Candidates {
+            columns,
+            themes: theme::names(),
+            buffers: (0..self.num_buffers())
+                .map(|i| self.buffer(i).viewer.file_stem.clone())
+                .collect(),
+        };
+        self.completions = palette::complete(input, &candidates);
+    }
+
+    /// Runs a line of the command line: one of [`palette::COMMANDS`], a row
+    /// number to go to, or a command keys can be bound to.
+    fn execute_line(&mut self, line: &str, event_sender: mpsc::Sender<Event>) -> Result<()> {
+        self.completions.clear();
+        let line = line.trim().trim_start_matches(':');
+        let (name, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let rest = rest.trim();
+        match name {
+            "" => {}
+            "q" | "quit" => self.running = false,
+            // As in vim.
+            _ if name.parse::<usize>().is_ok() => self.goto_line(name),
+            "goto" => self.goto_line(rest),
+            "sql" if rest.is_empty() => self.message = Some("expected a query".to_string()),
+            "sql" => {
+                let view = DataView {
+                    sql: Some(rest.to_string()),
+                    ..Default::default()
+                };
+                self.set_view(view, false);
+            }
+            "filter" => {
+                let view = DataView {
+                    filter: (!rest.is_empty()).then(|| rest.to_string()),
+                    ..self.view.clone()
+                };
+                self.set_view(view, false);
+            }
+            "sort" => {
+                let mut words = rest.split_whitespace();
+                let sort = match (words.next(), words.next(), words.next()) {
+                    (None, _, _) => None,
+                    (Some(column), None | Some("asc"), None) => Some((column.to_string(), false)),
+                    (Some(column), Some("desc"), None) => Some((column.to_string(), true)),
+                    _ => {
+                        self.message = Some("expected `sort <column> [asc|desc]`".to_string());
+                        return Ok(());
+                    }
+                };
+                let view = DataView {
+                    sort,
+                    ..self.view.clone()
+                };
+                self.set_view(view, false);
+            }
+            "toggle" => self.toggle_column(rest),
+            "reset" => self.set_view(DataView::default(), false),
+            "export" | "export!" if rest.is_empty() => {
+                self.message = Some("expected a path".to_string())
+            }
+            "export" | "export!" => {
+                let path = Path::new(rest);
+                let source = self.viewer.source.path();
+                let overwrite = name == "export!";
+                let exported = self
+                    .data_reader()
+                    .and_then(|r| export(r, path, source, overwrite));
+                self.message = Some(match exported {
+                    Ok(rows) => format!("Exported {rows} rows to {}", path.display()),
+                    Err(e) => e.to_string(),
+                });
+            }
+            "theme" => {
+                if let Err(e) = theme::set(rest) {
+                    self.message = Some(e);
+                }
+            }
+            "e" | "edit" | "open" if rest.is_empty() => {
+                self.message = Some("expected a path".to_string())
+            }
+            "e" | "edit" | "open" => {
+                if let Err(e) = self.open(Path::new(rest)) {
+                    self.message = Some(e.to_string());
+                }
+            }
+            "b" | "buffer" => self.switch_to_buffer(rest),
+            "sp" | "split" | "vs" | "vsplit" => {
+                let direction = match name {
+                    "sp" | "split" => Direction::Vertical,
+                    _ => Direction::Horizontal,
+                };
+                self.split_pane(direction);
+                if !rest.is_empty() {
+                    if let Err(e) = self.open(Path::new(rest)) {
+                        self.message = Some(e.to_string());
+                    }
+                }
+            }
+            // Short for commands keys can be bound to, as in vim.
+            "bn" | "bnext" => self.run_command(Command::Next(ScrollType::Buffer), event_sender)?,
+            "bp" | "bprevious" => {
+                self.run_command(Command::Previous(ScrollType::Buffer), event_sender)?
+            }
+            "ls" => self.run_command(Command::Buffers, event_sender)?,
+            "bd" | "bdelete" => self.run_command(Command::CloseBuffer, event_sender)?,
+            "source" => self.source(Path::new(rest), event_sender)?,
+            _ => match name.parse::<Command>() {
+                Ok(command) => self.run_command(command, event_sender)?,
+                Err(_) => self.message = Some(format!("unknown command `{name}`")),
+            },
+        }
+        self.sync_panes();
+        Ok(())
+    }
+
+    /// Reader over the rows the Data tab shows, from the start.
+    fn data_reader(&self) -> Result<Box<dyn RecordBatchReader + Send>> {
+        let table = self.query_engine.as_ref().and_then(|e| e.tables.first());
+        if let (Some(engine), Some(query)) =
+            (&self.query_engine, table.and_then(|t| self.view.query(t)))
+        {
+            return Ok(Box::new(engine.sql(&query)?));
+        }
+        let (_, reader) = self
+            .viewer
+            .source
+            .reader(self.shown_columns().as_deref(), self.viewer.batch_size)?;
+        Ok(reader)
+    }
+
+    /// Columns of the file the Data tab shows, all of them when `None`.
+    fn shown_columns(&self) -> Option<Vec<String>> {
+        (!self.view.hidden.is_empty()).then(|| {
+            self.viewer
+                .schema
+                .fields()
+                .iter()
+                .map(|f| f.name().clone())
+                .filter(|name| !self.view.hidden.contains(name))
+                .collect()
+        })
+    }
+
+    /// Reads the rows of the Data tab again, from the start.
+    fn rewind(&mut self) -> Result<()> {
+        let reader = self.data_reader()?;
+        self.viewer.set_data(reader)?;
+        // Filtered and queried rows can't be counted without reading them.
+        if !self.view.is_query() {
+            self.viewer.num_data_rows = self.viewer.num_source_rows();
+        }
+        self.visual = None;
+        Ok(())
+    }
+
+    /// The value of the selected cell, in full.
+    pub fn selected_value(&self) -> Option<String> {
+        let (row, col) = (self.viewer.selected_row, self.viewer.selected_col);
+        if row >= self.viewer.batch.num_rows() || col >= self.viewer.batch.num_columns() {
+            return None;
+        }
+        let cells = format_cells(&self.viewer.batch, row..row + 1, col..col + 1).ok()?;
+        cells.into_iter().flatten().next()
+    }
+
+    /// Shows the rows `view` says in the Data tab, keeping the row selected
+    /// or starting over, unless they can't be read.
+    fn set_view(&mut self, view: DataView, keep_row: bool) {
+        if view.is_query() && self.query_engine.is_none() {
+            match query_engine(&self.viewer) {
+                Ok(Some(engine)) => self.query_engine = Some(engine),
+                Ok(None) => {
+                    self.message = Some("SQL isn't available for Arrow IPC streams".to_string());
+                    return;
+                }
+                Err(e) => {
+                    self.message = Some(format!("can't query the file: {e}"));
+                    return;
+                }
+            }
+        }
+        let row = if keep_row {
+            self.viewer.first_row + self.viewer.selected_row
+        } else {
+            0
+        };
+        let previous = std::mem::replace(&mut self.view, view);
+        match self.rewind() {
+            Ok(()) => {
+                self.invalidate_profile();
+                self.tab = Tab::Data;
+                if let Err(e) = self.goto(row) {
+                    self.message = Some(e.to_string());
+                }
+            }
+            Err(e) => {
+                self.view = previous;
+                self.message = Some(e.to_string());
+            }
+        }
+    }
+
+    /// Hides the Data tab column `name`, or shows it again.
+    fn toggle_column(&mut self, name: &str) {
+        let mut view = self.view.clone();
+        if let Some(i) = view.hidden.iter().position(|c| c == name) {
+            view.hidden.remove(i);
+        } else if self.viewer.batch.schema().field_with_name(name).is_err() {
+            self.message = Some(format!("unknown column `{name}`"));
+            return;
+        } else if self.viewer.batch.num_columns() == 1 {
+            self.message = Some("the last column can't be hidden".to_string());
+            return;
+        } else {
+            view.hidden.push(name.to_string());
+        }
+        self.set_view(view, true);
+    }
+
+    /// Bookmarks the selected row, or goes to a bookmarked one, under the
+    /// letter pressed after `mark` or `jump-to-mark`.
+    pub fn mark_key(&mut self, key_event: KeyEvent) -> Result<()> {
+        let command = self.pending_mark.take();
+        let KeyCode::Char(letter) = key_event.code else {
+            self.message = None;
+            return Ok(());
+        };
+        if !letter.is_ascii_alphabetic() {
+            self.message = Some(format!("marks are letters, not `{letter}`"));
+            return Ok(());
+        }
+        let row = self.viewer.first_row + self.viewer.selected_row;
+        match command {
+            Some(Command::Mark) => {
+                self.bookmarks.insert(letter, row);
+                self.message = Some(format!("marked row {} as '{letter}", row + 1));
+            }
+            Some(Command::JumpToMark) => match self.bookmarks.get(&letter).copied() {
+                Some(row) => {
+                    self.goto(row)?;
+                    self.message = Some(format!("row {} ('{letter})", row + 1));
+                    self.sync_panes();
+                }
+                None => self.message = Some(format!("no mark '{letter}")),
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Where the file is and how it's viewed, to go back to in a later run.
+    /// Results of `:sql` aren't the file's rows, so aren't kept.
+    fn session(&self) -> Option<(PathBuf, Session)> {
+        let path = self.viewer.source.path()?;
+        if self.view.sql.is_some() {
+            return None;
+        }
+        let schema = self.viewer.batch.schema();
+        let column = (self.viewer.selected_col < schema.fields().len())
+            .then(|| schema.field(self.viewer.selected_col).name().clone());
+        let (sort, descending) = match &self.view.sort {
+            Some((column, descending)) => (Some(column.clone()), *descending),
+            None => (None, false),
+        };
+        let session = Session {
+            row: self.viewer.first_row + self.viewer.selected_row,
+            column,
+            hidden: self.view.hidden.clone(),
+            filter: self.view.filter.clone(),
+            sort,
+            descending,
+            bookmarks: self.bookmarks.clone(),
+            ..Default::default()
+        };
+        Some((path.to_path_buf(), session))
+    }
+
+    /// Goes back to how the file was last left, if it's in `sessions`.
+    fn restore_session(&mut self, sessions: &Sessions) {
+        if self.view.sql.is_some() {
+            return;
+        }
+        let Some(session) = self.viewer.source.path().and_then(|p| sessions.get(p)) else {
+            return;
+        };
+        let session = session.clone();
+        self.bookmarks = session.bookmarks;
+        let view = DataView {
+            sql: None,
+            filter: session.filter,
+            sort: session.sort.map(|column| (column, session.descending)),
+            hidden: session.hidden,
+        };
+        let tab = self.tab;
+        if view.is_query() || !view.hidden.is_empty() {
+            self.set_view(view, false);
+            self.tab = tab;
+        }
+        if let Err(e) = self.goto(session.row) {
+            self.message = Some(e.to_string());
+            return;
+        }
+        if let Some(i) = session
+            .column
+            .and_then(|name| self.viewer.batch.schema().index_of(&name).ok())
+        {
+            self.viewer.selected_col = i;
+            self.viewer.col_offset = i;
+        }
+        if self.message.is_none() {
+            self.message = Some(format!("back at row {}", session.row + 1));
+        }
+    }
+
+    /// Remembers how every open file is left, for later runs.
+    pub fn save_sessions(&mut self) -> io::Result<()> {
+        let sessions: Vec<_> = (0..self.num_buffers())
+            .filter_map(|i| self.buffer(i).session())
+            .collect();
+        for (path, session) in sessions {
+            self.sessions.set(&path, session);
+        }
+        self.sessions.save()
+    }
+
+    /// Goes to the row numbered `number`, counting from 1.
+    fn goto_line(&mut self, number: &str) {
+        let Some(row) = number.parse::<usize>().ok().filter(|n| *n > 0) else {
+            self.message = Some(format!("invalid row number `{number}`"));
+            return;
+        };
+        self.tab = Tab::Data;
+        if let Err(e) = self.goto(row - 1) {
+            self.message = Some(e.to_string());
+        }
+    }
+
+    /// Selects the `row`th row of the Data tab, or the last if there are
+    /// fewer, reading the rows again when it was already read past.
+    ///
+    /// Local Parquet files are read from the row group the row is in, other
+    /// sources and queried rows from the start.
+    fn goto(&mut self, row: usize) -> Result<()> {
+        let viewer = &self.viewer;
+        let row = viewer
+            .num_data_rows
+            .map_or(row, |n| row.min(n.saturating_sub(1)));
+        let read = viewer.first_row..viewer.first_row + viewer.batch.num_rows();
+        if !read.contains(&row) && !self.view.is_query() {
+            // Where the batch would start if read from the start.
+            let first_row = row - row % viewer.batch_size;
+            let columns = self.shown_columns();
+            let reader =
+                viewer
+                    .source
+                    .reader_from(columns.as_deref(), viewer.batch_size, first_row);
+            if let Some(reader) = reader {
+                self.viewer.seek(reader?, first_row)?;
+                self.visual = None;
+            }
+        }
+        if row < self.viewer.first_row {
+            self.rewind()?;
+        }
+        while row >= self.viewer.first_row + self.viewer.batch.num_rows() {
+            if !self.viewer.next_batch()? {
+                break;
+            }
+            self.visual = None;
+        }
+        let selected =
+            (row - self.viewer.first_row).min(self.viewer.batch.num_rows().saturating_sub(1));
+        self.viewer.selected_row = selected;
+        self.viewer.row_offset = selected.saturating_sub(self.viewer.visible_rows / 2);
+        Ok(())
+    }
+
+    /// Number of open files.
+    pub fn num_buffers(&self) -> usize {
+        self.buffers.len() + 1
+    }
+
+    /// The `i`th open file in the buffer list.
+    pub fn buffer(&self, i: usize) -> &State {
+        match i.cmp(&self.buffer_index) {
+            Ordering::Less => &self.buffers[i],
+            Ordering::Equal => self,
+            Ordering::Greater => &self.buffers[i - 1],
+        }
+    }
+
+    /// Opens the file at `path` in a buffer after this one, and switches to
+    /// it.
+    fn open(&mut self, path: &Path) -> Result<()> {
+        let mut buffer = Self::new(open_path(path)?);
+        buffer.restore_session(&self.sessions);
+        if buffer.tabs().contains(&self.tab) {
+            buffer.tab = self.tab;
+        }
+        self.buffers.insert(self.buffer_index, buffer);
+        for pane in self.panes.iter_mut() {
+            if pane.buffer > self.buffer_index {
+                pane.buffer += 1;
+            }
+        }
+        self.switch_buffer(self.buffer_index + 1);
+        Ok(())
+    }
+
+    /// Switches to the buffer numbered `arg`, counting from 1, or whose name
+    /// matches it best.
+    fn switch_to_buffer(&mut self, arg: &str) {
+        let i = match arg.parse::<usize>() {
+            Ok(number) => number.checked_sub(1).filter(|i| *i < self.num_buffers()),
+            Err(_) => (0..self.num_buffers())
+                .filter_map(|i| {
+                    let score = palette::fuzzy_score(arg, &self.buffer(i).viewer.file_stem)?;
+                    Some((score, i))
+                })
+                .max_by_key(|(score, i)| (*score, Reverse(*i)))
+                .map(|(_, i)| i),
+        };
+        match i {
+            Some(i) => self.switch_buffer(i),
+            None => self.message = Some(format!("no buffer `{arg}`")),
+        }
+    }
+
+    /// Shows the `to`th open file, each keeping what's selected and how its
+    /// rows are viewed.
+    fn switch_buffer(&mut self, to: usize) {
+        let from = self.buffer_index;
+        if to == from || to >= self.num_buffers() {
+            return;
+        }
+        let incoming = self.buffers.remove(if to > from { to - 1 } else { to });
+        let mut previous = mem::replace(self, incoming);
+        self.take_settings(&mut previous);
+        // Back where it was in the list, which now leaves out `to`.
+        self.buffers
+            .insert(if from > to { from - 1 } else { from }, previous);
+        self.buffer_index = to;
+        self.message = Some(format!(
+            "{}/{}: {}",
+            to + 1,
+            self.num_buffers(),
+            self.viewer.file_stem
+        ));
+    }
+
+    /// Moves over what applies to every open file, rather than to the one
+    /// shown, from `other`.
+    fn take_settings(&mut self, other: &mut State) {
+        self.profile_limit = other.profile_limit;
+        self.keymap = mem::take(&mut other.keymap);
+        self.clipboard = other.clipboard;
+        self.history = mem::take(&mut other.history);
+        self.sourcing = other.sourcing;
+        self.buffers = mem::take(&mut other.buffers);
+        self.panes = mem::take(&mut other.panes);
+        self.active_pane = other.active_pane;
+        self.split = other.split;
+        self.sync_scroll = other.sync_scroll;
+        self.sessions = mem::take(&mut other.sessions);
+    }
+
+    /// The `i`th open file in the buffer list.
+    pub fn buffer_mut(&mut self, i: usize) -> &mut State {
+        match i.cmp(&self.buffer_index) {
+            Ordering::Less => &mut self.buffers[i],
+            Ordering::Equal => self,
+            Ordering::Greater => &mut self.buffers[i - 1],
+        }
+    }
+
+    /// Notes what this file's pane shows, before the others are looked at.
+    pub fn store_pane(&mut self) {
+        let scroll = self.scroll();
+        if let Some(pane) = self.panes.get_mut(self.active_pane) {
+            *pane = Pane {
+                buffer: self.buffer_index,
+                tab: self.tab,
+                scroll,
+            };
+        }
+    }
+
+    /// Where the Data tab is in the rows.
+    pub fn scroll(&self) -> Scroll {
+        let viewer = &self.viewer;
+        Scroll {
+            row: viewer.first_row + viewer.selected_row,
+            height: viewer.selected_row - viewer.row_offset.min(viewer.selected_row),
+            selected_col: viewer.selected_col,
+            col_offset: viewer.col_offset,
+        }
+    }
+
+    /// Moves the Data tab back to `scroll`, reading the rows it's at unless
+    /// they're those read last.
+    pub fn set_scroll(&mut self, scroll: Scroll) -> Result<()> {
+        if scroll == self.scroll() {
+            return Ok(());
+        }
+        let viewer = &mut self.viewer;
+        let rows = viewer.first_row..viewer.first_row + viewer.batch.num_rows();
+        if rows.contains(&scroll.row) {
+            viewer.selected_row = scroll.row - viewer.first_row;
+        } else {
+            self.goto(scroll.row)?;
+        }
+        let viewer = &mut self.viewer;
+        viewer.row_offset = viewer.selected_row.saturating_sub(scroll.height);
+        viewer.selected_col = scroll.selected_col;
+        viewer.col_offset = scroll.col_offset;
+        Ok(())
+    }
+
+    /// Splits this file's pane in two, laying the panes out in `direction`.
+    fn split_pane(&mut self, direction: Direction) {
+        let pane = Pane {
+            buffer: self.buffer_index,
+            tab: self.tab,
+            scroll: self.scroll(),
+        };
+        if self.panes.is_empty() {
+            self.panes.push(pane);
+            self.active_pane = 0;
+        }
+        self.panes.insert(self.active_pane + 1, pane);
+        self.active_pane += 1;
+        self.split = direction;
+    }
+
+    /// Makes the `i`th pane the one keys act on.
+    fn focus_pane(&mut self, i: usize) {
+        self.store_pane();
+        let Some(pane) = self.panes.get(i).copied() else {
+            return;
+        };
+        self.switch_buffer(pane.buffer);
+        self.active_pane = i;
+        self.tab = pane.tab;
+        self.message = None;
+        if let Err(e) = self.set_scroll(pane.scroll) {
+            self.message = Some(e.to_string());
+        }
+    }
+
+    /// Closes this file's pane, moving to the next one.
+    fn close_pane(&mut self) {
+        if self.panes.len() < 2 {
+            self.message = Some("the screen isn't split".to_string());
+            return;
+        }
+        self.panes.remove(self.active_pane);
+        let i = self.active_pane.min(self.panes.len() - 1);
+        // Moving to another pane would note this one's again.
+        self.active_pane = usize::MAX;
+        self.focus_pane(i);
+        if self.panes.len() == 1 {
+            self.panes.clear();
+        }
+    }
+
+    /// Selects this pane's row in the other panes too, at the same height,
+    /// when scrolling is synced.
+    fn sync_panes(&mut self) {
+        if !self.sync_scroll || self.panes.len() < 2 || self.tab != Tab::Data {
+            return;
+        }
+        // Each pane moves to it once shown.
+        let Scroll { row, height, .. } = self.scroll();
+        for (i, pane) in self.panes.iter_mut().enumerate() {
+            if i != self.active_pane {
+                pane.scroll.row = row;
+                pane.scroll.height = height;
+            }
+        }
+    }
+
+    /// Closes the file shown, switching to the next one, or the one before
+    /// when it's the last.
+    fn close_buffer(&mut self) {
+        if self.buffers.is_empty() {
+            self.message = Some("can't close the only open file, quit instead".to_string());
+            return;
+        }
+        let from = self.buffer_index;
+        let to = if from + 1 < self.num_buffers() {
+            from + 1
+        } else {
+            from - 1
+        };
+        self.switch_buffer(to);
+        let closed = self.buffers.remove(if from > to { from - 1 } else { from });
+        if to > from {
+            self.buffer_index -= 1;
+        }
+        // Panes showing the closed file show this one instead.
+        for pane in self.panes.iter_mut() {
+            match pane.buffer.cmp(&from) {
+                Ordering::Less => {}
+                Ordering::Equal => pane.buffer = self.buffer_index,
+                Ordering::Greater => pane.buffer -= 1,
+            }
+        }
+        if let Some((path, session)) = closed.session() {
+            self.sessions.set(&path, session);
+        }
+        self.message = Some(format!("closed {}", closed.viewer.file_stem));
+    }
+
+    /// Runs the lines of the file at `path` on the command line, skipping
+    /// blank lines and `#` comments.
+    fn source(&mut self, path: &Path, event_sender: mpsc::Sender<Event>) -> Result<()> {
+        if self.sourcing >= MAX_SOURCE_DEPTH {
+            self.message = Some(format!("`source` nested more than {MAX_SOURCE_DEPTH} deep"));
+            return Ok(());
+        }
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) => {
+                self.message = Some(format!("can't read `{}`: {e}", path.display()));
+                return Ok(());
+            }
+        };
+        self.sourcing += 1;
+        let result = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .try_for_each(|line| self.execute_line(line, event_sender.clone()));
+        self.sourcing -= 1;
+        result
+    }
+
+    fn num_partition_rows(&self) -> usize {
+        self.viewer
+            .partitions()
+            .map_or(0, |p| p.summary().len())
+            .max(1)
+    }
+
+    fn num_history_rows(&self) -> usize {
+        self.viewer.delta().map_or(0, |t| t.history.len()).max(1)
+    }
+
+    /// Number of rows of the Iceberg tab shown, and its table state.
+    fn iceberg_rows(&mut self) -> (usize, &mut TableState) {
+        let table = self.viewer.iceberg();
+        match self.tab {
+            Tab::Snapshots => (
+                table.map_or(0, |t| t.snapshots.len()).max(1),
+                &mut self.snapshot_table_state,
+            ),
+            Tab::Schemas => (
+                table.map_or(0, |t| t.field_ids().len()).max(1),
+                &mut self.field_table_state,
+            ),
+            _ => (
+                table.map_or(0, |t| t.data_files.len()).max(1),
+                &mut self.file_table_state,
+            ),
+        }
+    }
+
+    /// The directory tree of the Files tab, rooted where the viewed file is,
+    /// or in the working directory.
+    pub fn browser(&mut self) -> &mut Browser {
+        let path = self.viewer.source.path();
+        self.browser.get_or_insert_with(|| {
+            let root = match path {
+                Some(path) if path.is_dir() => path.to_path_buf(),
+                Some(path) => path
+                    .parent()
+                    .filter(|p| !p.as_os_str().is_empty())
+                    .map_or_else(|| PathBuf::from("."), Path::to_path_buf),
+                None => PathBuf::from("."),
+            };
+            Browser::new(&root)
+        })
+    }
+
+    /// Opens the file selected on the Files tab, or expands or collapses
+    /// the directory.
+    fn open_browser_entry(&mut self) {
+        let Some(entry) = self.browser().selected() else {
+            return;
+        };
+        if let EntryKind::Directory { .. } = entry.kind {
+            return self.browser().toggle();
+        }
+        let path = entry.path.clone();
+        match self.open(&path) {
+            Ok(()) => self.tab = Tab::Data,
+            Err(e) => self.message = Some(e.to_string()),
+        }
+    }
+
+    /// Opens the data file selected on the Data files tab in a buffer of its
+    /// own, showing its rows.
+    fn open_data_file(&mut self) {
+        let Some(table) = self.viewer.iceberg() else {
+            return;
+        };
+        let selected = self.file_table_state.selected().unwrap_or(0);
+        let Some(file) = table.data_files.get(selected) else {
+            return;
+        };
+        if file.file_format != "PARQUET" {
+            self.message = Some(format!("{} files can't be read", file.file_format));
+            return;
+        }
+        let path = file.path.clone();
+        match self.open(&path) {
+            Ok(()) => self.tab = Tab::Data,
+            Err(e) => self.message = Some(e.to_string()),
+        }
+    }
+
+    fn num_diff_rows(&self) -> usize {
+        self.diff.as_ref().map_or(0, |d| d.columns.len()).max(1)
+    }
+
+    fn num_row_diff_rows(&self) -> usize {
+        self.data_diff.as_ref().map_or(0, |d| d.rows.len()).max(1)
+    }
+}
+
+/// Cells of `rows` and `cols` of `batch`, as shown in the Data tab but not
+/// cut short.
+fn format_cells(
+    batch: &RecordBatch,
+    rows: Range<usize>,
+    cols: Range<usize>,
+) -> Result<Vec<Vec<String>>> {
+    let options = FormatOptions::default();
+    let formatters = batch.columns()[cols]
+        .iter()
+        .map(|c| ArrayFormatter::try_new(c.as_ref(), &options))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(rows
+        .map(|i| formatters.iter().map(|f| f.value(i).to_string()).collect())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, sync::Arc};
+
+    use arrow::array::{ArrayRef, Int32Array};
+    use parquet::{arrow::ArrowWriter, file::properties::WriterProperties};
+
+    use super::*;
+
+    /// Writes `ids` in row groups of 100 rows.
+    fn write_ids(path: &Path, ids: Vec<i32>) {
+        let batch =
+            RecordBatch::try_from_iter([("id", Arc::new(Int32Array::from(ids)) as ArrayRef)])
+                .unwrap();
+        let props = WriterProperties::builder()
+            .set_max_row_group_size(100)
+            .build();
+        let mut writer =
+            ArrowWriter::try_new(File::create(path).unwrap(), batch.schema(), Some(props)).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+    }
+
+    /// Waits for the profile requested, skipping progress.
+    fn profile_event(receiver: &mpsc::Receiver<Event>) -> (u64, Result<ColumnProfile>) {
+        loop {
+            if let Event::Profile(request, profile) = receiver.recv().unwrap() {
+                return (request, *profile);
+            }
+        }
+    }
+
+    #[test]
+    fn routes_profiles_to_their_buffer() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b) = (dir.path().join("a.parquet"), dir.path().join("b.parquet"));
+        write_ids(&a, vec![1, 2, 3]);
+        write_ids(&b, vec![4, 5]);
+        let mut state = State::new(open_path(&a).unwrap())
+            .with_buffers(&[b])
+            .unwrap();
+        let (sender, receiver) = mpsc::channel();
+
+        state.profile_selected_column(sender.clone());
+        let (request, profile) = profile_event(&receiver);
+        // Switched away from the file before its profile came in.
+        state.switch_buffer(1);
+        state.set_profile(request, profile);
+        assert!(state.profile.is_none());
+        assert!(state.buffer(0).profiling.is_none());
+        assert_eq!(state.buffer(0).profile.as_ref().unwrap().column, "id");
+
+        // One file's profile comes in while the other's is being computed.
+        state.profile_selected_column(sender.clone());
+        let (request, profile) = profile_event(&receiver);
+        state.switch_buffer(0);
+        state.invalidate_profile();
+        state.profile_selected_column(sender);
+        state.set_profile(request, profile);
+        assert!(state.profile.is_none());
+        assert!(state.profiling.is_some());
+        assert!(state.buffer(1).profile.is_some());
+
+        let (request, profile) = profile_event(&receiver);
+        state.set_profile(request, profile);
+        assert!(state.profile.is_some());
+    }
+
+    #[test]
+    fn keeps_scroll_per_pane() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ids.parquet");
+        write_ids(&path, (0..1000).collect());
+        let mut state = State::new(open_path(&path).unwrap());
+        state.split_pane(Direction::Vertical);
+        state.goto(500).unwrap();
+
+        state.focus_pane(0);
+        assert_eq!(state.scroll().row, 0);
+        state.focus_pane(1);
+        assert_eq!(state.scroll().row, 500);
+        assert_eq!(state.panes[0].scroll.row, 0);
+
+        // Synced panes follow the active one's row.
+        state.sync_scroll = true;
+        state.sync_panes();
+        state.focus_pane(0);
+        assert_eq!(state.scroll().row, 500);
+    }
+
+    #[test]
+    fn goes_to_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ids.parquet");
+        write_ids(&path, (0..1000).collect());
+        let mut state = State::new(open_path(&path).unwrap());
+        let batch_size = state.viewer.batch_size;
+
+        for row in [750, 10, 999, 500, 99, 100] {
+            state.goto(row).unwrap();
+            assert_eq!(state.viewer.first_row, row - row % batch_size);
+            assert_eq!(state.selected_value().unwrap(), row.to_string());
+        }
+        // Past the end, once the rows are counted.
+        state.goto(5000).unwrap();
+        assert_eq!(state.selected_value().unwrap(), "999");
+
+        // Queried rows are read from the start.
+        state.set_view(
+            DataView {
+                filter: Some("id % 2 = 0".to_string()),
+                ..Default::default()
+            },
+            false,
+        );
+        state.goto(300).unwrap();
+        assert_eq!(state.selected_value().unwrap(), "600");
+    }
 }
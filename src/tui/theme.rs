@@ -0,0 +1,459 @@
+use std::{
+    collections::BTreeMap,
+    env,
+    str::FromStr,
+    sync::{OnceLock, RwLock},
+};
+
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier},
+};
+use serde::Deserialize;
+
+use crate::config::Config;
+
+/// Colours of the UI, named after the Rosé Pine palette the dark theme uses.
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ThemeColor {
+    Base,
+    Surface,
+    Pine,
+    Foam,
+    Text,
+    Subtle,
+    HighlightLow,
+    HighlightMed,
+    Iris,
+    Gold,
+    Rose,
+    Love,
+}
+
+/// Names of the colours in the config file, in the order of [`ThemeColor`].
+const COLOR_NAMES: [&str; 12] = [
+    "base",
+    "surface",
+    "pine",
+    "foam",
+    "text",
+    "subtle",
+    "highlight-low",
+    "highlight-med",
+    "iris",
+    "gold",
+    "rose",
+    "love",
+];
+
+/// A colour for each [`ThemeColor`].
+pub type Palette = [Color; 12];
+
+/// What stands in for each [`ThemeColor`] as a background without colours,
+/// so highlights, selections and striped rows still show.
+const BACKGROUND_MODIFIERS: [Modifier; 12] = [
+    Modifier::empty(),
+    Modifier::empty(),
+    Modifier::REVERSED,
+    Modifier::REVERSED,
+    Modifier::REVERSED,
+    Modifier::REVERSED,
+    Modifier::UNDERLINED,
+    Modifier::REVERSED,
+    Modifier::REVERSED,
+    Modifier::REVERSED,
+    Modifier::REVERSED,
+    Modifier::REVERSED,
+];
+
+/// Rosé Pine Moon.
+const DARK: Palette = [
+    Color::Rgb(35, 33, 54),
+    Color::Rgb(42, 39, 63),
+    Color::Rgb(62, 143, 176),
+    Color::Rgb(156, 207, 216),
+    Color::Rgb(224, 222, 244),
+    Color::Rgb(144, 140, 170),
+    Color::Rgb(42, 40, 62),
+    Color::Rgb(68, 65, 90),
+    Color::Rgb(196, 167, 231),
+    Color::Rgb(246, 193, 119),
+    Color::Rgb(234, 154, 151),
+    Color::Rgb(235, 111, 146),
+];
+
+/// Rosé Pine Dawn.
+const LIGHT: Palette = [
+    Color::Rgb(250, 244, 237),
+    Color::Rgb(255, 250, 243),
+    Color::Rgb(40, 105, 131),
+    Color::Rgb(86, 148, 159),
+    Color::Rgb(87, 82, 121),
+    Color::Rgb(121, 117, 147),
+    Color::Rgb(244, 237, 232),
+    Color::Rgb(223, 218, 217),
+    Color::Rgb(144, 122, 169),
+    Color::Rgb(234, 157, 52),
+    Color::Rgb(215, 130, 126),
+    Color::Rgb(180, 99, 122),
+];
+
+/// Saturated colours on black.
+const HIGH_CONTRAST: Palette = [
+    Color::Rgb(0, 0, 0),
+    Color::Rgb(24, 24, 24),
+    Color::Rgb(0, 175, 255),
+    Color::Rgb(0, 255, 255),
+    Color::Rgb(255, 255, 255),
+    Color::Rgb(208, 208, 208),
+    Color::Rgb(38, 38, 38),
+    Color::Rgb(88, 88, 88),
+    Color::Rgb(215, 135, 255),
+    Color::Rgb(255, 215, 0),
+    Color::Rgb(255, 175, 175),
+    Color::Rgb(255, 85, 85),
+];
+
+const BUILT_IN_THEMES: &[(&str, Palette)] = &[
+    ("dark", DARK),
+    ("light", LIGHT),
+    ("high-contrast", HIGH_CONTRAST),
+];
+
+/// Colours the terminal can show.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+pub enum ColorDepth {
+    #[serde(rename = "truecolor")]
+    TrueColor,
+    #[serde(rename = "256")]
+    Ansi256,
+    #[serde(rename = "16")]
+    Ansi16,
+    /// Only the terminal's own foreground and background.
+    #[serde(rename = "none")]
+    NoColor,
+}
+
+impl ColorDepth {
+    /// Guesses from the environment, honouring `NO_COLOR`.
+    pub fn detect() -> Self {
+        let var = |name| env::var(name).unwrap_or_default();
+        if !var("NO_COLOR").is_empty() {
+            Self::NoColor
+        } else if matches!(var("COLORTERM").as_str(), "truecolor" | "24bit") {
+            Self::TrueColor
+        } else if var("TERM").contains("256color") {
+            Self::Ansi256
+        } else {
+            Self::Ansi16
+        }
+    }
+
+    /// Approximates `color`, the `i`th of its palette, with the colours
+    /// available.
+    ///
+    /// Without colours, it's replaced by its index until [`fall_back`] turns
+    /// it into modifiers, once drawn.
+    fn convert(self, color: Color, i: usize) -> Color {
+        match (self, color) {
+            (Self::NoColor, _) => Color::Indexed(i as u8),
+            (Self::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(ansi256(r, g, b)),
+            (Self::Ansi16, Color::Rgb(r, g, b)) => ansi16(r, g, b),
+            (_, color) => color,
+        }
+    }
+}
+
+/// Palette of the running UI, set once its config is read and whenever the
+/// theme is switched.
+static PALETTE: RwLock<Palette> = RwLock::new(DARK);
+
+/// User-defined themes, mapping their names to colours by name.
+type Themes = BTreeMap<String, BTreeMap<String, String>>;
+
+/// Themes of the config file and the colour depth, to switch themes with.
+static SETTINGS: OnceLock<(Themes, ColorDepth)> = OnceLock::new();
+
+impl From<ThemeColor> for Color {
+    fn from(value: ThemeColor) -> Self {
+        PALETTE.read().map_or(DARK, |p| *p)[value as usize]
+    }
+}
+
+/// Resolves the theme named in `config`, built in or defined in its
+/// `[themes]` table, and makes it the palette of the UI.
+pub fn init(config: &Config) -> Result<(), String> {
+    let depth = config.color_depth.unwrap_or_else(ColorDepth::detect);
+    // The UI only starts once.
+    let _ = SETTINGS.set((config.themes.clone(), depth));
+    set(config.theme.as_deref().unwrap_or("dark"))
+}
+
+/// Switches to the theme `name`.
+pub fn set(name: &str) -> Result<(), String> {
+    let (themes, depth) = SETTINGS.get_or_init(|| (BTreeMap::new(), ColorDepth::detect()));
+    let mut palette = palette(name, themes, 0)?;
+    for (i, color) in palette.iter_mut().enumerate() {
+        *color = depth.convert(*color, i);
+    }
+    if let Ok(mut current) = PALETTE.write() {
+        *current = palette;
+    }
+    Ok(())
+}
+
+/// Without colours, replaces the palette's colours in `buffer` by modifiers:
+/// backgrounds by [`BACKGROUND_MODIFIERS`], and [`ThemeColor::Iris`] text,
+/// like the selected cell and rows, by bold.
+pub fn fall_back(buffer: &mut Buffer) {
+    if !matches!(SETTINGS.get(), Some((_, ColorDepth::NoColor))) {
+        return;
+    }
+    to_modifiers(buffer);
+}
+
+fn to_modifiers(buffer: &mut Buffer) {
+    for cell in buffer.content.iter_mut() {
+        if let Color::Indexed(i) = cell.bg {
+            cell.modifier |= BACKGROUND_MODIFIERS
+                .get(i as usize)
+                .copied()
+                .unwrap_or_default();
+            cell.bg = Color::Reset;
+        }
+        if let Color::Indexed(i) = cell.fg {
+            if i == ThemeColor::Iris as u8 {
+                cell.modifier |= Modifier::BOLD;
+            }
+            cell.fg = Color::Reset;
+        }
+    }
+}
+
+/// Names of the built-in and user-defined themes.
+pub fn names() -> Vec<String> {
+    let mut names: Vec<_> = BUILT_IN_THEMES
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .collect();
+    if let Some((themes, _)) = SETTINGS.get() {
+        // User-defined themes may override built-in ones.
+        names.retain(|name| !themes.contains_key(name));
+        names.extend(themes.keys().cloned());
+    }
+    names
+}
+
+/// Themes can inherit from each other, but not endlessly.
+const MAX_INHERITANCE: usize = 8;
+
+fn palette(name: &str, themes: &Themes, depth: usize) -> Result<Palette, String> {
+    if depth > MAX_INHERITANCE {
+        return Err(format!("theme `{name}` inherits from itself"));
+    }
+    let Some(theme) = themes.get(name) else {
+        return BUILT_IN_THEMES
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, palette)| *palette)
+            .ok_or_else(|| {
+                let names: Vec<_> = BUILT_IN_THEMES
+                    .iter()
+                    .map(|(n, _)| *n)
+                    .chain(themes.keys().map(String::as_str))
+                    .collect();
+                format!(
+                    "unknown theme `{name}`, expected one of {}",
+                    names.join(", ")
+                )
+            });
+    };
+    // A user-defined theme overrides colours of another one, the dark theme
+    // by default.
+    let inherits = theme.get("inherits").map_or("dark", String::as_str);
+    let mut palette = palette(inherits, themes, depth + 1)?;
+    for (key, value) in theme.iter().filter(|(key, _)| *key != "inherits") {
+        let i = COLOR_NAMES.iter().position(|n| n == key).ok_or_else(|| {
+            format!(
+                "unknown colour `{key}` in theme `{name}`, expected one of inherits, {}",
+                COLOR_NAMES.join(", ")
+            )
+        })?;
+        palette[i] = Color::from_str(value)
+            .map_err(|_| format!("invalid colour `{value}` for `{key}` in theme `{name}`"))?;
+    }
+    Ok(palette)
+}
+
+/// Nearest colour of the xterm 256-colour palette: its 6×6×6 cube or its
+/// grey ramp.
+fn ansi256(r: u8, g: u8, b: u8) -> u8 {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let nearest_level = |c: u8| {
+        (0..6)
+            .min_by_key(|&i| (LEVELS[i] as i32 - c as i32).abs())
+            .unwrap_or(0)
+    };
+    let (ri, gi, bi) = (nearest_level(r), nearest_level(g), nearest_level(b));
+    let cube = (LEVELS[ri], LEVELS[gi], LEVELS[bi]);
+    let mean = (r as u32 + g as u32 + b as u32) / 3;
+    let grey_index = (mean.saturating_sub(3) / 10).min(23) as u8;
+    let grey = 8 + 10 * grey_index;
+    if distance((r, g, b), (grey, grey, grey)) < distance((r, g, b), cube) {
+        232 + grey_index
+    } else {
+        16 + 36 * ri as u8 + 6 * gi as u8 + bi as u8
+    }
+}
+
+/// The ANSI colour of the same hue, or grey, in a normal or bright shade.
+///
+/// Nearest by distance would turn most pastel colours grey, as the terminal
+/// decides what the 16 colours look like anyway.
+fn ansi16(r: u8, g: u8, b: u8) -> Color {
+    let (max, min) = (r.max(g).max(b) as i32, r.min(g).min(b) as i32);
+    let lightness = (max + min) / 2;
+    if max - min < 40 {
+        return match lightness {
+            0..64 => Color::Black,
+            64..160 => Color::DarkGray,
+            160..224 => Color::Gray,
+            _ => Color::White,
+        };
+    }
+    let (r, g, b, max, min) = (r as f32, g as f32, b as f32, max as f32, min as f32);
+    let sector = match max {
+        _ if max == r => ((g - b) / (max - min)).rem_euclid(6.0),
+        _ if max == g => (b - r) / (max - min) + 2.0,
+        _ => (r - g) / (max - min) + 4.0,
+    };
+    let hue = (sector * 60.0) as u32;
+    let bright = lightness > 140;
+    match (hue, bright) {
+        (30..90, false) => Color::Yellow,
+        (30..90, true) => Color::LightYellow,
+        (90..150, false) => Color::Green,
+        (90..150, true) => Color::LightGreen,
+        (150..210, false) => Color::Cyan,
+        (150..210, true) => Color::LightCyan,
+        (210..270, false) => Color::Blue,
+        (210..270, true) => Color::LightBlue,
+        (270..330, false) => Color::Magenta,
+        (270..330, true) => Color::LightMagenta,
+        (_, false) => Color::Red,
+        (_, true) => Color::LightRed,
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::layout::Rect;
+
+    use super::*;
+
+    fn themes(toml: &str) -> Themes {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn inherits_palettes() {
+        let themes = themes(
+            r##"
+            [dusk]
+            inherits = "light"
+            love = "#ff0000"
+
+            [midnight]
+            inherits = "dusk"
+            base = "black"
+
+            [plain]
+            text = "white"
+            "##,
+        );
+        let dusk = palette("dusk", &themes, 0).unwrap();
+        assert_eq!(dusk[ThemeColor::Love as usize], Color::Rgb(255, 0, 0));
+        assert_eq!(
+            dusk[ThemeColor::Base as usize],
+            LIGHT[ThemeColor::Base as usize]
+        );
+
+        let midnight = palette("midnight", &themes, 0).unwrap();
+        assert_eq!(midnight[ThemeColor::Base as usize], Color::Black);
+        assert_eq!(midnight[ThemeColor::Love as usize], Color::Rgb(255, 0, 0));
+        assert_eq!(
+            midnight[ThemeColor::Pine as usize],
+            LIGHT[ThemeColor::Pine as usize]
+        );
+
+        // Themes inherit from the dark one by default.
+        let plain = palette("plain", &themes, 0).unwrap();
+        assert_eq!(plain[ThemeColor::Text as usize], Color::White);
+        assert_eq!(
+            plain[ThemeColor::Base as usize],
+            DARK[ThemeColor::Base as usize]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_themes() {
+        let themes = themes(
+            r#"
+            [a]
+            inherits = "b"
+
+            [b]
+            inherits = "a"
+
+            [typo]
+            lvoe = "red"
+
+            [orphan]
+            inherits = "missing"
+            "#,
+        );
+        assert!(palette("a", &themes, 0)
+            .unwrap_err()
+            .ends_with("inherits from itself"));
+        assert!(palette("typo", &themes, 0)
+            .unwrap_err()
+            .starts_with("unknown colour `lvoe` in theme `typo`"));
+        assert!(palette("orphan", &themes, 0)
+            .unwrap_err()
+            .starts_with("unknown theme `missing`"));
+    }
+
+    #[test]
+    fn falls_back_to_modifiers() {
+        let stand_in =
+            |color: ThemeColor| ColorDepth::NoColor.convert(DARK[color as usize], color as usize);
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 4, 1));
+        buffer[(0, 0)].set_bg(stand_in(ThemeColor::Base));
+        buffer[(1, 0)].set_bg(stand_in(ThemeColor::HighlightLow));
+        buffer[(2, 0)].set_bg(stand_in(ThemeColor::HighlightMed));
+        buffer[(3, 0)]
+            .set_bg(stand_in(ThemeColor::HighlightLow))
+            .set_fg(stand_in(ThemeColor::Iris));
+        to_modifiers(&mut buffer);
+        let modifiers: Vec<_> = buffer.content.iter().map(|c| c.modifier).collect();
+        assert_eq!(
+            modifiers,
+            [
+                Modifier::empty(),
+                Modifier::UNDERLINED,
+                Modifier::REVERSED,
+                Modifier::UNDERLINED | Modifier::BOLD,
+            ]
+        );
+        assert!(buffer
+            .content
+            .iter()
+            .all(|c| c.fg == Color::Reset && c.bg == Color::Reset));
+    }
+}
@@ -1,51 +1,94 @@
+use std::mem;
+
 use arrow::{
     array::RecordBatch,
-    error::ArrowError,
-    util::{
-        display::{ArrayFormatter, FormatOptions},
-        pretty::pretty_format_batches,
-    },
+    util::display::{ArrayFormatter, FormatOptions},
 };
 use parquet::file::statistics::Statistics;
 use ratatui::{
-    layout::{Alignment, Constraint, Direction, Layout, Offset, Rect},
-    style::{Color, Style, Stylize},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
+    style::{Style, Stylize},
     text::{Line, Span, Text},
-    widgets::{block::Title, Block, Paragraph, Row, Table, Tabs},
+    widgets::{
+        block::Title, Bar, BarChart, BarGroup, Block, Cell, Clear, Paragraph, Row, Sparkline,
+        Table, Tabs,
+    },
     Frame,
 };
 
-use super::state::State;
-use crate::error::Result;
+use super::{
+    browser::EntryKind,
+    state::{State, DEFAULT_COLUMN_WIDTH},
+    theme::ThemeColor,
+};
+use crate::{
+    app::SCHEMA_HEADERS,
+    delta::format_timestamp,
+    diff::DiffKind,
+    profile::QUANTILES,
+    salvage::DamagedChunk,
+    source::{format_size, Format},
+};
 
 pub fn render(state: &mut State, frame: &mut Frame) {
+    state.store_pane();
     frame.render_widget(
         Block::new().bg(ThemeColor::Base).fg(ThemeColor::Text),
         frame.area(),
     );
 
+    // The status bar, with the command line or a message below it.
+    let bottom_lines = match state.input.is_some() || state.message.is_some() {
+        true => 2,
+        false => 1,
+    };
     let screen = Layout::default()
         .direction(Direction::Vertical)
-        .constraints(vec![Constraint::Length(1), Constraint::Min(0)])
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(bottom_lines),
+        ])
         .spacing(1)
         .split(frame.area());
+    let bottom = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).split(screen[2]);
+
+    let visible_tabs = state.tabs();
+    let headers: Vec<_> = visible_tabs.iter().map(|t| t.header()).collect();
+    let tabs_width = headers.iter().map(|h| h.len() + 3).sum::<usize>() - 1;
 
     let title_line = Layout::default()
         .direction(Direction::Horizontal)
         .constraints(vec![
-            Constraint::Length(17),
+            Constraint::Length(tabs_width as u16),
             Constraint::Min(0),
             Constraint::Percentage(50),
         ])
         .split(screen[0]);
 
-    let tabs = Tabs::new(Tab::get_headers().to_vec())
+    let tabs = Tabs::new(headers)
         .highlight_style(Style::default().fg(ThemeColor::Iris.into()))
-        .select(state.tab as usize)
+        .select(
+            visible_tabs
+                .iter()
+                .position(|t| *t == state.tab)
+                .unwrap_or(0),
+        )
         .divider("|")
         .padding(" ", " ");
 
     frame.render_widget(tabs, title_line[0]);
+    // Each header is padded by a space either side, then followed by a divider.
+    let mut x = title_line[0].x;
+    state.click_areas.tabs = visible_tabs
+        .iter()
+        .map(|tab| {
+            let width = tab.header().len() as u16 + 2;
+            let area = Rect::new(x, title_line[0].y, width, 1).intersection(title_line[0]);
+            x += width + 1;
+            (area, *tab)
+        })
+        .collect();
     frame.render_widget(" (Tab) ".fg(ThemeColor::Subtle), title_line[1]);
 
     let label = Paragraph::new(Line::from(vec![
@@ -53,33 +96,393 @@ pub fn render(state: &mut State, frame: &mut Frame) {
             .bg(ThemeColor::Foam)
             .fg(ThemeColor::HighlightLow),
         Span::from(format!(" {} ", &state.viewer.file_stem)).bg(ThemeColor::Pine),
+        // Which of the open files this is, when there are several.
+        Span::from(match state.num_buffers() {
+            1 => String::new(),
+            n => format!(" {}/{n} ", state.buffer_index + 1),
+        })
+        .bg(ThemeColor::Surface),
     ]))
     .right_aligned();
     frame.render_widget(label, title_line[2]);
 
+    match state.panes.len() {
+        0 | 1 => {
+            state.click_areas.panes.clear();
+            render_tab(state, frame, screen[1])
+        }
+        _ => render_panes(state, frame, screen[1]),
+    }
+
+    render_status(state, frame, bottom[0]);
+    if let Some(input) = state.input.as_ref() {
+        frame.render_widget(Line::from(format!(":{input}")), bottom[1]);
+    } else if let Some(message) = state.message.as_ref() {
+        frame.render_widget(Line::from(message.as_str()).fg(ThemeColor::Love), bottom[1]);
+    }
+
+    if state.help {
+        render_help(state, frame, screen[1]);
+    }
+    if let Some(selected) = state.buffer_list {
+        render_buffers(state, selected, frame, screen[1]);
+    }
+    if state.input.is_some() && !state.completions.is_empty() {
+        render_completions(state, frame, screen[1], bottom[0]);
+    }
+}
+
+/// Renders the tab `state` is on.
+fn render_tab(state: &mut State, frame: &mut Frame, rect: Rect) {
     match state.tab {
-        Tab::Data => {
-            render_data(state, frame, screen[1]);
+        Tab::Data => render_data(state, frame, rect),
+        Tab::Metadata => render_metadata(state, frame, rect),
+        Tab::Profile => render_profile(state, frame, rect),
+        Tab::Partitions => render_partitions(state, frame, rect),
+        Tab::History => render_history(state, frame, rect),
+        Tab::Snapshots => render_snapshots(state, frame, rect),
+        Tab::Schemas => render_schemas(state, frame, rect),
+        Tab::DataFiles => render_data_files(state, frame, rect),
+        Tab::Diff => render_diff(state, frame, rect),
+        Tab::RowDiff => render_row_diff(state, frame, rect),
+        Tab::Files => render_files(state, frame, rect),
+    }
+}
+
+/// Renders each pane of a split screen, under a line naming what it shows.
+fn render_panes(state: &mut State, frame: &mut Frame, rect: Rect) {
+    let n = state.panes.len() as u32;
+    let rects = Layout::default()
+        .direction(state.split)
+        .constraints((0..n).map(|_| Constraint::Ratio(1, n)))
+        .spacing(1)
+        .split(rect);
+    state.click_areas.panes = rects.to_vec();
+
+    let panes = state.panes.clone();
+    let active = state.active_pane;
+    // The active pane last, so what it leaves behind, such as click areas,
+    // is what keys and clicks act on.
+    let order = (0..panes.len()).filter(|i| *i != active).chain([active]);
+    for i in order {
+        let Some(pane) = panes.get(i) else {
+            continue;
+        };
+        let [title, body] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(rects[i]);
+        let buffer = state.buffer_mut(pane.buffer);
+        let style = match i == active {
+            true => Style::new().fg(ThemeColor::Iris.into()).bold(),
+            false => Style::new().fg(ThemeColor::Subtle.into()),
+        };
+        frame.render_widget(
+            Line::from(format!(
+                " {} · {} ",
+                buffer.viewer.file_stem,
+                pane.tab.header()
+            ))
+            .style(style)
+            .bg(ThemeColor::Surface),
+            title,
+        );
+        let tab = mem::replace(&mut buffer.tab, pane.tab);
+        // The active pane's scroll is the buffer's own, which the others
+        // showing the same file leave as it was.
+        let (scroll, first_row, visual) = (buffer.scroll(), buffer.viewer.first_row, buffer.visual);
+        if let Err(e) = buffer.set_scroll(pane.scroll) {
+            buffer.message = Some(e.to_string());
+        }
+        render_tab(buffer, frame, body);
+        let rendered = buffer.scroll();
+        if i != active {
+            let _ = buffer.set_scroll(scroll);
+            // Unless other rows were read meanwhile, which it'd be off in.
+            if buffer.viewer.first_row == first_row {
+                buffer.visual = visual;
+            }
         }
-        Tab::Metadata => render_metadata(state, frame, screen[1]),
+        buffer.tab = tab;
+        state.panes[i].scroll = rendered;
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub enum Tab {
-    Data = 0,
-    Metadata = 1,
+/// Lists the open files over the current tab, with `selected` highlighted.
+fn render_buffers(state: &State, selected: usize, frame: &mut Frame, rect: Rect) {
+    let buffers: Vec<_> = (0..state.num_buffers()).map(|i| state.buffer(i)).collect();
+    let name_width = buffers
+        .iter()
+        .map(|b| b.viewer.file_stem.len())
+        .max()
+        .unwrap_or(0);
+    let lines: Vec<_> = buffers
+        .iter()
+        .enumerate()
+        .map(|(i, buffer)| {
+            let viewer = &buffer.viewer;
+            let current = if i == state.buffer_index { "%" } else { " " };
+            let rows = match viewer.num_source_rows() {
+                Some(n) => format!("{} rows", format_count(n)),
+                None => String::new(),
+            };
+            let line = Line::from(vec![
+                Span::from(format!(" {:>2} {current} ", i + 1)).fg(ThemeColor::Subtle),
+                Span::from(format!("{:name_width$}  ", viewer.file_stem)),
+                Span::from(format!("{:<14} ", viewer.format.name())).fg(ThemeColor::Subtle),
+                Span::from(format!("{rows} ")).fg(ThemeColor::Gold),
+            ]);
+            if i == selected {
+                line.bg(ThemeColor::HighlightMed)
+            } else {
+                line
+            }
+        })
+        .collect();
+
+    let hint = " Enter to switch, Esc to close ";
+    let width = lines
+        .iter()
+        .map(|l| l.width())
+        .max()
+        .unwrap_or(0)
+        .max(hint.len()) as u16
+        + 2;
+    let height = lines.len() as u16 + 2;
+    let area = Rect {
+        x: rect.x + rect.width.saturating_sub(width) / 2,
+        y: rect.y + rect.height.saturating_sub(height) / 2,
+        width: width.min(rect.width),
+        height: height.min(rect.height),
+    };
+    let block = Block::bordered()
+        .title(" Buffers ")
+        .title_bottom(Line::from(hint).right_aligned())
+        .fg(ThemeColor::Subtle)
+        .bg(ThemeColor::Surface);
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines).fg(ThemeColor::Text).block(block),
+        area,
+    );
+}
+
+/// Shows where the Data tab is, the selected cell in full, how the rows are
+/// filtered and sorted, and how far profiling has got.
+fn render_status(state: &State, frame: &mut Frame, rect: Rect) {
+    let viewer = &state.viewer;
+    let separator = || Span::from("│").fg(ThemeColor::Subtle);
+    let mut left = vec![];
+    if viewer.batch.num_rows() == 0 {
+        left.push(Span::from(" no rows "));
+    } else {
+        let row = viewer.first_row + viewer.selected_row;
+        // Until they've all been read, queried rows can't be counted.
+        let total = match viewer.num_data_rows {
+            Some(n) => format_count(n),
+            None => format!(
+                "{}+",
+                format_count(viewer.first_row + viewer.batch.num_rows())
+            ),
+        };
+        left.push(Span::from(format!(
+            " row {} / {total} ",
+            format_count(row + 1)
+        )));
+        if let Some(row_group) = viewer.row_group(row).filter(|_| !state.view.is_query()) {
+            left.push(separator());
+            left.push(Span::from(format!(
+                " row group {} / {} ",
+                row_group + 1,
+                viewer.num_row_groups
+            )));
+        }
+    }
+    if let Some(field) = viewer.batch.schema().fields().get(viewer.selected_col) {
+        left.push(separator());
+        left.push(Span::from(format!(" {}", field.name())).fg(ThemeColor::Iris));
+        left.push(Span::from(format!(": {} ", field.data_type())).fg(ThemeColor::Subtle));
+    }
+    if let Some(value) = state.selected_value() {
+        left.push(separator());
+        // On one line.
+        let value = value.replace(['\n', '\r', '\t'], " ");
+        left.push(Span::from(format!(" {value} ")).fg(ThemeColor::Foam));
+    }
+
+    let mut right = vec![];
+    if viewer
+        .iceberg()
+        .is_some_and(|t| t.deletes_warning().is_some())
+    {
+        right.push(Span::from(" deletes not applied ").fg(ThemeColor::Love));
+    }
+    let view = &state.view;
+    if view.sql.is_some() {
+        right.push(Span::from(" sql ").fg(ThemeColor::Gold));
+    }
+    if let Some(filter) = &view.filter {
+        right.push(Span::from(format!(" filter {filter} ")).fg(ThemeColor::Gold));
+    }
+    if let Some((column, descending)) = &view.sort {
+        let order = if *descending { "desc" } else { "asc" };
+        right.push(Span::from(format!(" sort {column} {order} ")).fg(ThemeColor::Gold));
+    }
+    if !view.hidden.is_empty() {
+        let hidden = format!(" {} hidden ", view.hidden.len());
+        right.push(Span::from(hidden).fg(ThemeColor::Gold));
+    }
+    if let Some(column) = &state.profiling {
+        let rows = state.profile_progress;
+        let total = match (viewer.num_source_rows(), state.profile_limit) {
+            (Some(n), Some(limit)) => Some(n.min(limit)),
+            (n, limit) => n.or(limit),
+        };
+        let progress = match total.filter(|t| *t > 0) {
+            Some(total) => format!("{}%", (rows * 100 / total).min(100)),
+            None => format!("{} rows", format_count(rows)),
+        };
+        right.push(Span::from(format!(" profiling {column} {progress} ")).fg(ThemeColor::Gold));
+    }
+    let right = Line::from(
+        right
+            .into_iter()
+            .flat_map(|span| [separator(), span])
+            .collect::<Vec<_>>(),
+    );
+
+    let layout = Layout::horizontal([Constraint::Min(0), Constraint::Length(right.width() as u16)])
+        .split(rect);
+    frame.render_widget(Block::new().bg(ThemeColor::Surface), rect);
+    frame.render_widget(Line::from(left), layout[0]);
+    frame.render_widget(right, layout[1]);
 }
 
-impl Default for Tab {
-    fn default() -> Self {
-        Self::Data
+/// Formats `n` with thousands separators, e.g. `48,000,000`.
+fn format_count(n: usize) -> String {
+    let digits = n.to_string();
+    let mut formatted = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            formatted.push(',');
+        }
+        formatted.push(digit);
     }
+    formatted
+}
+
+/// Completions of the command line shown at most at once.
+const MAX_COMPLETIONS: usize = 10;
+
+/// Lists completions of the command line just above it, over the bottom of
+/// the current tab.
+fn render_completions(state: &State, frame: &mut Frame, rect: Rect, input: Rect) {
+    let selected = state.completion.unwrap_or(0);
+    // Scrolls along when tab goes past the completions shown.
+    let first = (selected + 1).saturating_sub(MAX_COMPLETIONS);
+    let shown = &state.completions[first..state.completions.len().min(first + MAX_COMPLETIONS)];
+    let line_width = shown.iter().map(|c| c.line.len()).max().unwrap_or(0);
+    let lines: Vec<_> = shown
+        .iter()
+        .enumerate()
+        .map(|(i, completion)| {
+            let line = Line::from(vec![
+                Span::from(format!(" {:line_width$}  ", completion.line)),
+                Span::from(format!("{} ", completion.hint)).fg(ThemeColor::Subtle),
+            ]);
+            if state.completion == Some(first + i) {
+                line.bg(ThemeColor::HighlightMed)
+            } else {
+                line
+            }
+        })
+        .collect();
+
+    let width = lines.iter().map(|l| l.width()).max().unwrap_or(0) as u16;
+    let height = (lines.len() as u16).min(rect.height);
+    let area = Rect {
+        x: input.x,
+        y: input.y.saturating_sub(height).max(rect.y),
+        width: width.min(rect.width),
+        height,
+    };
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .fg(ThemeColor::Text)
+            .bg(ThemeColor::Surface),
+        area,
+    );
+}
+
+/// Lists the active key bindings over the current tab.
+fn render_help(state: &State, frame: &mut Frame, rect: Rect) {
+    let help = state.keymap.help();
+    let name_width = help.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    let lines: Vec<_> = help
+        .iter()
+        .map(|(name, chords)| {
+            let keys = match chords.is_empty() {
+                true => Span::from("unbound").fg(ThemeColor::Subtle),
+                false => Span::from(chords.join(", ")).fg(ThemeColor::Gold),
+            };
+            Line::from(vec![Span::from(format!(" {name:name_width$}  ")), keys])
+        })
+        .collect();
+
+    let width = lines.iter().map(|l| l.width()).max().unwrap_or(0) as u16 + 3;
+    let height = lines.len() as u16 + 2;
+    let area = Rect {
+        x: rect.x + rect.width.saturating_sub(width) / 2,
+        y: rect.y + rect.height.saturating_sub(height) / 2,
+        width: width.min(rect.width),
+        height: height.min(rect.height),
+    };
+    let block = Block::bordered()
+        .title(" Keys ")
+        .title_bottom(Line::from(" ? or Esc to close ").right_aligned())
+        .fg(ThemeColor::Subtle)
+        .bg(ThemeColor::Surface);
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines).fg(ThemeColor::Text).block(block),
+        area,
+    );
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Tab {
+    #[default]
+    Data = 0,
+    Metadata = 1,
+    Profile = 2,
+    Partitions = 3,
+    History = 4,
+    Snapshots = 5,
+    Schemas = 6,
+    DataFiles = 7,
+    Diff = 8,
+    RowDiff = 9,
+    Files = 10,
 }
 
 impl Tab {
     const fn get_headers() -> &'static [&'static str] {
-        &["Data", "Metadata"]
+        &[
+            "Data",
+            "Metadata",
+            "Profile",
+            "Partitions",
+            "History",
+            "Snapshots",
+            "Schemas",
+            "Data files",
+            "Diff",
+            "Row diff",
+            "Files",
+        ]
+    }
+
+    pub fn header(self) -> &'static str {
+        Self::get_headers()[self as usize]
     }
 }
 
@@ -88,22 +491,32 @@ impl From<usize> for Tab {
         match v {
             0 => Self::Data,
             1 => Self::Metadata,
+            2 => Self::Profile,
+            3 => Self::Partitions,
+            4 => Self::History,
+            5 => Self::Snapshots,
+            6 => Self::Schemas,
+            7 => Self::DataFiles,
+            8 => Self::Diff,
+            9 => Self::RowDiff,
+            10 => Self::Files,
             _ => Self::default(),
         }
     }
 }
 
+/// Shown next to the columns of an encrypted file that couldn't be decrypted.
+const ENCRYPTED_BADGE: &str = "encrypted";
+/// Shown next to the columns of a salvaged file with chunks that couldn't be
+/// decoded.
+const DAMAGED_BADGE: &str = "damaged";
+
 pub const N_TABS: usize = Tab::get_headers().len() - 1;
 
 pub fn render_metadata(state: &mut State, frame: &mut Frame, rect: Rect) {
-    let kv_n = state.viewer.file_kv_data.len();
-    let metadata_height = if kv_n == 0 { 4 } else { 5 + kv_n } as u16;
-    let layout = Layout::vertical([
-        Constraint::Length(metadata_height),
-        Constraint::Min(5),
-        Constraint::Length(5),
-    ])
-    .split(rect);
+    if state.viewer.format != Format::Parquet {
+        return render_arrow_metadata(state, frame, rect);
+    }
     let file_metadata_block = Block::bordered()
         .title("File metadata".bold())
         .fg(ThemeColor::Subtle);
@@ -123,7 +536,7 @@ pub fn render_metadata(state: &mut State, frame: &mut Frame, rect: Rect) {
         ]),
         Line::from(vec![
             Span::styled("# rows: ", Style::default().fg(ThemeColor::Love.into())),
-            Span::raw(state.viewer.num_rows.to_string()),
+            Span::raw(state.viewer.num_rows.unwrap_or_default().to_string()),
             Span::styled("  |  ", Style::default().fg(ThemeColor::Rose.into())),
             Span::styled("# columns: ", Style::default().fg(ThemeColor::Love.into())),
             Span::raw(state.viewer.num_cols.to_string()),
@@ -135,6 +548,59 @@ pub fn render_metadata(state: &mut State, frame: &mut Frame, rect: Rect) {
             Span::raw(state.viewer.num_row_groups.to_string()),
         ]),
     ];
+    if let Some(encryption) = state.viewer.encryption.as_ref() {
+        let footer = if encryption.encrypted_footer {
+            "encrypted"
+        } else {
+            "plaintext"
+        };
+        file_metadata_lines.push(Line::from(vec![
+            Span::styled("Encryption: ", Style::default().fg(ThemeColor::Love.into())),
+            Span::raw(encryption.algorithm),
+            Span::styled("  |  ", Style::default().fg(ThemeColor::Rose.into())),
+            Span::styled("Footer: ", Style::default().fg(ThemeColor::Love.into())),
+            Span::raw(footer),
+        ]));
+        let mut columns = vec![Span::styled(
+            "Encrypted columns: ",
+            Style::default().fg(ThemeColor::Love.into()),
+        )];
+        for (i, column) in encryption.columns.iter().enumerate() {
+            if i > 0 {
+                columns.push(Span::raw(", "));
+            }
+            let key = if column.column_key {
+                "column key"
+            } else {
+                "footer key"
+            };
+            columns.push(Span::raw(format!("{} ({key}", column.path)));
+            if !column.decrypted {
+                columns.push(Span::raw(", "));
+                columns.push(Span::styled(
+                    "no key",
+                    Style::default().fg(ThemeColor::Love.into()),
+                ));
+            }
+            columns.push(Span::raw(")"));
+        }
+        file_metadata_lines.push(Line::from(columns));
+        if !encryption.missing_keys().is_empty() {
+            file_metadata_lines.push(Line::from(
+                "Columns without a key aren't read, and their chunk metadata isn't known",
+            ));
+        }
+    }
+    if let Some(salvage) = state.viewer.salvage() {
+        let num_chunks = state.viewer.num_row_groups * state.viewer.schema.fields().len();
+        file_metadata_lines.push(Line::from(vec![
+            Span::styled("Salvaged: ", Style::default().fg(ThemeColor::Love.into())),
+            Span::raw(format!(
+                "{} of {num_chunks} column chunks couldn't be decoded, and are shown as nulls",
+                salvage.damaged.len()
+            )),
+        ]));
+    }
     if !state.viewer.file_kv_data.is_empty() {
         file_metadata_lines.push(Line::from(vec![Span::styled(
             "Other",
@@ -152,6 +618,12 @@ pub fn render_metadata(state: &mut State, frame: &mut Frame, rect: Rect) {
             file_metadata_lines.push(l);
         }
     };
+    let layout = Layout::vertical([
+        Constraint::Length(file_metadata_lines.len() as u16 + 2),
+        Constraint::Min(5),
+        Constraint::Length(5),
+    ])
+    .split(rect);
     let file_meta_data_widget = Paragraph::new(file_metadata_lines)
         .block(file_metadata_block)
         .fg(ThemeColor::Text);
@@ -159,20 +631,38 @@ pub fn render_metadata(state: &mut State, frame: &mut Frame, rect: Rect) {
     frame.render_widget(file_meta_data_widget, layout[0]);
 
     let mut rows = vec![];
+    let mut name_width = state.viewer.max_col_name_width;
     for (i, r) in state.viewer.schema_table_data.iter().enumerate() {
         let bg_color = if i % 2 == 0 {
             ThemeColor::HighlightLow
         } else {
             ThemeColor::Base
         };
-        let row = Row::new(r.iter().map(|s| s.as_str()).collect::<Vec<_>>());
-        rows.push(row.fg(ThemeColor::Text).bg(bg_color));
+        let mut cells: Vec<_> = r.iter().map(|s| Cell::from(s.as_str())).collect();
+        let badge = if !is_decrypted(state, &r[0]) {
+            Some(ENCRYPTED_BADGE)
+        } else if !damaged_chunks(state, i).is_empty() {
+            Some(DAMAGED_BADGE)
+        } else {
+            None
+        };
+        if let Some(badge) = badge {
+            cells[0] = Cell::from(Line::from(vec![
+                Span::raw(&r[0]),
+                Span::styled(
+                    format!(" {badge}"),
+                    Style::default().fg(ThemeColor::Love.into()),
+                ),
+            ]));
+            name_width = name_width.max(r[0].len() + 1 + badge.len());
+        }
+        rows.push(Row::new(cells).fg(ThemeColor::Text).bg(bg_color));
     }
 
     let table = Table::new(
         rows,
         [
-            Constraint::Max(state.viewer.max_col_name_width as u16),
+            Constraint::Max(name_width as u16),
             // Constraint::Min(state.viewer.max_col_name_width as u16),
             Constraint::Max(11),
             Constraint::Min(12),
@@ -182,30 +672,37 @@ pub fn render_metadata(state: &mut State, frame: &mut Frame, rect: Rect) {
             Constraint::Max(5),
             Constraint::Max(10),
             Constraint::Max(10),
+            Constraint::Max(10),
+            Constraint::Max(8),
             // Constraint::Min(0),
         ],
     )
     .column_spacing(1)
-    .header(
-        Row::new(vec![
-            "Name",
-            "Column type",
-            "Logical type",
-            "Converted type",
-            "Physical type",
-            "Type length",
-            "Scale",
-            "Precision",
-            "Sort order",
-        ])
-        .fg(ThemeColor::Love),
-    )
+    .header(Row::new(SCHEMA_HEADERS.to_vec()).fg(ThemeColor::Love))
     .highlight_style(Style::default().fg(ThemeColor::Iris.into()).bold())
     .block(parquet_schema_block);
 
     let col_selected = state.table_state.selected().unwrap();
+    frame.render_stateful_widget(table, layout[1], &mut state.table_state);
+    // Inside the border, below the header.
+    let inner = layout[1].inner(Margin::new(1, 1));
+    state.click_areas.schema_rows = Rect {
+        y: inner.y + 1,
+        height: inner.height.saturating_sub(1),
+        ..inner
+    };
 
-    let chunk_meta = state.viewer.row_groups[state.chunk_ind].column(col_selected);
+    let Some(row_group) = state.viewer.row_groups.get(state.chunk_ind) else {
+        let chunk_block = Block::bordered()
+            .title("Chunk metadata".bold())
+            .fg(ThemeColor::Subtle);
+        let p = Paragraph::new("No row groups, as the file has no rows")
+            .block(chunk_block)
+            .fg(ThemeColor::Text);
+        frame.render_widget(p, layout[2]);
+        return;
+    };
+    let chunk_meta = row_group.column(col_selected);
     let col_name = chunk_meta.column_descr().name();
     let num_values = chunk_meta.num_values().to_string();
     let encodings = chunk_meta
@@ -218,6 +715,7 @@ pub fn render_metadata(state: &mut State, frame: &mut Frame, rect: Rect) {
     let compressed_size = chunk_meta.compressed_size() / 1000;
     let uncompressed_size = chunk_meta.uncompressed_size() / 1000;
 
+    let col_path = chunk_meta.column_descr().path().string();
     let mut chunk_metadata_lines = vec![
         Line::from(vec![
             Span::styled("# values: ", Style::default().fg(ThemeColor::Love.into())),
@@ -244,7 +742,21 @@ pub fn render_metadata(state: &mut State, frame: &mut Frame, rect: Rect) {
         ]),
     ];
 
-    if let Some(stats) = chunk_meta.statistics() {
+    let damaged = damaged_chunks(state, col_selected)
+        .into_iter()
+        .find(|c| c.row_group == state.chunk_ind);
+    if !is_decrypted(state, &col_path) {
+        chunk_metadata_lines =
+            vec![
+                Line::from("Encrypted, and no key was given to decrypt its metadata")
+                    .fg(ThemeColor::Love),
+            ];
+    } else if let Some(damaged) = damaged {
+        chunk_metadata_lines.push(
+            Line::from(format!("Damaged, so shown as nulls: {}", damaged.error))
+                .fg(ThemeColor::Love),
+        );
+    } else if let Some(stats) = chunk_meta.statistics() {
         let distinct_cnt = stats
             .distinct_count_opt()
             .map_or("".to_string(), |v| v.to_string());
@@ -358,134 +870,1388 @@ pub fn render_metadata(state: &mut State, frame: &mut Frame, rect: Rect) {
     let p = Paragraph::new(chunk_metadata_lines)
         .block(chunk_block)
         .fg(ThemeColor::Text);
-    frame.render_stateful_widget(table, layout[1], &mut state.table_state);
     frame.render_widget(p, layout[2]);
 }
 
-fn render_data(state: &mut State, frame: &mut Frame, rect: Rect) -> Result<()> {
-    let col_names: Vec<_> = state
+/// Whether the column at `path` is either in plaintext or could be decrypted.
+fn is_decrypted(state: &State, path: &str) -> bool {
+    state
         .viewer
-        .batch
-        .schema_ref()
-        .fields()
+        .encryption
+        .as_ref()
+        .and_then(|e| e.column(path))
+        .is_none_or(|c| c.decrypted)
+}
+
+/// Chunks of the top-level column of the `col`-th leaf column that couldn't
+/// be decoded, when salvaging a damaged file.
+fn damaged_chunks(state: &State, col: usize) -> Vec<&DamagedChunk> {
+    let (Some(salvage), Some(row_group)) =
+        (state.viewer.salvage(), state.viewer.row_groups.first())
+    else {
+        return vec![];
+    };
+    let root = row_group.schema_descr().get_column_root(col).name();
+    salvage
+        .damaged
         .iter()
-        .skip(state.viewer.col_offset)
-        .map(|f| f.name().to_owned())
-        .take(state.viewer.visible_cols)
-        .collect();
-    let table_slice = batch_slice(
-        &state.viewer.batch,
-        state.viewer.row_offset,
-        state.viewer.col_offset,
-        state.viewer.visible_rows,
-        state.viewer.visible_cols,
+        .filter(|c| c.column == root)
+        .collect()
+}
+
+/// Metadata tab for formats other than Parquet, which only have an Arrow schema.
+fn render_arrow_metadata(state: &mut State, frame: &mut Frame, rect: Rect) {
+    let mut file_metadata_lines = vec![Line::from(vec![
+        Span::styled("Format: ", Style::default().fg(ThemeColor::Love.into())),
+        Span::raw(state.viewer.format.name()),
+        Span::styled("  |  ", Style::default().fg(ThemeColor::Rose.into())),
+        Span::styled("# columns: ", Style::default().fg(ThemeColor::Love.into())),
+        Span::raw(state.viewer.num_cols.to_string()),
+    ])];
+    for (k, v) in state.viewer.format_details.iter() {
+        file_metadata_lines.push(Line::from(vec![
+            Span::styled(
+                format!("{}: ", k),
+                Style::default().fg(ThemeColor::Love.into()),
+            ),
+            Span::raw(v),
+        ]));
+    }
+    if !state.viewer.file_kv_data.is_empty() {
+        file_metadata_lines.push(Line::from(vec![Span::styled(
+            "Other",
+            Style::default().fg(ThemeColor::Rose.into()).bold(),
+        )]));
+        for (k, v) in state.viewer.file_kv_data.iter() {
+            file_metadata_lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{}: ", k),
+                    Style::default().fg(ThemeColor::Love.into()),
+                ),
+                Span::raw(v),
+            ]));
+        }
+    }
+    let layout = Layout::vertical([
+        Constraint::Length(file_metadata_lines.len() as u16 + 2),
+        Constraint::Min(5),
+    ])
+    .split(rect);
+
+    let file_metadata_block = Block::bordered()
+        .title("File metadata".bold())
+        .fg(ThemeColor::Subtle);
+    frame.render_widget(
+        Paragraph::new(file_metadata_lines)
+            .block(file_metadata_block)
+            .fg(ThemeColor::Text),
+        layout[0],
     );
 
-    let col_width = 21_usize;
-    let col_constraints =
-        Constraint::from_lengths(vec![col_width as u16; state.viewer.visible_cols]);
-    let col_layout = Layout::horizontal(col_constraints).split(rect);
-    for (i, (data, name)) in table_slice.iter().zip(col_names).enumerate() {
-        let mut lines = vec![Line::from(mask_string(&name, col_width - 1)).fg(ThemeColor::Love)];
-        lines.extend(data.iter().enumerate().map(|(j, c)| {
-            let bg_color = if j % 2 == 0 {
+    let rows = state
+        .viewer
+        .schema
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let bg_color = if i % 2 == 0 {
                 ThemeColor::HighlightLow
             } else {
                 ThemeColor::Base
             };
-            let fg_color = if ((i + state.viewer.col_offset) == state.viewer.selected_col)
-                && ((j + state.viewer.row_offset) == state.viewer.selected_row)
-            {
-                ThemeColor::Iris
-            } else {
-                ThemeColor::Text
-            };
-            let s = mask_string(c.as_str(), col_width - 1);
-            Line::from(format!("{s} "))
-                .bg(bg_color)
-                .fg(fg_color)
-                .alignment(Alignment::Right)
-        }));
-        let col = Text::from(lines);
-        frame.render_widget(col, col_layout[i]);
-    }
-
-    Ok(())
+            let mut metadata: Vec<_> = field
+                .metadata()
+                .iter()
+                .map(|(k, v)| format!("{k}={v}"))
+                .collect();
+            metadata.sort();
+            Row::new(vec![
+                field.name().to_string(),
+                field.data_type().to_string(),
+                field.is_nullable().to_string(),
+                metadata.join(", "),
+            ])
+            .fg(ThemeColor::Text)
+            .bg(bg_color)
+        })
+        .collect::<Vec<_>>();
+    let schema_block = Block::bordered()
+        .title("Schema".bold())
+        .title(Title::from("(↑/↓)").alignment(Alignment::Center))
+        .fg(ThemeColor::Subtle);
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Max(state.viewer.max_col_name_width as u16),
+            Constraint::Min(20),
+            Constraint::Max(8),
+            Constraint::Min(0),
+        ],
+    )
+    .column_spacing(1)
+    .header(Row::new(vec!["Name", "Data type", "Nullable", "Metadata"]).fg(ThemeColor::Love))
+    .highlight_style(Style::default().fg(ThemeColor::Iris.into()).bold())
+    .block(schema_block);
+    frame.render_stateful_widget(table, layout[1], &mut state.table_state);
 }
 
-fn mask_string(s: &str, max_len: usize) -> &str {
-    if s.chars().count() > max_len {
-        let mut char_indices = s.char_indices();
-        let truncate_at = char_indices.nth(max_len).map_or(s.len(), |(idx, _)| idx);
-        &s[..truncate_at]
+pub fn render_profile(state: &mut State, frame: &mut Frame, rect: Rect) {
+    let title = match state.profiling.as_ref() {
+        Some(column) => Line::from(vec![
+            Span::raw("Profile: "),
+            Span::styled(
+                format!("profiling {column}…"),
+                Style::default().fg(ThemeColor::Gold.into()),
+            ),
+        ]),
+        None => Line::from("Profile"),
+    };
+    let summary_block = Block::bordered()
+        .title(title.bold())
+        .title(Title::from("(←/→)").alignment(Alignment::Center))
+        .fg(ThemeColor::Subtle);
+    let Some(profile) = state.profile.as_ref() else {
+        frame.render_widget(summary_block, rect);
+        return;
+    };
+
+    let separator = || Span::styled("  |  ", Style::default().fg(ThemeColor::Rose.into()));
+    let label = |s: &'static str| Span::styled(s, Style::default().fg(ThemeColor::Love.into()));
+    let null_fraction = if profile.num_rows == 0 {
+        0.0
     } else {
-        s
+        profile.null_count as f64 / profile.num_rows as f64
+    };
+    let scanned = if profile.sampled {
+        format!("{} (sampled)", profile.num_rows)
+    } else {
+        profile.num_rows.to_string()
+    };
+    let mut summary_lines = vec![
+        Line::from(vec![
+            label("Column: "),
+            Span::styled(
+                profile.column.as_str(),
+                Style::default().fg(ThemeColor::Iris.into()),
+            ),
+            separator(),
+            label("Type: "),
+            Span::raw(profile.data_type.to_string()),
+        ]),
+        Line::from(vec![
+            label("# rows: "),
+            Span::raw(scanned),
+            separator(),
+            label("# null: "),
+            Span::raw(format!(
+                "{} ({:.1}%)",
+                profile.null_count,
+                null_fraction * 100.0
+            )),
+            separator(),
+            label("# distinct: "),
+            Span::raw(format!("≈{}", profile.distinct)),
+        ]),
+    ];
+    if let Some(numeric) = profile.numeric.as_ref() {
+        summary_lines.push(Line::from(vec![
+            label("Min: "),
+            Span::raw(format_number(numeric.min)),
+            separator(),
+            label("Max: "),
+            Span::raw(format_number(numeric.max)),
+            separator(),
+            label("Mean: "),
+            Span::raw(format_number(numeric.mean)),
+            separator(),
+            label("Std dev: "),
+            Span::raw(format_number(numeric.stddev)),
+        ]));
+        let mut quantiles = vec![];
+        for (q, v) in QUANTILES.iter().zip(numeric.quantiles.iter()) {
+            if !quantiles.is_empty() {
+                quantiles.push(separator());
+            }
+            quantiles.push(Span::styled(
+                format!("p{}: ", q * 100.0),
+                Style::default().fg(ThemeColor::Love.into()),
+            ));
+            quantiles.push(Span::raw(format_number(*v)));
+        }
+        summary_lines.push(Line::from(quantiles));
     }
-}
-fn batch_slice(
-    batch: &RecordBatch,
-    row: usize,
+    if let Some(lengths) = profile.lengths.as_ref() {
+        summary_lines.push(Line::from(vec![
+            label("Min length: "),
+            Span::raw(lengths.min.to_string()),
+            separator(),
+            label("Max length: "),
+            Span::raw(lengths.max.to_string()),
+            separator(),
+            label("Mean length: "),
+            Span::raw(format!("{:.1}", lengths.mean)),
+        ]));
+    }
+
+    let layout = Layout::vertical([
+        Constraint::Length(summary_lines.len() as u16 + 2),
+        Constraint::Min(5),
+    ])
+    .split(rect);
+    let charts = Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(layout[1]);
+    frame.render_widget(
+        Paragraph::new(summary_lines)
+            .block(summary_block)
+            .fg(ThemeColor::Text),
+        layout[0],
+    );
+
+    if let Some(numeric) = profile.numeric.as_ref() {
+        let block = Block::bordered()
+            .title("Histogram".bold())
+            .title(
+                Title::from(format!(
+                    "{} … {}",
+                    format_number(numeric.min),
+                    format_number(numeric.max)
+                ))
+                .alignment(Alignment::Right),
+            )
+            .fg(ThemeColor::Subtle);
+        let bars: Vec<_> = numeric
+            .histogram
+            .iter()
+            .map(|&n| Bar::default().value(n).text_value(String::new()))
+            .collect();
+        let inner_width = charts[0].width.saturating_sub(2) as usize;
+        let bar_width = (inner_width / bars.len().max(1)).saturating_sub(1).max(1);
+        let chart = BarChart::default()
+            .block(block)
+            .data(BarGroup::default().bars(&bars))
+            .bar_width(bar_width as u16)
+            .bar_gap(1)
+            .bar_style(Style::default().fg(ThemeColor::Foam.into()));
+        frame.render_widget(chart, charts[0]);
+    } else if let Some(lengths) = profile.lengths.as_ref() {
+        let block = Block::bordered()
+            .title("Length distribution".bold())
+            .title(
+                Title::from(format!("{} … {} chars", lengths.min, lengths.max))
+                    .alignment(Alignment::Right),
+            )
+            .fg(ThemeColor::Subtle);
+        let sparkline = Sparkline::default()
+            .block(block)
+            .data(&lengths.counts)
+            .style(Style::default().fg(ThemeColor::Foam.into()));
+        frame.render_widget(sparkline, charts[0]);
+    } else {
+        frame.render_widget(
+            Block::bordered()
+                .title("Distribution".bold())
+                .fg(ThemeColor::Subtle),
+            charts[0],
+        );
+    }
+
+    let top_title = if profile.top_values_approximate {
+        "Top values (approximate counts)"
+    } else {
+        "Top values"
+    };
+    let label_width = charts[1].width.saturating_sub(2) as usize / 2;
+    let bars: Vec<_> = profile
+        .top_values
+        .iter()
+        .map(|(value, n)| {
+            Bar::default()
+                .value(*n)
+                .label(Line::from(mask_string(value, label_width).to_string()))
+                .text_value(n.to_string())
+        })
+        .collect();
+    let chart = BarChart::default()
+        .block(
+            Block::bordered()
+                .title(top_title.bold())
+                .fg(ThemeColor::Subtle),
+        )
+        .direction(Direction::Horizontal)
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(1)
+        .bar_gap(0)
+        .bar_style(Style::default().fg(ThemeColor::Iris.into()))
+        .value_style(
+            Style::default()
+                .fg(ThemeColor::Base.into())
+                .bg(ThemeColor::Iris.into()),
+        )
+        .label_style(Style::default().fg(ThemeColor::Text.into()));
+    frame.render_widget(chart, charts[1]);
+}
+
+/// Formats `x` without the noise of long fractions.
+fn format_number(x: f64) -> String {
+    if x.fract() == 0.0 && x.abs() < 1e15 {
+        format!("{x:.0}")
+    } else if x.abs() >= 1e6 || x.abs() < 1e-3 {
+        format!("{x:.3e}")
+    } else {
+        format!("{x:.3}")
+    }
+}
+
+pub fn render_partitions(state: &mut State, frame: &mut Frame, rect: Rect) {
+    let Some(partitioning) = state.viewer.partitions() else {
+        return;
+    };
+    let layout = Layout::vertical([Constraint::Length(4), Constraint::Min(5)]).split(rect);
+
+    let dataset_lines = vec![
+        Line::from(vec![
+            Span::styled("Root: ", Style::default().fg(ThemeColor::Love.into())),
+            Span::raw(partitioning.root.display().to_string()),
+        ]),
+        Line::from(vec![
+            Span::styled("Keys: ", Style::default().fg(ThemeColor::Love.into())),
+            Span::raw(partitioning.keys.join(", ")),
+            Span::styled("  |  ", Style::default().fg(ThemeColor::Rose.into())),
+            Span::styled("# files: ", Style::default().fg(ThemeColor::Love.into())),
+            Span::raw(partitioning.files.len().to_string()),
+            Span::styled("  |  ", Style::default().fg(ThemeColor::Rose.into())),
+            Span::styled("# rows: ", Style::default().fg(ThemeColor::Love.into())),
+            Span::raw(partitioning.num_rows().to_string()),
+        ]),
+    ];
+    let dataset_block = Block::bordered()
+        .title("Dataset".bold())
+        .fg(ThemeColor::Subtle);
+    let dataset_widget = Paragraph::new(dataset_lines)
+        .block(dataset_block)
+        .fg(ThemeColor::Text);
+    frame.render_widget(dataset_widget, layout[0]);
+
+    let rows = partitioning
+        .summary()
+        .into_iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let bg_color = if i % 2 == 0 {
+                ThemeColor::HighlightLow
+            } else {
+                ThemeColor::Base
+            };
+            Row::new(vec![
+                p.key,
+                p.value.unwrap_or("null".to_string()),
+                p.num_files.to_string(),
+                p.num_rows.to_string(),
+                format_size(p.size),
+            ])
+            .fg(ThemeColor::Text)
+            .bg(bg_color)
+        })
+        .collect::<Vec<_>>();
+
+    let partitions_block = Block::bordered()
+        .title("Partitions".bold())
+        .title(Title::from("(↑/↓)").alignment(Alignment::Center))
+        .fg(ThemeColor::Subtle);
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(10),
+            Constraint::Min(20),
+            Constraint::Max(10),
+            Constraint::Max(14),
+            Constraint::Max(12),
+        ],
+    )
+    .column_spacing(1)
+    .header(Row::new(vec!["Key", "Value", "# files", "# rows", "Size"]).fg(ThemeColor::Love))
+    .highlight_style(Style::default().fg(ThemeColor::Iris.into()).bold())
+    .block(partitions_block);
+    frame.render_stateful_widget(table, layout[1], &mut state.partition_table_state);
+}
+
+pub fn render_files(state: &mut State, frame: &mut Frame, rect: Rect) {
+    let browser = state.browser();
+    let rows = browser
+        .entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let bg_color = if i % 2 == 0 {
+                ThemeColor::HighlightLow
+            } else {
+                ThemeColor::Base
+            };
+            let indent = "  ".repeat(entry.depth);
+            let (name, preview) = match &entry.kind {
+                EntryKind::Directory { expanded } => {
+                    let marker = if *expanded { "▾" } else { "▸" };
+                    (format!("{indent}{marker} {}", entry.name()), None)
+                }
+                EntryKind::File(preview) => {
+                    (format!("{indent}  {}", entry.name()), preview.as_ref())
+                }
+            };
+            let optional = |v: Option<String>| v.unwrap_or_default();
+            Row::new(vec![
+                name,
+                optional(preview.map(|p| p.format.name().to_string())),
+                optional(
+                    preview
+                        .and_then(|p| p.rows)
+                        .map(|r| format_count(r as usize)),
+                ),
+                optional(preview.and_then(|p| p.columns).map(|c| c.to_string())),
+                optional(preview.map(|p| format_size(p.size))),
+            ])
+            .fg(ThemeColor::Text)
+            .bg(bg_color)
+        })
+        .collect::<Vec<_>>();
+    let block = Block::bordered()
+        .title(format!("Files in {}", browser.root.display()).bold())
+        .title(
+            Title::from("(↑/↓, ←/→ to collapse/expand, Enter to open)")
+                .alignment(Alignment::Center),
+        )
+        .fg(ThemeColor::Subtle);
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(30),
+            Constraint::Max(16),
+            Constraint::Max(12),
+            Constraint::Max(10),
+            Constraint::Max(10),
+        ],
+    )
+    .column_spacing(1)
+    .header(Row::new(vec!["Name", "Format", "# rows", "# columns", "Size"]).fg(ThemeColor::Love))
+    .highlight_style(Style::default().fg(ThemeColor::Iris.into()).bold())
+    .block(block);
+    frame.render_stateful_widget(table, rect, &mut browser.table_state);
+    // Inside the border, below the header.
+    let inner = rect.inner(Margin::new(1, 1));
+    state.click_areas.file_rows = Rect {
+        y: inner.y + 1,
+        height: inner.height.saturating_sub(1),
+        ..inner
+    };
+}
+
+pub fn render_history(state: &mut State, frame: &mut Frame, rect: Rect) {
+    let Some(table) = state.viewer.delta() else {
+        return;
+    };
+    let separator = || Span::styled("  |  ", Style::default().fg(ThemeColor::Rose.into()));
+    let label = |s: &'static str| Span::styled(s, Style::default().fg(ThemeColor::Love.into()));
+
+    let mut table_lines = vec![Line::from(vec![
+        label("Version: "),
+        Span::styled(
+            table.version.to_string(),
+            Style::default().fg(ThemeColor::Iris.into()),
+        ),
+        separator(),
+        label("# files: "),
+        Span::raw(table.partitioning.files.len().to_string()),
+        separator(),
+        label("# rows: "),
+        Span::raw(table.partitioning.num_rows().to_string()),
+    ])];
+    if let Some(metadata) = table.metadata.as_ref() {
+        let mut line = vec![label("Id: "), Span::raw(metadata.id.as_str())];
+        if let Some(name) = metadata.name.as_ref() {
+            line.extend([separator(), label("Name: "), Span::raw(name.as_str())]);
+        }
+        if !metadata.partition_columns.is_empty() {
+            line.extend([
+                separator(),
+                label("Partition columns: "),
+                Span::raw(metadata.partition_columns.join(", ")),
+            ]);
+        }
+        table_lines.push(Line::from(line));
+        if let Some(description) = metadata.description.as_ref() {
+            table_lines.push(Line::from(vec![
+                label("Description: "),
+                Span::raw(description.as_str()),
+            ]));
+        }
+        for (k, v) in metadata.configuration.iter() {
+            table_lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{k}: "),
+                    Style::default().fg(ThemeColor::Love.into()),
+                ),
+                Span::raw(v.as_str()),
+            ]));
+        }
+    }
+    if let Some(protocol) = table.protocol.as_ref() {
+        table_lines.push(Line::from(vec![
+            label("Reader version: "),
+            Span::raw(protocol.min_reader_version.to_string()),
+            separator(),
+            label("Writer version: "),
+            Span::raw(protocol.min_writer_version.to_string()),
+        ]));
+    }
+
+    let layout = Layout::vertical([
+        Constraint::Length(table_lines.len() as u16 + 2),
+        Constraint::Min(5),
+        Constraint::Length(10),
+    ])
+    .split(rect);
+    let table_block = Block::bordered()
+        .title("Delta table".bold())
+        .fg(ThemeColor::Subtle);
+    frame.render_widget(
+        Paragraph::new(table_lines)
+            .block(table_block)
+            .fg(ThemeColor::Text),
+        layout[0],
+    );
+
+    let rows = table
+        .history
+        .iter()
+        .enumerate()
+        .map(|(i, commit)| {
+            let bg_color = if i % 2 == 0 {
+                ThemeColor::HighlightLow
+            } else {
+                ThemeColor::Base
+            };
+            // Commits after the version being viewed are dimmed.
+            let fg_color = if commit.version > table.version {
+                ThemeColor::Subtle
+            } else {
+                ThemeColor::Text
+            };
+            let marker = if commit.version == table.version {
+                "●"
+            } else {
+                ""
+            };
+            Row::new(vec![
+                marker.to_string(),
+                commit.version.to_string(),
+                format_timestamp(commit.timestamp),
+                commit.operation.clone().unwrap_or_default(),
+                commit.added.len().to_string(),
+                commit.removed.len().to_string(),
+            ])
+            .fg(fg_color)
+            .bg(bg_color)
+        })
+        .collect::<Vec<_>>();
+    let history_block = Block::bordered()
+        .title("History".bold())
+        .title(Title::from("(↑/↓)").alignment(Alignment::Center))
+        .fg(ThemeColor::Subtle);
+    let history_table = Table::new(
+        rows,
+        [
+            Constraint::Length(1),
+            Constraint::Max(8),
+            Constraint::Length(19),
+            Constraint::Min(20),
+            Constraint::Max(8),
+            Constraint::Max(10),
+        ],
+    )
+    .column_spacing(1)
+    .header(
+        Row::new(vec![
+            "",
+            "Version",
+            "Timestamp",
+            "Operation",
+            "Added",
+            "Removed",
+        ])
+        .fg(ThemeColor::Love),
+    )
+    .highlight_style(Style::default().fg(ThemeColor::Iris.into()).bold())
+    .block(history_block);
+    frame.render_stateful_widget(history_table, layout[1], &mut state.history_table_state);
+
+    let selected = state.history_table_state.selected().unwrap_or(0);
+    let mut commit_lines = vec![];
+    if let Some(commit) = table.history.get(selected) {
+        for (k, v) in commit.operation_parameters.iter() {
+            commit_lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{k}: "),
+                    Style::default().fg(ThemeColor::Love.into()),
+                ),
+                Span::raw(v.as_str()),
+            ]));
+        }
+        for path in commit.added.iter() {
+            commit_lines.push(Line::from(format!("+ {path}")).fg(ThemeColor::Foam));
+        }
+        for path in commit.removed.iter() {
+            commit_lines.push(Line::from(format!("- {path}")).fg(ThemeColor::Love));
+        }
+    }
+    let commit_block = Block::bordered()
+        .title("Commit".bold())
+        .fg(ThemeColor::Subtle);
+    frame.render_widget(
+        Paragraph::new(commit_lines)
+            .block(commit_block)
+            .fg(ThemeColor::Text),
+        layout[2],
+    );
+}
+
+pub fn render_snapshots(state: &mut State, frame: &mut Frame, rect: Rect) {
+    let Some(table) = state.viewer.iceberg() else {
+        return;
+    };
+    let separator = || Span::styled("  |  ", Style::default().fg(ThemeColor::Rose.into()));
+    let label = |s: &'static str| Span::styled(s, Style::default().fg(ThemeColor::Love.into()));
+
+    let snapshot_id = table
+        .snapshot_id
+        .map_or("none".to_string(), |id| id.to_string());
+    let mut table_lines = vec![
+        Line::from(vec![
+            label("Snapshot: "),
+            Span::styled(snapshot_id, Style::default().fg(ThemeColor::Iris.into())),
+            separator(),
+            label("# files: "),
+            Span::raw(table.data_files.len().to_string()),
+            separator(),
+            label("# rows: "),
+            Span::raw(table.partitioning.num_rows().to_string()),
+        ]),
+        Line::from(vec![
+            label("Format version: "),
+            Span::raw(table.format_version.to_string()),
+            separator(),
+            label("UUID: "),
+            Span::raw(table.table_uuid.clone().unwrap_or_default()),
+        ]),
+        Line::from(vec![
+            label("Location: "),
+            Span::raw(table.location.as_str()),
+        ]),
+        Line::from(vec![
+            label("Metadata: "),
+            Span::raw(table.metadata_path.display().to_string()),
+        ]),
+    ];
+    if let Some(last_updated) = table.last_updated_ms {
+        table_lines.push(Line::from(vec![
+            label("Last updated: "),
+            Span::raw(format_timestamp(last_updated)),
+        ]));
+    }
+    for (k, v) in table.properties.iter() {
+        table_lines.push(Line::from(vec![
+            Span::styled(
+                format!("{k}: "),
+                Style::default().fg(ThemeColor::Love.into()),
+            ),
+            Span::raw(v.as_str()),
+        ]));
+    }
+
+    let layout = Layout::vertical([
+        Constraint::Length(table_lines.len() as u16 + 2),
+        Constraint::Min(5),
+        Constraint::Length(10),
+    ])
+    .split(rect);
+    let table_block = Block::bordered()
+        .title("Iceberg table".bold())
+        .fg(ThemeColor::Subtle);
+    frame.render_widget(
+        Paragraph::new(table_lines)
+            .block(table_block)
+            .fg(ThemeColor::Text),
+        layout[0],
+    );
+
+    let current = table.snapshot().map(|s| s.timestamp_ms);
+    let rows = table
+        .snapshots
+        .iter()
+        .enumerate()
+        .map(|(i, snapshot)| {
+            let bg_color = if i % 2 == 0 {
+                ThemeColor::HighlightLow
+            } else {
+                ThemeColor::Base
+            };
+            // Snapshots after the one being read are dimmed.
+            let fg_color = if current.is_some_and(|t| snapshot.timestamp_ms > t) {
+                ThemeColor::Subtle
+            } else {
+                ThemeColor::Text
+            };
+            let marker = if Some(snapshot.snapshot_id) == table.snapshot_id {
+                "●"
+            } else {
+                ""
+            };
+            let optional = |v: Option<i64>| v.map_or("".to_string(), |v| v.to_string());
+            Row::new(vec![
+                marker.to_string(),
+                snapshot.snapshot_id.to_string(),
+                optional(snapshot.parent_snapshot_id),
+                optional(snapshot.sequence_number),
+                format_timestamp(snapshot.timestamp_ms),
+                optional(snapshot.schema_id.map(i64::from)),
+                snapshot.operation().unwrap_or_default().to_string(),
+            ])
+            .fg(fg_color)
+            .bg(bg_color)
+        })
+        .collect::<Vec<_>>();
+    let snapshots_block = Block::bordered()
+        .title("Snapshots".bold())
+        .title(Title::from("(↑/↓)").alignment(Alignment::Center))
+        .fg(ThemeColor::Subtle);
+    let snapshots_table = Table::new(
+        rows,
+        [
+            Constraint::Length(1),
+            Constraint::Length(19),
+            Constraint::Length(19),
+            Constraint::Max(8),
+            Constraint::Length(19),
+            Constraint::Max(6),
+            Constraint::Min(10),
+        ],
+    )
+    .column_spacing(1)
+    .header(
+        Row::new(vec![
+            "",
+            "Snapshot ID",
+            "Parent",
+            "Sequence",
+            "Timestamp",
+            "Schema",
+            "Operation",
+        ])
+        .fg(ThemeColor::Love),
+    )
+    .highlight_style(Style::default().fg(ThemeColor::Iris.into()).bold())
+    .block(snapshots_block);
+    frame.render_stateful_widget(snapshots_table, layout[1], &mut state.snapshot_table_state);
+
+    let selected = state.snapshot_table_state.selected().unwrap_or(0);
+    let mut summary_lines = vec![];
+    if let Some(snapshot) = table.snapshots.get(selected) {
+        if let Some(manifest_list) = snapshot.manifest_list.as_ref() {
+            summary_lines.push(Line::from(vec![
+                label("Manifest list: "),
+                Span::raw(manifest_list.as_str()),
+            ]));
+        }
+        for (k, v) in snapshot.summary.iter() {
+            summary_lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{k}: "),
+                    Style::default().fg(ThemeColor::Love.into()),
+                ),
+                Span::raw(v.as_str()),
+            ]));
+        }
+    }
+    let summary_block = Block::bordered()
+        .title("Summary".bold())
+        .fg(ThemeColor::Subtle);
+    frame.render_widget(
+        Paragraph::new(summary_lines)
+            .block(summary_block)
+            .fg(ThemeColor::Text),
+        layout[2],
+    );
+}
+
+pub fn render_schemas(state: &mut State, frame: &mut Frame, rect: Rect) {
+    let Some(table) = state.viewer.iceberg() else {
+        return;
+    };
+
+    let mut spec_lines = vec![];
+    for spec in table.partition_specs.iter() {
+        let mut line = vec![Span::styled(
+            format!("Spec {}: ", spec.spec_id),
+            Style::default().fg(ThemeColor::Love.into()),
+        )];
+        if spec.fields.is_empty() {
+            line.push(Span::raw("unpartitioned"));
+        }
+        let fields = spec.fields.iter().map(|f| {
+            let source = table
+                .field(f.source_id)
+                .map_or(f.source_id.to_string(), |s| s.name.clone());
+            format!("{} = {}({source})", f.name, f.transform)
+        });
+        line.push(Span::raw(fields.collect::<Vec<_>>().join(", ")));
+        if spec.spec_id == table.default_spec_id {
+            line.push(Span::styled(
+                "  (default)",
+                Style::default().fg(ThemeColor::Iris.into()),
+            ));
+        }
+        spec_lines.push(Line::from(line));
+    }
+
+    let layout = Layout::vertical([
+        Constraint::Min(5),
+        Constraint::Length(spec_lines.len() as u16 + 2),
+    ])
+    .split(rect);
+
+    // One column per schema, with the fields lined up by ID so renames,
+    // type changes, additions and drops stand out.
+    let rows = table
+        .field_ids()
+        .into_iter()
+        .enumerate()
+        .map(|(i, id)| {
+            let bg_color = if i % 2 == 0 {
+                ThemeColor::HighlightLow
+            } else {
+                ThemeColor::Base
+            };
+            let mut previous: Option<String> = None;
+            let mut cells = vec![Cell::from(id.to_string())];
+            for schema in table.schemas.iter() {
+                let field = schema.fields.iter().find(|f| f.id == id);
+                let text = field.map(|f| {
+                    let required = if f.required { " (required)" } else { "" };
+                    format!("{}: {}{required}", f.name, f.data_type)
+                });
+                let cell = match (&previous, &text) {
+                    (None, Some(text)) if schema.schema_id != table.schemas[0].schema_id => {
+                        Cell::from(text.clone()).fg(ThemeColor::Foam)
+                    }
+                    (Some(_), None) => Cell::from("dropped").fg(ThemeColor::Love),
+                    (Some(previous), Some(text)) if previous != text => {
+                        Cell::from(text.clone()).fg(ThemeColor::Gold)
+                    }
+                    (_, text) => Cell::from(text.clone().unwrap_or_default()),
+                };
+                cells.push(cell);
+                previous = text;
+            }
+            Row::new(cells).fg(ThemeColor::Text).bg(bg_color)
+        })
+        .collect::<Vec<_>>();
+    let mut header = vec![Cell::from("Field ID")];
+    let mut widths = vec![Constraint::Length(8)];
+    for schema in table.schemas.iter() {
+        let marker = if schema.schema_id == table.current_schema_id {
+            " ●"
+        } else {
+            ""
+        };
+        header.push(Cell::from(format!("Schema {}{marker}", schema.schema_id)));
+        widths.push(Constraint::Fill(1));
+    }
+    let schemas_block = Block::bordered()
+        .title("Schemas".bold())
+        .title(Title::from("(↑/↓)").alignment(Alignment::Center))
+        .fg(ThemeColor::Subtle);
+    let schemas_table = Table::new(rows, widths)
+        .column_spacing(1)
+        .header(Row::new(header).fg(ThemeColor::Love))
+        .highlight_style(Style::default().fg(ThemeColor::Iris.into()).bold())
+        .block(schemas_block);
+    frame.render_stateful_widget(schemas_table, layout[0], &mut state.field_table_state);
+
+    let specs_block = Block::bordered()
+        .title("Partition specs".bold())
+        .fg(ThemeColor::Subtle);
+    frame.render_widget(
+        Paragraph::new(spec_lines)
+            .block(specs_block)
+            .fg(ThemeColor::Text),
+        layout[1],
+    );
+}
+
+pub fn render_data_files(state: &mut State, frame: &mut Frame, rect: Rect) {
+    let Some(table) = state.viewer.iceberg() else {
+        return;
+    };
+    let layout =
+        Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)]).split(rect);
+
+    let rows = table
+        .data_files
+        .iter()
+        .enumerate()
+        .map(|(i, file)| {
+            let bg_color = if i % 2 == 0 {
+                ThemeColor::HighlightLow
+            } else {
+                ThemeColor::Base
+            };
+            let path = file.path.strip_prefix(&table.root).unwrap_or(&file.path);
+            let partition = file
+                .partition
+                .iter()
+                .map(|(k, v)| format!("{k}={}", v.as_deref().unwrap_or("null")))
+                .collect::<Vec<_>>()
+                .join(", ");
+            Row::new(vec![
+                path.display().to_string(),
+                file.content.name().to_string(),
+                file.file_format.clone(),
+                partition,
+                file.record_count.to_string(),
+                format_size(file.file_size),
+            ])
+            .fg(ThemeColor::Text)
+            .bg(bg_color)
+        })
+        .collect::<Vec<_>>();
+    let files_block = Block::bordered()
+        .title("Data files".bold())
+        .title(Title::from("(↑/↓, Enter to open)").alignment(Alignment::Center))
+        .fg(ThemeColor::Subtle);
+    let files_table = Table::new(
+        rows,
+        [
+            Constraint::Min(30),
+            Constraint::Max(16),
+            Constraint::Max(8),
+            Constraint::Min(10),
+            Constraint::Max(10),
+            Constraint::Max(10),
+        ],
+    )
+    .column_spacing(1)
+    .header(
+        Row::new(vec![
+            "Path",
+            "Content",
+            "Format",
+            "Partition",
+            "# records",
+            "Size",
+        ])
+        .fg(ThemeColor::Love),
+    )
+    .highlight_style(Style::default().fg(ThemeColor::Iris.into()).bold())
+    .block(files_block);
+    frame.render_stateful_widget(files_table, layout[0], &mut state.file_table_state);
+
+    let selected = state.file_table_state.selected().unwrap_or(0);
+    let optional = |v: Option<i64>| v.map_or("".to_string(), |v| v.to_string());
+    let rows = table
+        .data_files
+        .get(selected)
+        .map_or(vec![], |file| file.columns.clone())
+        .into_iter()
+        .enumerate()
+        .map(|(i, stats)| {
+            let bg_color = if i % 2 == 0 {
+                ThemeColor::HighlightLow
+            } else {
+                ThemeColor::Base
+            };
+            Row::new(vec![
+                stats.field_id.to_string(),
+                table
+                    .field(stats.field_id)
+                    .map_or("".to_string(), |f| f.name.clone()),
+                stats.size.map_or("".to_string(), |s| format_size(s as u64)),
+                optional(stats.values),
+                optional(stats.nulls),
+                optional(stats.nans),
+                stats.lower.unwrap_or_default(),
+                stats.upper.unwrap_or_default(),
+            ])
+            .fg(ThemeColor::Text)
+            .bg(bg_color)
+        })
+        .collect::<Vec<_>>();
+    let stats_block = Block::bordered()
+        .title("Column statistics".bold())
+        .fg(ThemeColor::Subtle);
+    let stats_table = Table::new(
+        rows,
+        [
+            Constraint::Length(8),
+            Constraint::Min(10),
+            Constraint::Max(10),
+            Constraint::Max(10),
+            Constraint::Max(10),
+            Constraint::Max(10),
+            Constraint::Min(10),
+            Constraint::Min(10),
+        ],
+    )
+    .column_spacing(1)
+    .header(
+        Row::new(vec![
+            "Field ID", "Name", "Size", "Values", "Nulls", "NaNs", "Lower", "Upper",
+        ])
+        .fg(ThemeColor::Love),
+    )
+    .block(stats_block);
+    frame.render_widget(stats_table, layout[1]);
+}
+
+/// Schema attributes shown in each pane of the Diff tab.
+const DIFF_ATTRIBUTES: [usize; 6] = [0, 2, 3, 4, 9, 10];
+
+pub fn render_diff(state: &mut State, frame: &mut Frame, rect: Rect) {
+    let Some(diff) = state.diff.as_ref() else {
+        return;
+    };
+    let layout = Layout::vertical([Constraint::Min(5), Constraint::Length(10)]).split(rect);
+    let panes = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(layout[0]);
+    let details = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(layout[1]);
+
+    let diff_color = |kind: DiffKind| match kind {
+        DiffKind::Added => ThemeColor::Foam,
+        DiffKind::Removed => ThemeColor::Love,
+        DiffKind::Changed => ThemeColor::Gold,
+        DiffKind::Unchanged => ThemeColor::Text,
+    };
+    let pane = |name: &str, right: bool| {
+        let rows = diff.columns.iter().enumerate().map(|(i, c)| {
+            let bg_color = if i % 2 == 0 {
+                ThemeColor::HighlightLow
+            } else {
+                ThemeColor::Base
+            };
+            let side = if right { &c.right } else { &c.left };
+            let cells = side
+                .as_ref()
+                .map_or(vec![""; DIFF_ATTRIBUTES.len()], |row| {
+                    DIFF_ATTRIBUTES.iter().map(|&j| row[j].as_str()).collect()
+                });
+            Row::new(cells).fg(diff_color(c.kind())).bg(bg_color)
+        });
+        let block = Block::bordered()
+            .title(name.to_string().bold())
+            .title(Title::from("(↑/↓)").alignment(Alignment::Center))
+            .fg(ThemeColor::Subtle);
+        Table::new(
+            rows.collect::<Vec<_>>(),
+            [
+                Constraint::Min(10),
+                Constraint::Min(8),
+                Constraint::Max(14),
+                Constraint::Max(20),
+                Constraint::Max(10),
+                Constraint::Max(8),
+            ],
+        )
+        .column_spacing(1)
+        .header(Row::new(DIFF_ATTRIBUTES.map(|j| SCHEMA_HEADERS[j]).to_vec()).fg(ThemeColor::Love))
+        .highlight_style(Style::default().bold().reversed())
+        .block(block)
+    };
+    let mut left_state = state.diff_table_state.clone();
+    frame.render_stateful_widget(pane(&diff.left_name, false), panes[0], &mut left_state);
+    frame.render_stateful_widget(
+        pane(&diff.right_name, true),
+        panes[1],
+        &mut state.diff_table_state,
+    );
+
+    let selected = state.diff_table_state.selected().unwrap_or(0);
+    let mut change_lines = vec![];
+    if let Some(column) = diff.columns.get(selected) {
+        let kind = column.kind();
+        change_lines.push(Line::from(vec![Span::styled(
+            format!("{} {}", kind.symbol(), column.path()),
+            Style::default().fg(diff_color(kind).into()).bold(),
+        )]));
+        for (attr, l, r) in column.changes() {
+            change_lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{attr}: "),
+                    Style::default().fg(ThemeColor::Love.into()),
+                ),
+                Span::raw(format!("{l} → {r}")),
+            ]));
+        }
+        if let Some(stats) = diff.stats.iter().find(|s| s.path == column.path()) {
+            let opt = |v: &Option<String>| v.clone().unwrap_or_default();
+            let count = |v: Option<u64>| v.map_or("".to_string(), |n| n.to_string());
+            for (label, l, r) in [
+                ("Min: ", opt(&stats.left.min), opt(&stats.right.min)),
+                ("Max: ", opt(&stats.left.max), opt(&stats.right.max)),
+                (
+                    "# null: ",
+                    count(stats.left.null_count),
+                    count(stats.right.null_count),
+                ),
+            ] {
+                let color = if l == r {
+                    ThemeColor::Text
+                } else {
+                    ThemeColor::Gold
+                };
+                change_lines.push(Line::from(vec![
+                    Span::styled(label, Style::default().fg(ThemeColor::Love.into())),
+                    Span::styled(format!("{l} → {r}"), Style::default().fg(color.into())),
+                ]));
+            }
+        }
+    }
+    if let Some((l, r)) = diff.num_rows {
+        change_lines.push(Line::from(vec![
+            Span::styled("# rows: ", Style::default().fg(ThemeColor::Love.into())),
+            Span::raw(format!("{l} → {r}")),
+        ]));
+    }
+    let changes_block = Block::bordered()
+        .title("Column changes".bold())
+        .fg(ThemeColor::Subtle);
+    frame.render_widget(
+        Paragraph::new(change_lines)
+            .block(changes_block)
+            .fg(ThemeColor::Text),
+        details[0],
+    );
+
+    let kv_lines: Vec<_> = diff
+        .key_values
+        .iter()
+        .map(|kv| {
+            let kind = kv.kind();
+            Line::from(vec![Span::styled(
+                format!("{} {}", kind.symbol(), kv.key),
+                Style::default().fg(diff_color(kind).into()),
+            )])
+        })
+        .collect();
+    let kv_block = Block::bordered()
+        .title("Key-value metadata".bold())
+        .fg(ThemeColor::Subtle);
+    frame.render_widget(
+        Paragraph::new(kv_lines)
+            .block(kv_block)
+            .fg(ThemeColor::Text),
+        details[1],
+    );
+}
+
+pub fn render_row_diff(state: &mut State, frame: &mut Frame, rect: Rect) {
+    let Some(diff) = state.data_diff.as_ref() else {
+        return;
+    };
+    let layout = Layout::vertical([Constraint::Length(3), Constraint::Min(5)]).split(rect);
+    let panes = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(layout[1]);
+
+    let summary = Line::from(vec![
+        Span::styled("Keys: ", Style::default().fg(ThemeColor::Love.into())),
+        Span::raw(diff.keys.join(", ")),
+        Span::styled("  |  ", Style::default().fg(ThemeColor::Rose.into())),
+        Span::styled(
+            format!("- {} ", diff.num_removed),
+            Style::default().fg(ThemeColor::Love.into()),
+        ),
+        Span::styled(
+            format!("+ {} ", diff.num_added),
+            Style::default().fg(ThemeColor::Foam.into()),
+        ),
+        Span::styled(
+            format!("~ {} ", diff.num_changed),
+            Style::default().fg(ThemeColor::Gold.into()),
+        ),
+        Span::raw(format!("= {}", diff.num_unchanged)),
+    ]);
+    let summary_block = Block::bordered()
+        .title("Row diff".bold())
+        .fg(ThemeColor::Subtle);
+    frame.render_widget(
+        Paragraph::new(summary)
+            .block(summary_block)
+            .fg(ThemeColor::Text),
+        layout[0],
+    );
+
+    let col_offset = state.row_diff_col_offset;
+    let pane = |name: &str, right: bool| {
+        let rows = diff.rows.iter().enumerate().map(|(i, row)| {
+            let bg_color = if i % 2 == 0 {
+                ThemeColor::HighlightLow
+            } else {
+                ThemeColor::Base
+            };
+            let fg_color = match row.kind {
+                DiffKind::Added => ThemeColor::Foam,
+                DiffKind::Removed => ThemeColor::Love,
+                _ => ThemeColor::Text,
+            };
+            let changed = row.changed_columns();
+            let values = if right { &row.right } else { &row.left };
+            let mut cells = vec![];
+            if let Some(values) = values {
+                cells.push(Cell::from(row.key.as_str()));
+                cells.extend(values.iter().enumerate().skip(col_offset).map(|(j, v)| {
+                    let cell = Cell::from(v.as_str());
+                    if changed.contains(&j) {
+                        cell.fg(ThemeColor::Gold).bold()
+                    } else {
+                        cell
+                    }
+                }));
+            }
+            Row::new(cells).fg(fg_color).bg(bg_color)
+        });
+        let block = Block::bordered()
+            .title(name.to_string().bold())
+            .title(Title::from("(↑/↓/←/→)").alignment(Alignment::Center))
+            .fg(ThemeColor::Subtle);
+        let n_cols = diff.columns.len().saturating_sub(col_offset);
+        let mut widths = vec![Constraint::Length(16)];
+        widths.extend(vec![Constraint::Length(14); n_cols]);
+        let mut header = vec!["Key"];
+        header.extend(diff.columns[col_offset..].iter().map(|c| c.as_str()));
+        Table::new(rows.collect::<Vec<_>>(), widths)
+            .column_spacing(1)
+            .header(Row::new(header).fg(ThemeColor::Love))
+            .highlight_style(Style::default().reversed())
+            .block(block)
+    };
+    let mut left_state = state.row_diff_table_state.clone();
+    frame.render_stateful_widget(pane(&diff.left_name, false), panes[0], &mut left_state);
+    frame.render_stateful_widget(
+        pane(&diff.right_name, true),
+        panes[1],
+        &mut state.row_diff_table_state,
+    );
+}
+
+fn render_data(state: &mut State, frame: &mut Frame, mut rect: Rect) {
+    if let Some(warning) = state.viewer.iceberg().and_then(|t| t.deletes_warning()) {
+        let [top, rest] = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(rect);
+        frame.render_widget(Line::from(warning).fg(ThemeColor::Love), top);
+        rect = rest;
+    }
+    // As many columns as fit, at least one, and a row per line below the
+    // header.
+    let schema = state.viewer.batch.schema();
+    let mut col_widths = vec![];
+    let mut total_width = 0;
+    for field in schema.fields().iter().skip(state.viewer.col_offset) {
+        let width = state
+            .column_widths
+            .get(field.name())
+            .copied()
+            .unwrap_or(DEFAULT_COLUMN_WIDTH);
+        if !col_widths.is_empty() && total_width + width > rect.width {
+            break;
+        }
+        total_width += width;
+        col_widths.push(width);
+    }
+    state.viewer.visible_cols = col_widths.len().max(1);
+    state.viewer.visible_rows = (rect.height as usize).saturating_sub(1).max(1);
+
+    let col_names: Vec<_> = state
+        .viewer
+        .batch
+        .schema_ref()
+        .fields()
+        .iter()
+        .skip(state.viewer.col_offset)
+        .map(|f| f.name().to_owned())
+        .take(state.viewer.visible_cols)
+        .collect();
+    let table_slice = batch_slice(
+        &state.viewer.batch,
+        state.viewer.row_offset,
+        state.viewer.col_offset,
+        state.viewer.visible_rows,
+        state.viewer.visible_cols,
+    );
+
+    let col_layout = Layout::horizontal(Constraint::from_lengths(col_widths)).split(rect);
+    state.click_areas.data_columns = col_layout
+        .iter()
+        .enumerate()
+        .map(|(i, r)| (*r, state.viewer.col_offset + i))
+        .collect();
+    let (selected_rows, selected_cols) = state.selection();
+    for (i, (data, name)) in table_slice.iter().zip(col_names).enumerate() {
+        let col_width = col_layout[i].width as usize;
+        let mut lines = vec![Line::from(mask_string(&name, col_width - 1)).fg(ThemeColor::Love)];
+        lines.extend(data.iter().enumerate().map(|(j, c)| {
+            let bg_color = if state.visual.is_some()
+                && selected_rows.contains(&(j + state.viewer.row_offset))
+                && selected_cols.contains(&(i + state.viewer.col_offset))
+            {
+                ThemeColor::HighlightMed
+            } else if j % 2 == 0 {
+                ThemeColor::HighlightLow
+            } else {
+                ThemeColor::Base
+            };
+            let fg_color = if ((i + state.viewer.col_offset) == state.viewer.selected_col)
+                && ((j + state.viewer.row_offset) == state.viewer.selected_row)
+            {
+                ThemeColor::Iris
+            } else {
+                ThemeColor::Text
+            };
+            let s = mask_string(c.as_str(), col_width - 1);
+            Line::from(format!("{s} "))
+                .bg(bg_color)
+                .fg(fg_color)
+                .alignment(Alignment::Right)
+        }));
+        let col = Text::from(lines);
+        frame.render_widget(col, col_layout[i]);
+    }
+}
+
+fn mask_string(s: &str, max_len: usize) -> &str {
+    if s.chars().count() > max_len {
+        let mut char_indices = s.char_indices();
+        let truncate_at = char_indices.nth(max_len).map_or(s.len(), |(idx, _)| idx);
+        &s[..truncate_at]
+    } else {
+        s
+    }
+}
+fn batch_slice(
+    batch: &RecordBatch,
+    row: usize,
     col: usize,
     nrows: usize,
     ncols: usize,
 ) -> Vec<Vec<String>> {
     let options = FormatOptions::default();
+    let end = (row + nrows).min(batch.num_rows());
     batch
         .columns()
         .iter()
         .skip(col)
         .map(|c| {
             let formatter = ArrayFormatter::try_new(c.as_ref(), &options).unwrap();
-            (row..(row + nrows))
+            (row..end)
                 .map(|i| formatter.value(i).to_string())
                 .collect::<Vec<_>>()
         })
         .take(ncols)
         .collect()
 }
-
-fn render_batch(batch: &RecordBatch, row: usize, col: usize, nrows: usize, ncols: usize) {
-    let col_names: Vec<_> = batch
-        .schema()
-        .fields()
-        .iter()
-        .skip(col)
-        .map(|f| f.name())
-        .take(ncols)
-        .collect();
-}
-
-#[allow(dead_code)]
-enum ThemeColor {
-    Base,
-    Surface,
-    Pine,
-    Foam,
-    Text,
-    Subtle,
-    HighlightLow,
-    HighlightMed,
-    Iris,
-    Gold,
-    Rose,
-    Love,
-}
-
-impl From<ThemeColor> for Color {
-    fn from(value: ThemeColor) -> Self {
-        match value {
-            ThemeColor::Base => Self::Rgb(35, 33, 54),
-            ThemeColor::Surface => Self::Rgb(42, 39, 63),
-            ThemeColor::Pine => Self::Rgb(62, 143, 176),
-            ThemeColor::Foam => Self::Rgb(156, 207, 216),
-            ThemeColor::Text => Self::Rgb(224, 222, 244),
-            ThemeColor::Subtle => Self::Rgb(144, 140, 170),
-            ThemeColor::HighlightLow => Self::Rgb(42, 40, 62),
-            ThemeColor::HighlightMed => Self::Rgb(68, 65, 90),
-            ThemeColor::Iris => Self::Rgb(196, 167, 231),
-            ThemeColor::Gold => Self::Rgb(246, 193, 119),
-            ThemeColor::Rose => Self::Rgb(234, 154, 151),
-            ThemeColor::Love => Self::Rgb(235, 111, 146),
-        }
-    }
-}
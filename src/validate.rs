@@ -0,0 +1,775 @@
+use half::f16;
+use parquet::{
+    basic::{LogicalType, SortOrder},
+    column::reader::{ColumnReader, ColumnReaderImpl},
+    data_type::{ByteArray, DataType, FixedLenByteArray},
+    file::{
+        metadata::{ColumnChunkMetaData, ParquetMetaData},
+        reader::{ChunkReader, FileReader, SerializedFileReader},
+        statistics::{Statistics, ValueStatistics},
+    },
+    format::PageHeader,
+    thrift::TSerializable,
+};
+use rayon::prelude::*;
+use thrift::protocol::TCompactInputProtocol;
+
+use crate::{
+    error::{catch_panic, Result},
+    mmap::MappedFile,
+    source::{check_footer, FOOTER_SIZE, MAGIC_SIZE},
+};
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+    fs::File,
+    ops::Range,
+    path::Path,
+};
+
+/// Records decoded at a time.
+const BATCH_SIZE: usize = 8192;
+
+/// Integrity checks of a Parquet file, from its layout down to every page.
+pub struct Validation {
+    pub name: String,
+    /// Problems with the file as a whole, e.g. overlapping column chunks.
+    pub issues: Vec<String>,
+    pub row_groups: Vec<RowGroupValidation>,
+}
+
+pub struct RowGroupValidation {
+    pub num_rows: i64,
+    /// E.g. columns decoding to fewer rows than the row group has.
+    pub issues: Vec<String>,
+    pub columns: Vec<ColumnValidation>,
+}
+
+pub struct ColumnValidation {
+    pub path: String,
+    pub num_pages: usize,
+    /// Pages with a CRC in their header, all of which were checked.
+    pub num_crcs: usize,
+    pub issues: Vec<String>,
+}
+
+impl Validation {
+    /// Decodes every page of every column chunk of the file at `path`.
+    ///
+    /// Only a file whose footer can't be read is an error, anything else is
+    /// reported as an issue.
+    pub fn new(path: &Path) -> Result<Self> {
+        let name = path.display().to_string();
+        match MappedFile::open(path) {
+            Ok(file) => Self::check(name, file.clone(), file, true),
+            // Reads of a `File` seek a handle shared between threads, so the
+            // row groups of files that aren't mapped are checked in turn,
+            // each column chunk read into memory on its own.
+            Err(_) => Self::check(name, File::open(path)?, File::open(path)?, false),
+        }
+    }
+
+    /// Checks `file`, whose values are decoded through `values`, a handle to
+    /// the same file, and its row groups in parallel if `parallel`.
+    fn check<R: ChunkReader + Sync + 'static>(
+        name: String,
+        file: R,
+        values: R,
+        parallel: bool,
+    ) -> Result<Self> {
+        check_footer(&name, &file)?;
+        let reader = SerializedFileReader::new(values)?;
+        let metadata = reader.metadata();
+
+        // Column chunks and page indexes go between the magic and the
+        // metadata, whose length was checked to fit in the file.
+        let size = file.len();
+        let length = file.get_bytes(size - FOOTER_SIZE as u64, 4)?;
+        let length = u32::from_le_bytes([length[0], length[1], length[2], length[3]]);
+        let data = MAGIC_SIZE as u64..size - FOOTER_SIZE as u64 - length as u64;
+
+        let mut validation = Self {
+            issues: check_layout(metadata, data),
+            row_groups: vec![],
+            name,
+        };
+        let num_rows: i64 = metadata.row_groups().iter().map(|rg| rg.num_rows()).sum();
+        if num_rows != metadata.file_metadata().num_rows() {
+            validation.issues.push(format!(
+                "the footer gives {} rows, but its row groups hold {num_rows}",
+                metadata.file_metadata().num_rows()
+            ));
+        }
+
+        let row_groups = 0..metadata.num_row_groups();
+        let check = |i| RowGroupValidation::new(&file, &reader, i);
+        validation.row_groups = if parallel {
+            row_groups.into_par_iter().map(check).collect()
+        } else {
+            row_groups.map(check).collect()
+        };
+        Ok(validation)
+    }
+
+    pub fn num_issues(&self) -> usize {
+        self.issues.len()
+            + self
+                .row_groups
+                .iter()
+                .map(|rg| {
+                    rg.issues.len() + rg.columns.iter().map(|c| c.issues.len()).sum::<usize>()
+                })
+                .sum::<usize>()
+    }
+
+    pub fn passed(&self) -> bool {
+        self.num_issues() == 0
+    }
+}
+
+impl RowGroupValidation {
+    /// Checks the pages and values of every column chunk of row group `i`.
+    fn new<R: ChunkReader + 'static>(file: &R, reader: &SerializedFileReader<R>, i: usize) -> Self {
+        let row_group = reader.metadata().row_group(i);
+        let mut validation = Self {
+            num_rows: row_group.num_rows(),
+            issues: vec![],
+            columns: vec![],
+        };
+        for (j, chunk) in row_group.columns().iter().enumerate() {
+            let mut column = ColumnValidation {
+                path: chunk.column_path().string(),
+                num_pages: 0,
+                num_crcs: 0,
+                issues: vec![],
+            };
+            if let Err(e) = column.check_pages(file, chunk) {
+                column.issues.push(format!("failed to read its pages: {e}"));
+            }
+            // A CRC mismatch would only be reported again.
+            if column.issues.is_empty() {
+                let decode = || -> Result<_> {
+                    let reader = reader.get_row_group(i)?.get_column_reader(j)?;
+                    column.check_values(reader, chunk)
+                };
+                // Corrupt pages can make the parquet crate panic rather than
+                // fail, which is reported like any other decoding error.
+                match catch_panic(decode) {
+                    Ok(Ok(num_rows)) if num_rows != row_group.num_rows() => {
+                        validation.issues.push(format!(
+                            "`{}` decodes to {num_rows} rows rather than {}",
+                            chunk.column_path(),
+                            row_group.num_rows()
+                        ))
+                    }
+                    Ok(Ok(_)) => {}
+                    Ok(Err(e)) => column.issues.push(format!("failed to decode: {e}")),
+                    Err(_) => column.issues.push("the decoder panicked".to_string()),
+                }
+            }
+            validation.columns.push(column);
+        }
+        validation
+    }
+}
+
+impl ColumnValidation {
+    /// Walks the page headers of `chunk`, checking their CRCs and that they
+    /// add up to the chunk.
+    fn check_pages<R: ChunkReader>(&mut self, file: &R, chunk: &ColumnChunkMetaData) -> Result<()> {
+        let (start, end) = chunk_range(chunk);
+        let buf = file.get_bytes(start, (end - start) as usize)?;
+
+        let mut rest = &buf[..];
+        let mut num_values = 0;
+        while !rest.is_empty() {
+            let header =
+                PageHeader::read_from_in_protocol(&mut TCompactInputProtocol::new(&mut rest))?;
+            let size = header.compressed_page_size.max(0) as usize;
+            let Some(page) = rest.get(..size) else {
+                self.issues.push(format!(
+                    "page {} runs {} bytes past the end of the chunk",
+                    self.num_pages,
+                    size - rest.len()
+                ));
+                return Ok(());
+            };
+            if let Some(crc) = header.crc {
+                self.num_crcs += 1;
+                if crc32fast::hash(page) != crc as u32 {
+                    self.issues.push(format!(
+                        "page {} doesn't match its CRC checksum",
+                        self.num_pages
+                    ));
+                }
+            }
+            num_values += match (&header.data_page_header, &header.data_page_header_v2) {
+                (Some(h), _) => h.num_values as i64,
+                (_, Some(h)) => h.num_values as i64,
+                _ => 0,
+            };
+            self.num_pages += 1;
+            rest = &rest[size..];
+        }
+        if num_values != chunk.num_values() {
+            self.issues.push(format!(
+                "its pages hold {num_values} values, but its metadata gives {}",
+                chunk.num_values()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Decodes every value of a chunk, checking its statistics, and returns
+    /// the number of rows.
+    fn check_values(&mut self, reader: ColumnReader, chunk: &ColumnChunkMetaData) -> Result<i64> {
+        let stats = chunk.statistics();
+        match (reader, stats) {
+            (ColumnReader::BoolColumnReader(r), Some(Statistics::Boolean(s))) => {
+                self.check(r, chunk, Some(s))
+            }
+            (ColumnReader::Int32ColumnReader(r), Some(Statistics::Int32(s))) => {
+                self.check(r, chunk, Some(s))
+            }
+            (ColumnReader::Int64ColumnReader(r), Some(Statistics::Int64(s))) => {
+                self.check(r, chunk, Some(s))
+            }
+            (ColumnReader::FloatColumnReader(r), Some(Statistics::Float(s))) => {
+                self.check(r, chunk, Some(s))
+            }
+            (ColumnReader::DoubleColumnReader(r), Some(Statistics::Double(s))) => {
+                self.check(r, chunk, Some(s))
+            }
+            (ColumnReader::ByteArrayColumnReader(r), Some(Statistics::ByteArray(s))) => {
+                self.check(r, chunk, Some(s))
+            }
+            (
+                ColumnReader::FixedLenByteArrayColumnReader(r),
+                Some(Statistics::FixedLenByteArray(s)),
+            ) => self.check(r, chunk, Some(s)),
+            // INT96 has no defined sort order, so its values are only decoded.
+            (ColumnReader::Int96ColumnReader(r), _) => self.check(r, chunk, None),
+            (_, Some(_)) => {
+                self.issues
+                    .push("its statistics don't match its physical type".to_string());
+                Ok(0)
+            }
+            (ColumnReader::BoolColumnReader(r), None) => self.check(r, chunk, None),
+            (ColumnReader::Int32ColumnReader(r), None) => self.check(r, chunk, None),
+            (ColumnReader::Int64ColumnReader(r), None) => self.check(r, chunk, None),
+            (ColumnReader::FloatColumnReader(r), None) => self.check(r, chunk, None),
+            (ColumnReader::DoubleColumnReader(r), None) => self.check(r, chunk, None),
+            (ColumnReader::ByteArrayColumnReader(r), None) => self.check(r, chunk, None),
+            (ColumnReader::FixedLenByteArrayColumnReader(r), None) => self.check(r, chunk, None),
+        }
+    }
+
+    fn check<T: DataType>(
+        &mut self,
+        mut reader: ColumnReaderImpl<T>,
+        chunk: &ColumnChunkMetaData,
+        stats: Option<&ValueStatistics<T::T>>,
+    ) -> Result<i64>
+    where
+        T::T: Value,
+    {
+        let descr = chunk.column_descr();
+        let order = Order {
+            sort_order: descr.sort_order(),
+            float16: descr.logical_type() == Some(LogicalType::Float16),
+        };
+        let max_def_level = descr.max_def_level();
+        let (mut def_levels, mut rep_levels, mut values) = (vec![], vec![], vec![]);
+        let (mut num_rows, mut num_levels, mut num_nulls) = (0, 0, 0);
+        let (mut min, mut max): (Option<T::T>, Option<T::T>) = (None, None);
+        loop {
+            def_levels.clear();
+            rep_levels.clear();
+            values.clear();
+            let (records, _, levels) = reader.read_records(
+                BATCH_SIZE,
+                Some(&mut def_levels),
+                Some(&mut rep_levels),
+                &mut values,
+            )?;
+            if records == 0 && levels == 0 {
+                break;
+            }
+            num_rows += records as i64;
+            num_levels += levels as i64;
+            if max_def_level > 0 {
+                num_nulls += def_levels.iter().filter(|&&l| l < max_def_level).count() as u64;
+            }
+            for value in values.iter().filter(|v| !v.is_nan(order)) {
+                if min
+                    .as_ref()
+                    .is_none_or(|m| value.compare(m, order) == Some(Ordering::Less))
+                {
+                    min = Some(value.clone());
+                }
+                if max
+                    .as_ref()
+                    .is_none_or(|m| value.compare(m, order) == Some(Ordering::Greater))
+                {
+                    max = Some(value.clone());
+                }
+            }
+        }
+
+        if num_levels != chunk.num_values() {
+            self.issues.push(format!(
+                "decodes to {num_levels} values, but its metadata gives {}",
+                chunk.num_values()
+            ));
+        }
+        let Some(stats) = stats else {
+            return Ok(num_rows);
+        };
+        // Writers disagree on what counts as null inside lists.
+        if let Some(expected) = stats
+            .null_count_opt()
+            .filter(|_| descr.max_rep_level() == 0)
+        {
+            if expected != num_nulls {
+                self.issues.push(format!(
+                    "statistics give {expected} nulls, but it has {num_nulls}"
+                ));
+            }
+        }
+        if order.sort_order == SortOrder::UNDEFINED {
+            return Ok(num_rows);
+        }
+        // Truncated bounds only have to hold every value.
+        let bounds = [
+            (
+                "min",
+                stats.min_opt(),
+                min.as_ref(),
+                stats.min_is_exact(),
+                Ordering::Greater,
+            ),
+            (
+                "max",
+                stats.max_opt(),
+                max.as_ref(),
+                stats.max_is_exact(),
+                Ordering::Less,
+            ),
+        ];
+        for (name, expected, actual, exact, wrong) in bounds {
+            let (Some(expected), Some(actual)) = (expected, actual) else {
+                continue;
+            };
+            match expected.compare(actual, order) {
+                Some(Ordering::Equal) => {}
+                Some(ordering) if !exact && ordering != wrong => {}
+                _ => self.issues.push(format!(
+                    "statistics give a {name} of {}, but it's {}",
+                    expected.show(order),
+                    actual.show(order)
+                )),
+            }
+        }
+        Ok(num_rows)
+    }
+}
+
+/// Checks that the column chunks and page indexes lie within `data`, without
+/// overlapping.
+fn check_layout(metadata: &ParquetMetaData, data: Range<u64>) -> Vec<String> {
+    let mut issues = vec![];
+
+    let mut ranges: Vec<(Range<u64>, String)> = vec![];
+    for (i, row_group) in metadata.row_groups().iter().enumerate() {
+        for chunk in row_group.columns() {
+            let path = chunk.column_path();
+            let (start, end) = chunk_range(chunk);
+            ranges.push((
+                start..end,
+                format!("the chunk of `{path}` in row group {i}"),
+            ));
+            if let Some((offset, length)) =
+                chunk.column_index_offset().zip(chunk.column_index_length())
+            {
+                let (start, end) = (offset.max(0) as u64, (offset + length as i64).max(0) as u64);
+                ranges.push((
+                    start..end,
+                    format!("the column index of `{path}` in row group {i}"),
+                ));
+            }
+            if let Some((offset, length)) =
+                chunk.offset_index_offset().zip(chunk.offset_index_length())
+            {
+                let (start, end) = (offset.max(0) as u64, (offset + length as i64).max(0) as u64);
+                ranges.push((
+                    start..end,
+                    format!("the offset index of `{path}` in row group {i}"),
+                ));
+            }
+        }
+    }
+    for (range, what) in ranges.iter() {
+        if range.start < data.start || range.end > data.end {
+            issues.push(format!(
+                "{what} at bytes {}..{} lies outside bytes {}..{}, between the magic and the \
+                 metadata",
+                range.start, range.end, data.start, data.end
+            ));
+        }
+    }
+    ranges.sort_by_key(|(range, _)| (range.start, range.end));
+    for pair in ranges.windows(2) {
+        let ((a, a_what), (b, b_what)) = (&pair[0], &pair[1]);
+        if b.start < a.end {
+            issues.push(format!(
+                "{a_what} at bytes {}..{} overlaps {b_what} at bytes {}..{}",
+                a.start, a.end, b.start, b.end
+            ));
+        }
+    }
+    issues
+}
+
+/// Bytes of a column chunk, from its first page to the end of its last one.
+fn chunk_range(chunk: &ColumnChunkMetaData) -> (u64, u64) {
+    let start = chunk
+        .dictionary_page_offset()
+        .unwrap_or(chunk.data_page_offset())
+        .max(0) as u64;
+    (start, start + chunk.compressed_size().max(0) as u64)
+}
+
+/// How the values of a column compare.
+#[derive(Clone, Copy)]
+struct Order {
+    sort_order: SortOrder,
+    /// Whether they're half-precision floats, stored as 2 little-endian
+    /// bytes, which compare as floats rather than as bytes.
+    float16: bool,
+}
+
+/// Physical values, compared in the order of their column.
+trait Value: Clone {
+    fn compare(&self, other: &Self, order: Order) -> Option<Ordering>;
+
+    fn is_nan(&self, _order: Order) -> bool {
+        false
+    }
+
+    fn show(&self, order: Order) -> String;
+}
+
+impl Value for bool {
+    fn compare(&self, other: &Self, _: Order) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+
+    fn show(&self, _: Order) -> String {
+        self.to_string()
+    }
+}
+
+impl Value for i32 {
+    fn compare(&self, other: &Self, order: Order) -> Option<Ordering> {
+        match order.sort_order {
+            SortOrder::UNSIGNED => Some((*self as u32).cmp(&(*other as u32))),
+            _ => Some(self.cmp(other)),
+        }
+    }
+
+    fn show(&self, _: Order) -> String {
+        self.to_string()
+    }
+}
+
+impl Value for i64 {
+    fn compare(&self, other: &Self, order: Order) -> Option<Ordering> {
+        match order.sort_order {
+            SortOrder::UNSIGNED => Some((*self as u64).cmp(&(*other as u64))),
+            _ => Some(self.cmp(other)),
+        }
+    }
+
+    fn show(&self, _: Order) -> String {
+        self.to_string()
+    }
+}
+
+impl Value for f32 {
+    fn compare(&self, other: &Self, _: Order) -> Option<Ordering> {
+        self.partial_cmp(other)
+    }
+
+    fn is_nan(&self, _: Order) -> bool {
+        f32::is_nan(*self)
+    }
+
+    fn show(&self, _: Order) -> String {
+        self.to_string()
+    }
+}
+
+impl Value for f64 {
+    fn compare(&self, other: &Self, _: Order) -> Option<Ordering> {
+        self.partial_cmp(other)
+    }
+
+    fn is_nan(&self, _: Order) -> bool {
+        f64::is_nan(*self)
+    }
+
+    fn show(&self, _: Order) -> String {
+        self.to_string()
+    }
+}
+
+impl Value for parquet::data_type::Int96 {
+    fn compare(&self, _: &Self, _: Order) -> Option<Ordering> {
+        None
+    }
+
+    fn show(&self, _: Order) -> String {
+        format!("{:?}", self.data())
+    }
+}
+
+impl Value for ByteArray {
+    fn compare(&self, other: &Self, order: Order) -> Option<Ordering> {
+        compare_bytes(self.data(), other.data(), order)
+    }
+
+    fn show(&self, order: Order) -> String {
+        show_bytes(self.data(), order)
+    }
+}
+
+impl Value for FixedLenByteArray {
+    fn compare(&self, other: &Self, order: Order) -> Option<Ordering> {
+        compare_bytes(self.data(), other.data(), order)
+    }
+
+    fn is_nan(&self, order: Order) -> bool {
+        order.float16 && float16(self.data()).is_some_and(f16::is_nan)
+    }
+
+    fn show(&self, order: Order) -> String {
+        show_bytes(self.data(), order)
+    }
+}
+
+/// Compares strings and binary lexicographically, decimals as big-endian
+/// two's complement integers, and half-precision floats as floats.
+fn compare_bytes(a: &[u8], b: &[u8], order: Order) -> Option<Ordering> {
+    if order.float16 {
+        return float16(a)?.partial_cmp(&float16(b)?);
+    }
+    match order.sort_order {
+        SortOrder::UNSIGNED => Some(a.cmp(b)),
+        SortOrder::SIGNED => {
+            let negative = |v: &[u8]| v.first().is_some_and(|b| b & 0x80 != 0);
+            let (a_negative, b_negative) = (negative(a), negative(b));
+            if a_negative != b_negative {
+                return Some(b_negative.cmp(&a_negative));
+            }
+            // Sign-extend the shorter one.
+            let len = a.len().max(b.len());
+            let fill = if a_negative { 0xff } else { 0 };
+            let a = std::iter::repeat_n(fill, len - a.len()).chain(a.iter().copied());
+            let b = std::iter::repeat_n(fill, len - b.len()).chain(b.iter().copied());
+            Some(a.cmp(b))
+        }
+        SortOrder::UNDEFINED => None,
+    }
+}
+
+fn float16(bytes: &[u8]) -> Option<f16> {
+    Some(f16::from_le_bytes(bytes.try_into().ok()?))
+}
+
+fn show_bytes(bytes: &[u8], order: Order) -> String {
+    if let Some(value) = float16(bytes).filter(|_| order.float16) {
+        return value.to_string();
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(s) => format!("{s:?}"),
+        Err(_) => format!(
+            "0x{}",
+            bytes.iter().map(|b| format!("{b:02x}")).collect::<String>()
+        ),
+    }
+}
+
+impl Display for Validation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.name)?;
+        for issue in self.issues.iter() {
+            writeln!(f, "  - {issue}")?;
+        }
+        let width = self
+            .row_groups
+            .iter()
+            .flat_map(|rg| rg.columns.iter().map(|c| c.path.len()))
+            .max()
+            .unwrap_or(0);
+        for (i, row_group) in self.row_groups.iter().enumerate() {
+            writeln!(f, "  Row group {i}: {} rows", row_group.num_rows)?;
+            for issue in row_group.issues.iter() {
+                writeln!(f, "    - {issue}")?;
+            }
+            for column in row_group.columns.iter() {
+                let status = if column.issues.is_empty() {
+                    "ok"
+                } else {
+                    "FAIL"
+                };
+                let pages = match column.num_pages {
+                    1 => "1 page".to_string(),
+                    n => format!("{n} pages"),
+                };
+                writeln!(
+                    f,
+                    "    {:width$}  {pages:9}  {} with CRC  {status}",
+                    column.path, column.num_crcs
+                )?;
+                for issue in column.issues.iter() {
+                    writeln!(f, "      - {issue}")?;
+                }
+            }
+        }
+        match self.num_issues() {
+            0 => writeln!(f, "PASS"),
+            1 => writeln!(f, "FAIL: 1 issue"),
+            n => writeln!(f, "FAIL: {n} issues"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, sync::Arc};
+
+    use arrow::array::{ArrayRef, Float16Array, Int32Array, RecordBatch, StringArray};
+    use parquet::arrow::ArrowWriter;
+
+    use super::*;
+
+    const UNSIGNED: Order = Order {
+        sort_order: SortOrder::UNSIGNED,
+        float16: false,
+    };
+    const SIGNED: Order = Order {
+        sort_order: SortOrder::SIGNED,
+        float16: false,
+    };
+    const FLOAT16: Order = Order {
+        sort_order: SortOrder::SIGNED,
+        float16: true,
+    };
+
+    fn half(value: f32) -> [u8; 2] {
+        f16::from_f32(value).to_le_bytes()
+    }
+
+    #[test]
+    fn compares_bytes() {
+        assert_eq!(
+            compare_bytes(b"b", b"ab", UNSIGNED),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(compare_bytes(b"a", b"ab", UNSIGNED), Some(Ordering::Less));
+        // Bytes past 0x7f are greater, unlike signed bytes.
+        assert_eq!(
+            compare_bytes(&[0x80], &[0x7f], UNSIGNED),
+            Some(Ordering::Greater)
+        );
+
+        // -1 < 1, and -1 in one byte equals -1 in two.
+        assert_eq!(
+            compare_bytes(&[0xff], &[0x01], SIGNED),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            compare_bytes(&[0xff], &[0xff, 0xff], SIGNED),
+            Some(Ordering::Equal)
+        );
+        // 256 > 255, whatever their lengths.
+        assert_eq!(
+            compare_bytes(&[0x01, 0x00], &[0x00, 0xff], SIGNED),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(
+            compare_bytes(&[0x01, 0x00], &[0xff], SIGNED),
+            Some(Ordering::Greater)
+        );
+        // -2 > -256.
+        assert_eq!(
+            compare_bytes(&[0xfe], &[0xff, 0x00], SIGNED),
+            Some(Ordering::Greater)
+        );
+
+        let undefined = Order {
+            sort_order: SortOrder::UNDEFINED,
+            float16: false,
+        };
+        assert_eq!(compare_bytes(b"a", b"b", undefined), None);
+    }
+
+    #[test]
+    fn compares_float16() {
+        // As bytes, -1 would be greater than 2.
+        assert_eq!(
+            compare_bytes(&half(-1.0), &half(2.0), SIGNED),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(
+            compare_bytes(&half(-1.0), &half(2.0), FLOAT16),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            compare_bytes(&half(-0.0), &half(0.0), FLOAT16),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(compare_bytes(&half(f32::NAN), &half(0.0), FLOAT16), None);
+        let nan = FixedLenByteArray::from(half(f32::NAN).to_vec());
+        assert!(nan.is_nan(FLOAT16));
+        assert!(!nan.is_nan(SIGNED));
+        assert_eq!(show_bytes(&half(1.5), FLOAT16), "1.5");
+    }
+
+    #[test]
+    fn validates_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.parquet");
+        let halves = [-1.5, 2.0, -0.5, 0.25].map(f16::from_f32);
+        let batch = RecordBatch::try_from_iter([
+            (
+                "id",
+                Arc::new(Int32Array::from(vec![Some(3), None, Some(-7), Some(1)])) as ArrayRef,
+            ),
+            (
+                "name",
+                Arc::new(StringArray::from(vec!["b", "é", "a", "c"])) as ArrayRef,
+            ),
+            (
+                "half",
+                Arc::new(Float16Array::from(halves.to_vec())) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+        let mut writer =
+            ArrowWriter::try_new(File::create(&path).unwrap(), batch.schema(), None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let validation = Validation::new(&path).unwrap();
+        assert!(validation.passed(), "{validation}");
+        assert_eq!(validation.row_groups.len(), 1);
+        assert_eq!(validation.row_groups[0].columns.len(), 3);
+        assert!(validation.to_string().ends_with("PASS\n"));
+
+        // As through files that can't be mapped.
+        let file = || File::open(&path).unwrap();
+        let read = Validation::check(validation.name.clone(), file(), file(), false).unwrap();
+        assert_eq!(read.to_string(), validation.to_string());
+    }
+}